@@ -1,6 +1,12 @@
-use clap::Parser;
-use compiler::{codegen, print_errors};
-use std::path::PathBuf;
+use crate::config::{Config, ModuleStyle};
+use crate::watch;
+use clap::{Parser, ValueEnum};
+use compiler::ast::canonical as can;
+use compiler::random::Generator;
+use compiler::{codegen, diagnostic, docs, export, format, import, print_errors};
+use server::chaos::Latency;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::{fs, io};
 
 #[derive(Parser, Debug)]
@@ -15,25 +21,189 @@ pub struct Cli {
 enum Command {
     /// Start a server that can be used a a mock server and displays documentation.
     #[command()]
+    ///
+    /// Settings, which are not given, are read from the `[server]`
+    /// table of the closest wrpc.toml.
     Server {
+        /// The spec to serve, instead of the only spec in the wrpc.toml.
+        #[arg()]
+        file: Option<PathBuf>,
+        /// The configuration to use, instead of the closest wrpc.toml.
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// The host to listen on [default: 127.0.0.1]
+        #[arg(long)]
+        host: Option<String>,
+        /// The port to listen on [default: 3000]
+        #[arg(long)]
+        port: Option<u16>,
+        /// A prefix for all routes, like `/api`.
+        #[arg(long)]
+        base_path: Option<String>,
+        /// An origin allowed to call the server from a browser, like
+        /// `http://localhost:5173`, or `*` for any origin.
+        #[arg(long = "cors-origin", value_name = "ORIGIN")]
+        cors_origins: Vec<String>,
+        /// Open the documentation in the browser.
+        #[arg(long)]
+        open: bool,
+        /// Delay every call by some milliseconds, like `200` or
+        /// `100..500` for a random delay in between.
+        #[arg(long, value_name = "MILLIS")]
+        latency: Option<Latency>,
+        /// The share of calls, that fail with the `--error-status`.
+        #[arg(long, value_name = "RATE")]
+        error_rate: Option<f64>,
+        /// The status of failed calls [default: 500]
+        #[arg(long, value_name = "STATUS")]
+        error_status: Option<u16>,
+        /// The share of calls, that are answered with a body cut off.
+        #[arg(long, value_name = "RATE")]
+        malformed_rate: Option<f64>,
+        /// The share of calls, that are never answered.
+        #[arg(long, value_name = "RATE")]
+        timeout_rate: Option<f64>,
+        /// Answer calls with the responses recorded by `wrpc proxy`,
+        /// if the request matches exactly.
+        #[arg(long, value_name = "FILE")]
+        replay: Option<PathBuf>,
+    },
+    /// Forward all calls to a real server and log every request and
+    /// response, that does not follow the spec.
+    #[command()]
+    Proxy {
         #[arg()]
         file: PathBuf,
+        /// The URL of the real server, like `http://localhost:8080`.
+        #[arg(long)]
+        upstream: String,
+        /// Append all calls to this file, so `wrpc server --replay`
+        /// can answer them later.
+        #[arg(long, value_name = "FILE")]
+        record: Option<PathBuf>,
+        /// The host to listen on.
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+        /// The port to listen on.
+        #[arg(long, default_value_t = 3000)]
+        port: u16,
     },
     #[command()]
     Check {
         #[arg()]
         file: PathBuf,
+        /// The format of the reported errors.
+        #[arg(long, value_enum, default_value_t = MessageFormat::Human)]
+        message_format: MessageFormat,
+        /// Check the file again, whenever it changes.
+        #[arg(long)]
+        watch: bool,
     },
-    // Does stuff
+    /// Print the parsed module, e.g. for other tools.
     #[command()]
     Parse {
         #[arg()]
         file: PathBuf,
+        /// The format of the printed module.
+        #[arg(long, value_enum, default_value_t = ParseFormat::Debug)]
+        format: ParseFormat,
+        /// The stage of the compiler, the module is printed after.
+        #[arg(long, value_enum, default_value_t = Stage::Canonical)]
+        stage: Stage,
+        /// Include the region of every name in the JSON and YAML output.
+        #[arg(long)]
+        regions: bool,
+        /// The format of the reported errors.
+        #[arg(long, value_enum, default_value_t = MessageFormat::Human)]
+        message_format: MessageFormat,
     },
+    /// Generate code for a single target or, without a target, for
+    /// all targets in the wrpc.toml.
     #[command()]
     Gen {
         #[command(subcommand)]
-        lang: Lang,
+        lang: Option<Lang>,
+        /// The configuration to use, instead of the closest wrpc.toml.
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// The format of the reported errors.
+        #[arg(long, global = true, value_enum, default_value_t = MessageFormat::Human)]
+        message_format: MessageFormat,
+        /// Generate the code again, whenever a spec changes.
+        #[arg(long, global = true)]
+        watch: bool,
+        /// Only check, if the generated files in the output are up to
+        /// date, without writing them. Prints a diff of all stale files.
+        #[arg(long, global = true, conflicts_with = "watch")]
+        check: bool,
+    },
+    /// Format the given files according to the wRPC style.
+    #[command()]
+    Fmt {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Only check, if the files are formatted, without writing them.
+        #[arg(long)]
+        check: bool,
+    },
+    /// Render the documentation of a spec, like `wrpc server` shows it.
+    #[command()]
+    Docs {
+        #[arg()]
+        file: PathBuf,
+        /// Write a static site with a page per service and a page of
+        /// all types to this directory, instead of printing a single
+        /// page. Markdown is written to a single file named after
+        /// the spec, like `api.md`.
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// The format of the documentation.
+        #[arg(long, value_enum, default_value_t = DocsFormat::Html)]
+        format: DocsFormat,
+        /// The format of the reported errors.
+        #[arg(long, value_enum, default_value_t = MessageFormat::Human)]
+        message_format: MessageFormat,
+    },
+    /// Translate the spec of another format into a wRPC spec.
+    #[command()]
+    Import {
+        #[command(subcommand)]
+        format: ImportFormat,
+    },
+    /// Start a language server for wRPC files, communicating over stdio.
+    #[command()]
+    Lsp,
+    /// Print random values of a record or enum, one JSON value per
+    /// line. The values satisfy the constraints of the spec, where
+    /// possible.
+    #[command()]
+    Sample {
+        /// The name of a record or enum without type parameters.
+        #[arg()]
+        name: String,
+        /// The spec declaring the type, instead of the specs of the
+        /// closest wrpc.toml.
+        #[arg(long)]
+        spec: Option<PathBuf>,
+        /// The number of values to print.
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+        /// The seed of the generator, the same seed prints the same values.
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+}
+
+#[derive(Parser, Debug, Clone)]
+enum ImportFormat {
+    /// Import an OpenAPI 3.0 or 3.1 document in JSON or YAML. Prints
+    /// everything, that could not be translated, to stderr.
+    Openapi {
+        #[arg()]
+        file: PathBuf,
+        /// The path of the resulting spec, instead of printing it.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 }
 
@@ -54,6 +224,9 @@ enum Lang {
         /// The output path of the resulting files
         #[arg(short, long)]
         output: Option<PathBuf>,
+        /// How the client imports the generated models.
+        #[arg(long, value_enum, default_value_t = ModuleStyle::Ts)]
+        module_style: ModuleStyle,
     },
     /// Generate a Typescript Client.
     Kotlin {
@@ -65,11 +238,213 @@ enum Lang {
         #[arg(short, long)]
         package: String,
     },
+    /// Generate an OpenAPI 3.1 document, where every method is a
+    /// POST operation.
+    Openapi {
+        #[arg()]
+        file: PathBuf,
+        /// The output path of the resulting `openapi.json`
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// The title of the API, which defaults to the name of the spec.
+        #[arg(long)]
+        title: Option<String>,
+        /// The version of the API.
+        #[arg(long, default_value = DEFAULT_API_VERSION)]
+        api_version: String,
+    },
+    /// Generate a JSON Schema (draft 2020-12) for every record and
+    /// enum, like `Person.schema.json`.
+    Jsonschema {
+        #[arg()]
+        file: PathBuf,
+        /// The output path of the resulting files
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Write all types into a single file named after the spec,
+        /// like `api.schema.json`.
+        #[arg(long)]
+        bundle: bool,
+    },
+    /// Generate code with an external plugin, see docs/plugins.md.
+    Plugin {
+        #[arg()]
+        file: PathBuf,
+        /// The output path of the resulting files
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// The plugin executable, like `wrpc-gen-elixir`.
+        #[arg(long)]
+        exec: PathBuf,
+        /// An option for the plugin, like `--opt app=my_app`.
+        #[arg(long = "opt", value_name = "KEY=VALUE", value_parser = parse_plugin_option)]
+        options: Vec<(String, String)>,
+    },
+}
+
+/// The version of an API in the generated OpenAPI document, unless
+/// another one is given.
+pub const DEFAULT_API_VERSION: &str = "1.0.0";
+
+/// Returns the default title of an API, which is the name of its spec,
+/// like `api` for `specs/api.wrpc`.
+pub fn api_title(spec: &Path) -> String {
+    spec.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "API".to_string())
+}
+
+fn parse_plugin_option(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("`{value}` should have the form KEY=VALUE"))
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum ParseFormat {
+    /// A versioned JSON document.
+    Json,
+    /// The same document as `json`, but as YAML.
+    Yaml,
+    /// The Rust debug representation, which may change at any time.
+    Debug,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum DocsFormat {
+    /// A page like `wrpc server` shows or a static site.
+    Html,
+    /// A single Markdown document, e.g. for a wiki.
+    Markdown,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum Stage {
+    /// The module as written in the file.
+    Source,
+    /// The module after resolving annotations and constraints.
+    Canonical,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum MessageFormat {
+    /// Colored reports, meant to be read by humans.
+    Human,
+    /// Reports without any colors.
+    Text,
+    /// A JSON document with all diagnostics.
+    Json,
+    /// A SARIF log, as understood by many CI systems.
+    Sarif,
+}
+
+impl From<MessageFormat> for diagnostic::MessageFormat {
+    fn from(value: MessageFormat) -> Self {
+        match value {
+            MessageFormat::Human => diagnostic::MessageFormat::Human,
+            MessageFormat::Text => diagnostic::MessageFormat::Text,
+            MessageFormat::Json => diagnostic::MessageFormat::Json,
+            MessageFormat::Sarif => diagnostic::MessageFormat::Sarif,
+        }
+    }
+}
+
+impl Lang {
+    /// Returns the spec and the [`codegen::command::Command`] to generate it.
+    fn into_command(self) -> (PathBuf, codegen::command::Command) {
+        match self {
+            Lang::Rust { file, output } => {
+                let options = codegen::command::RustOptions {
+                    print: output.is_none(),
+                    output,
+                };
+                (file, codegen::command::Command::Rust(options))
+            }
+            Lang::Ts {
+                file,
+                output,
+                module_style,
+            } => {
+                let options = codegen::command::TypescriptOptions {
+                    print: output.is_none(),
+                    output,
+                    module_style: module_style.into(),
+                };
+                (file, codegen::command::Command::Typescript(options))
+            }
+            Lang::Kotlin {
+                file,
+                output,
+                package,
+            } => {
+                let options = codegen::command::KotlinOptions {
+                    print: output.is_none(),
+                    package,
+                    output,
+                };
+                (file, codegen::command::Command::Kotlin(options))
+            }
+            Lang::Openapi {
+                file,
+                output,
+                title,
+                api_version,
+            } => {
+                let options = codegen::command::OpenapiOptions {
+                    print: output.is_none(),
+                    output,
+                    title: title.unwrap_or_else(|| api_title(&file)),
+                    version: api_version,
+                };
+                (file, codegen::command::Command::Openapi(options))
+            }
+            Lang::Jsonschema {
+                file,
+                output,
+                bundle,
+            } => {
+                let options = codegen::command::JsonschemaOptions {
+                    print: output.is_none(),
+                    output,
+                    bundle: bundle.then(|| api_title(&file)),
+                };
+                (file, codegen::command::Command::Jsonschema(options))
+            }
+            Lang::Plugin {
+                file,
+                output,
+                exec,
+                options,
+            } => {
+                let options = codegen::command::PluginOptions {
+                    print: output.is_none(),
+                    output,
+                    exec,
+                    options: options.into_iter().collect(),
+                };
+                (file, codegen::command::Command::Plugin(options))
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum Error {
     Io(io::Error),
+    Unformatted(Vec<PathBuf>),
+    /// The given file contains errors, which have already been reported.
+    Invalid,
+    /// The configuration at the path could not be found or read.
+    Config(PathBuf, String),
+    /// The given number of generated files are out of date.
+    Stale(usize),
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(value: serde_yaml::Error) -> Self {
+        Error::Io(io::Error::other(value))
+    }
 }
 
 impl From<io::Error> for Error {
@@ -84,73 +459,507 @@ pub fn parse() -> Cli {
 
 pub async fn run(cli: Cli) -> Result<(), Error> {
     match cli.command {
-        Command::Server { file } => server::run(file).await,
-        Command::Check { file } => {
+        Command::Server {
+            file,
+            config,
+            host,
+            port,
+            base_path,
+            cors_origins,
+            open,
+            latency,
+            error_rate,
+            error_status,
+            malformed_rate,
+            timeout_rate,
+            replay,
+        } => {
+            let config = match config {
+                Some(path) => Some(path),
+                // A spec can be served without any configuration.
+                None if file.is_some() => Config::find().ok(),
+                None => Some(Config::find()?),
+            };
+            let (config_path, config) = match config {
+                Some(path) => {
+                    let config = Config::load(&path)?;
+                    (path, Some(config))
+                }
+                None => (PathBuf::new(), None),
+            };
+
+            let file = match (file, &config) {
+                (Some(file), _) => file,
+                (None, Some(config)) if config.specs.len() == 1 => config.specs[0].clone(),
+                (None, _) => {
+                    let message = "pass the spec to serve, since there is not exactly one";
+                    return Err(Error::Config(config_path, message.to_string()));
+                }
+            };
+
+            let settings = config.map(|config| config.server).unwrap_or_default();
+            let mut chaos = settings.chaos;
+            let global = &mut chaos.global;
+            global.latency = latency.or(global.latency);
+            global.error_rate = error_rate.unwrap_or(global.error_rate);
+            global.error_status = error_status.unwrap_or(global.error_status);
+            global.malformed_rate = malformed_rate.unwrap_or(global.malformed_rate);
+            global.timeout_rate = timeout_rate.unwrap_or(global.timeout_rate);
+
+            let defaults = server::Options::default();
+            let options = server::Options {
+                host: host.or(settings.host).unwrap_or(defaults.host),
+                port: port.or(settings.port).unwrap_or(defaults.port),
+                base_path: base_path
+                    .or(settings.base_path)
+                    .unwrap_or(defaults.base_path),
+                cors_origins: match cors_origins.is_empty() {
+                    true => settings.cors_origins,
+                    false => cors_origins,
+                },
+                open: open || settings.open,
+                chaos,
+                replay: replay.or(settings.replay),
+            };
+            server::run(file, options).await?
+        }
+        Command::Proxy {
+            file,
+            upstream,
+            record,
+            host,
+            port,
+        } => {
+            let options = server::Options {
+                host,
+                port,
+                ..server::Options::default()
+            };
+            server::proxy::run(file, upstream, options, record).await?
+        }
+        Command::Lsp => lsp::run().await,
+        Command::Check {
+            file,
+            message_format,
+            watch: false,
+        } => check(&file, message_format)?,
+        Command::Check {
+            file,
+            message_format,
+            watch: true,
+        } => watch::watch(std::slice::from_ref(&file), || {
+            check(&file, message_format)?;
+            if let MessageFormat::Human | MessageFormat::Text = message_format {
+                println!("No errors found in {}.", file.display());
+            }
+            Ok(())
+        })?,
+        Command::Parse {
+            file,
+            format,
+            stage,
+            regions,
+            message_format,
+        } => {
             let result = fs::read_to_string(&file)?;
             let str = result.as_str();
-            if let Err(error) = compiler::parse(Some(file.clone()), str) {
-                print_errors(&file, str, error);
+            let parsed = match stage {
+                Stage::Source => compiler::parse(Some(file.clone()), str)
+                    .map(|module| (format!("{:#?}", module), export::source(&module, regions))),
+                Stage::Canonical => compiler::compile(Some(file.clone()), str).map(|module| {
+                    (
+                        format!("{:#?}", module),
+                        export::canonical(&module, regions),
+                    )
+                }),
+            };
+
+            match parsed {
+                Ok((debug, value)) => match format {
+                    ParseFormat::Json => println!("{:#}", value),
+                    ParseFormat::Yaml => print!("{}", serde_yaml::to_string(&value)?),
+                    ParseFormat::Debug => println!("{}", debug),
+                },
+                Err(error) => return Err(report(&file, str, error, message_format)),
             }
         }
-        Command::Parse { file } => {
-            let result = fs::read_to_string(&file)?;
-            let str = result.as_str();
-            match compiler::compile(Some(file.clone()), str) {
-                Ok(module) => {
-                    println!("{:#?}", module)
+        Command::Gen {
+            lang: Some(lang),
+            message_format,
+            watch,
+            check,
+            ..
+        } => {
+            let (file, cmd) = lang.into_command();
+            if check {
+                let Some(output) = cmd.output() else {
+                    let message = "--check requires an --output to compare with";
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+                };
+
+                let module = compile_file(&file, message_format)?;
+                let files = codegen::render(&module, &cmd)?;
+                return check_generated(&[(output, &files)]);
+            }
+
+            let generate = || -> Result<Vec<PathBuf>, Error> {
+                let module = compile_file(&file, message_format)?;
+                Ok(codegen::generate(&module, &cmd)?)
+            };
+
+            if watch {
+                watch::watch(std::slice::from_ref(&file), || print_written(generate()?))?;
+            } else {
+                generate()?;
+            }
+        }
+        Command::Gen {
+            lang: None,
+            config,
+            message_format,
+            watch,
+            check,
+        } => {
+            let path = match config {
+                Some(path) => path,
+                None => Config::find()?,
+            };
+
+            if check {
+                check_all(&path, message_format)?;
+            } else if watch {
+                let mut files = Config::load(&path)?.specs;
+                files.push(path.clone());
+                watch::watch(&files, || {
+                    print_written(generate_all(&path, message_format)?)
+                })?;
+            } else {
+                generate_all(&path, message_format)?;
+            }
+        }
+        Command::Sample {
+            name,
+            spec,
+            count,
+            seed,
+        } => {
+            let specs = match spec {
+                Some(spec) => vec![spec],
+                None => Config::load(&Config::find()?)?.specs,
+            };
+
+            let mut modules = vec![];
+            for spec in &specs {
+                modules.push(compile_file(spec, MessageFormat::Human)?);
+            }
+
+            let (module, type_) = modules
+                .iter()
+                .find_map(|module| Some((module, sample_type(module, &name)?)))
+                .ok_or_else(|| {
+                    let message = format!("There is no record or enum `{name}` in the spec");
+                    io::Error::new(io::ErrorKind::NotFound, message)
+                })?;
+
+            let type_ = type_?;
+            let mut generator = Generator::new(module, seed);
+            for _ in 0..count {
+                println!("{}", generator.generate(&type_));
+            }
+        }
+        Command::Docs {
+            file,
+            out,
+            format,
+            message_format,
+        } => {
+            let module = compile_file(&file, message_format)?;
+            let pages = match (format, &out) {
+                (DocsFormat::Html, Some(_)) => docs::render_site(&module),
+                (DocsFormat::Html, None) => vec![("".to_string(), docs::render(&module))],
+                (DocsFormat::Markdown, _) => {
+                    let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+                    vec![(format!("{stem}.md"), docs::render_markdown(&module))]
                 }
+            };
 
-                Err(error) => {
-                    print_errors(&file, str, error);
+            let Some(out) = out else {
+                for (_, page) in pages {
+                    print!("{page}");
                 }
+                return Ok(());
+            };
+
+            fs::create_dir_all(&out)?;
+            let mut written = vec![];
+            for (name, page) in pages {
+                let path = out.join(name);
+                fs::write(&path, page)?;
+                written.push(path);
             }
+            print_written(written)?;
         }
-        Command::Gen { lang } => {
-            let file = match &lang {
-                Lang::Rust { file, .. } => file,
-                Lang::Ts { file, .. } => file,
-                Lang::Kotlin { file, .. } => file,
+        Command::Import {
+            format: ImportFormat::Openapi { file, output },
+        } => {
+            let content = fs::read_to_string(&file)?;
+            let extension = file.extension().and_then(|extension| extension.to_str());
+            let document: import::openapi::Document = match extension {
+                Some("yaml" | "yml") => serde_yaml::from_str(&content)?,
+                _ => serde_json::from_str(&content).map_err(io::Error::other)?,
             };
 
-            let output = match &lang {
-                Lang::Rust { output, .. } => output,
-                Lang::Ts { output, .. } => output,
-                Lang::Kotlin { output, .. } => output,
-            };
+            let import = import::openapi::import(&document)
+                .map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))?;
+            let spec = format::format_module(&import.module);
+            match output {
+                Some(output) => {
+                    fs::write(&output, spec)?;
+                    print_written(vec![output])?;
+                }
+                None => print!("{spec}"),
+            }
 
-            let result = fs::read_to_string(&file)?;
-            let str = result.as_str();
-            let print = output.is_none();
-            match compiler::compile(Some(file.clone()), str) {
-                Ok(module) => {
-                    let cmd = match &lang {
-                        Lang::Rust { .. } => codegen::command::Command::Rust,
-                        Lang::Ts { .. } => {
-                            let options = codegen::command::TypescriptOptions {
-                                print,
-                                output: output.clone(),
-                            };
-                            codegen::command::Command::Typescript(options)
-                        }
-                        Lang::Kotlin { package, .. } => {
-                            let options = codegen::command::KotlinOptions {
-                                print,
-                                package: package.clone(),
-                                output: output.clone(),
-                            };
-                            codegen::command::Command::Kotlin(options)
-                        }
-                    };
-
-                    codegen::generate(&module, &cmd)?;
+            // The report goes to stderr, so the printed spec can be
+            // redirected into a file.
+            if !import.unsupported.is_empty() {
+                eprintln!(
+                    "\n{} part(s) of {} could not be translated:\n",
+                    import.unsupported.len(),
+                    file.display()
+                );
+                for unsupported in &import.unsupported {
+                    eprintln!("{}\n    {}", unsupported.pointer, unsupported.message);
                 }
+            }
+        }
+        Command::Fmt { files, check } => {
+            let mut unformatted = vec![];
+            for file in files {
+                let result = fs::read_to_string(&file)?;
+                let str = result.as_str();
+                match compiler::format(Some(file.clone()), str) {
+                    Ok(formatted) if formatted == str => {}
+                    Ok(_) if check => {
+                        println!("Would reformat: {}", file.display());
+                        unformatted.push(file);
+                    }
+                    Ok(formatted) => fs::write(&file, formatted)?,
+                    Err(error) => {
+                        print_errors(&file, str, error);
+                        unformatted.push(file);
+                    }
+                }
+            }
+
+            if !unformatted.is_empty() {
+                return Err(Error::Unformatted(unformatted));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check(file: &Path, format: MessageFormat) -> Result<(), Error> {
+    let source = fs::read_to_string(file)?;
+    let (_, warnings) = compiler::compile_with_warnings(Some(file.to_path_buf()), &source)
+        .map_err(|error| report(file, &source, error, format))?;
+
+    // Warnings are printed, but do not fail the check.
+    let human = matches!(format, MessageFormat::Human | MessageFormat::Text);
+    if !warnings.is_empty() || !human {
+        println!(
+            "{}",
+            diagnostic::render_warnings(file, &source, &warnings, format.into())
+        );
+    }
+
+    Ok(())
+}
+
+/// Generate all targets of the configuration at the given path and
+/// return the paths of all written files.
+fn generate_all(path: &Path, format: MessageFormat) -> Result<Vec<PathBuf>, Error> {
+    let mut written = vec![];
+    for (cmd, files) in render_all(path, format)? {
+        written.extend(codegen::emit(&cmd, &files)?);
+    }
+
+    Ok(written)
+}
+
+/// Render all targets of the configuration at the given path for
+/// each of their specs, without writing anything.
+///
+/// Fails, if two of them would write the same file, e.g. a target
+/// without a `spec` in a configuration with multiple specs.
+fn render_all(
+    path: &Path,
+    format: MessageFormat,
+) -> Result<Vec<(codegen::command::Command, Vec<codegen::File>)>, Error> {
+    let config = Config::load(path)?;
+    let mut rendered = vec![];
+    let mut owners: HashMap<PathBuf, &Path> = HashMap::new();
+    for spec in &config.specs {
+        let module = compile_file(spec, format)?;
+        for target in config.targets_for(spec) {
+            let cmd = target.to_command(spec);
+            let files = codegen::render(&module, &cmd)?;
+            for file in &files {
+                let Some(output) = cmd.output() else {
+                    continue;
+                };
 
-                Err(error) => {
-                    print_errors(file, str, error);
+                let file = output.join(&file.path);
+                if let Some(other) = owners.insert(file.clone(), spec) {
+                    let message = format!(
+                        "{} would be generated for {} and for {}. Give the targets different outputs or restrict them to a single `spec`.",
+                        file.display(),
+                        other.display(),
+                        spec.display(),
+                    );
+                    return Err(Error::Config(path.to_path_buf(), message));
                 }
             }
+
+            rendered.push((cmd, files));
         }
     }
 
+    Ok(rendered)
+}
+
+/// Check all targets of the configuration at the given path, which
+/// have an output. Targets printing their code are skipped.
+fn check_all(path: &Path, format: MessageFormat) -> Result<(), Error> {
+    let rendered = render_all(path, format)?;
+    let outputs = rendered
+        .iter()
+        .filter_map(|(cmd, files)| Some((cmd.output()?, files.as_slice())))
+        .collect::<Vec<_>>();
+    check_generated(&outputs)
+}
+
+/// Compare the given files with their output directories and print a
+/// unified diff for every stale file and all orphaned files.
+fn check_generated(outputs: &[(&Path, &[codegen::File])]) -> Result<(), Error> {
+    let drift = codegen::compare_files(outputs)?;
+    for stale in &drift.stale {
+        let current = stale.current.as_deref().unwrap_or("");
+        let path = stale.path.display().to_string();
+        let old = match stale.current {
+            Some(_) => format!("a/{path}"),
+            None => "/dev/null".to_string(),
+        };
+        let diff = similar::TextDiff::from_lines(current, &stale.expected);
+        print!("{}", diff.unified_diff().header(&old, &format!("b/{path}")));
+    }
+
+    for orphan in &drift.orphaned {
+        println!("Not generated anymore: {}", orphan.display());
+    }
+
+    match drift.stale.len() + drift.orphaned.len() {
+        0 => Ok(()),
+        count => Err(Error::Stale(count)),
+    }
+}
+
+/// Returns the type of the record or enum with the given name, or an
+/// error, if it has type parameters.
+fn sample_type(module: &can::Module, name: &str) -> Option<Result<can::Type, io::Error>> {
+    let type_variables = match (module.records.get(name), module.enums.get(name)) {
+        (Some(record), _) => &record.type_variables,
+        (None, Some(enum_)) => &enum_.type_variables,
+        (None, None) => return None,
+    };
+
+    match type_variables.is_empty() {
+        true => Some(Ok(can::Type::Ref(name.to_string(), vec![]))),
+        false => {
+            let message = format!("`{name}` has type parameters and cannot be sampled");
+            Some(Err(io::Error::new(io::ErrorKind::InvalidInput, message)))
+        }
+    }
+}
+
+fn print_written(files: Vec<PathBuf>) -> Result<(), Error> {
+    for file in &files {
+        println!("Wrote {}", file.display());
+    }
+
+    if files.is_empty() {
+        println!("All generated files are up to date.");
+    }
+
     Ok(())
 }
+
+/// Read and compile the given file and report any errors in the given format.
+///
+/// Warnings are printed to stderr, so they do not end up in printed
+/// code or documentation.
+fn compile_file(file: &Path, format: MessageFormat) -> Result<can::Module, Error> {
+    let source = fs::read_to_string(file)?;
+    let (module, warnings) = compiler::compile_with_warnings(Some(file.to_path_buf()), &source)
+        .map_err(|error| report(file, &source, error, format))?;
+
+    if !warnings.is_empty() {
+        eprintln!(
+            "{}",
+            diagnostic::render_warnings(file, &source, &warnings, format.into())
+        );
+    }
+
+    Ok(module)
+}
+
+/// Print the given error in the given format and return an
+/// [`Error::Invalid`].
+fn report(
+    file: &Path,
+    source: &str,
+    error: compiler::error::Error,
+    format: MessageFormat,
+) -> Error {
+    println!(
+        "{}",
+        diagnostic::render(file, source, &error, format.into())
+    );
+    Error::Invalid
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use clap::Parser;
+
+    use crate::cli::{run, Cli, Error};
+
+    #[tokio::test]
+    async fn test_gen_rust_check() {
+        let dir = env::temp_dir().join(format!("wrpc-gen-rust-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let spec = dir.join("api.wrpc");
+        fs::write(&spec, "data Person {\n    name: String,\n}\n").unwrap();
+        let out = dir.join("server");
+        let gen = |check: bool| {
+            let mut args = vec!["wrpc", "gen", "rust", spec.to_str().unwrap()];
+            args.extend(["-o", out.to_str().unwrap()]);
+            if check {
+                args.push("--check");
+            }
+            Cli::try_parse_from(args).unwrap()
+        };
+
+        assert!(matches!(run(gen(true)).await, Err(Error::Stale(1))));
+        run(gen(false)).await.unwrap();
+        assert!(out.join("lib.rs").is_file());
+        assert!(run(gen(true)).await.is_ok());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
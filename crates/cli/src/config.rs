@@ -0,0 +1,383 @@
+//! The project configuration in `wrpc.toml`.
+//!
+//! ```toml
+//! specs = ["api.wrpc"]
+//!
+//! [[targets]]
+//! lang = "ts"
+//! output = "web/src/api"
+//! module_style = "js"
+//!
+//! [[targets]]
+//! lang = "kotlin"
+//! output = "server/src/main/kotlin/api"
+//! package = "com.example.api"
+//!
+//! [server]
+//! port = 8080
+//! cors_origins = ["http://localhost:5173"]
+//!
+//! [server.chaos.methods."PersonService/get"]
+//! error_rate = 0.5
+//! ```
+//!
+//! All paths are relative to the directory of the configuration file.
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use compiler::codegen::command::{
+    Command, JsonschemaOptions, KotlinOptions, ModuleStyle as TsModuleStyle, OpenapiOptions,
+    PluginOptions, RustOptions, TypescriptOptions,
+};
+use serde::Deserialize;
+use server::chaos::Chaos;
+
+use crate::cli::{api_title, Error, DEFAULT_API_VERSION};
+
+pub const FILE_NAME: &str = "wrpc.toml";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The specs to generate code for.
+    pub specs: Vec<PathBuf>,
+    #[serde(default)]
+    pub targets: Vec<Target>,
+    #[serde(default)]
+    pub server: ServerConfig,
+}
+
+/// The settings of `wrpc server`, which can be overridden on the
+/// command line.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub base_path: Option<String>,
+    #[serde(default)]
+    pub cors_origins: Vec<String>,
+    #[serde(default)]
+    pub open: bool,
+    /// The faults of the mock server, see [`Chaos`].
+    #[serde(default)]
+    pub chaos: Chaos,
+    /// The calls recorded by `wrpc proxy` to answer with.
+    pub replay: Option<PathBuf>,
+}
+
+/// A target, whose keys besides `spec` and `output` depend on its
+/// `lang`. Unknown keys are rejected, see [`TargetLang`].
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "toml::Table")]
+pub struct Target {
+    /// Only generate this target for the given spec, instead of all.
+    pub spec: Option<PathBuf>,
+    /// Print the generated code, if there is no output.
+    pub output: Option<PathBuf>,
+    pub lang: TargetLang,
+}
+
+impl TryFrom<toml::Table> for Target {
+    type Error = toml::de::Error;
+
+    fn try_from(mut table: toml::Table) -> Result<Self, Self::Error> {
+        let spec = table
+            .remove("spec")
+            .map(|spec| spec.try_into())
+            .transpose()?;
+        let output = table
+            .remove("output")
+            .map(|output| output.try_into())
+            .transpose()?;
+
+        Ok(Target {
+            spec,
+            output,
+            lang: toml::Value::Table(table).try_into()?,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "lang", rename_all = "lowercase", deny_unknown_fields)]
+pub enum TargetLang {
+    Rust,
+    Ts {
+        #[serde(default)]
+        module_style: ModuleStyle,
+    },
+    Kotlin {
+        package: String,
+    },
+    Openapi {
+        /// The title of the API, which defaults to the name of the spec.
+        title: Option<String>,
+        /// The version of the API.
+        version: Option<String>,
+    },
+    Jsonschema {
+        /// Write all types into a single file named after the spec.
+        #[serde(default)]
+        bundle: bool,
+    },
+    Plugin {
+        exec: PathBuf,
+        #[serde(default)]
+        options: BTreeMap<String, String>,
+    },
+}
+
+/// How the generated TypeScript client imports its models.
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ModuleStyle {
+    /// `./models.ts`
+    #[default]
+    Ts,
+    /// `./models.js`
+    Js,
+    /// `./models`
+    Bare,
+}
+
+impl From<ModuleStyle> for TsModuleStyle {
+    fn from(value: ModuleStyle) -> Self {
+        match value {
+            ModuleStyle::Ts => TsModuleStyle::Ts,
+            ModuleStyle::Js => TsModuleStyle::Js,
+            ModuleStyle::Bare => TsModuleStyle::Bare,
+        }
+    }
+}
+
+impl Config {
+    /// Load the configuration at the given path and resolve all
+    /// paths relative to its directory.
+    pub fn load(path: &Path) -> Result<Config, Error> {
+        let content = fs::read_to_string(path)?;
+        let mut config: Config = toml::from_str(&content)
+            .map_err(|error| Error::Config(path.to_path_buf(), error.to_string()))?;
+
+        let root = path.parent().unwrap_or(Path::new(""));
+        for spec in config.specs.iter_mut() {
+            *spec = root.join(&spec);
+        }
+
+        for target in config.targets.iter_mut() {
+            target.spec = target.spec.as_ref().map(|spec| root.join(spec));
+            target.output = target.output.as_ref().map(|output| root.join(output));
+            if let TargetLang::Plugin { exec, .. } = &mut target.lang {
+                // A plain name is looked up in the PATH instead.
+                if exec.components().count() > 1 {
+                    *exec = root.join(&exec);
+                }
+            }
+        }
+
+        config.server.replay = config.server.replay.as_ref().map(|path| root.join(path));
+
+        Ok(config)
+    }
+
+    /// Find the configuration in the current directory or any of its parents.
+    pub fn find() -> Result<PathBuf, Error> {
+        let current = env::current_dir()?;
+        current
+            .ancestors()
+            .map(|dir| dir.join(FILE_NAME))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                let message =
+                    format!("Could not find a {FILE_NAME} in this or any parent directory");
+                Error::Config(current.join(FILE_NAME), message)
+            })
+    }
+
+    /// Returns all targets, that should be generated for the given spec.
+    pub fn targets_for<'a>(&'a self, spec: &'a Path) -> impl Iterator<Item = &'a Target> {
+        self.targets
+            .iter()
+            .filter(move |target| target.spec.as_deref().is_none_or(|other| other == spec))
+    }
+}
+
+impl Target {
+    /// Returns the command to generate this target for the given spec.
+    pub fn to_command(&self, spec: &Path) -> Command {
+        let print = self.output.is_none();
+        let output = self.output.clone();
+        match &self.lang {
+            TargetLang::Rust => Command::Rust(RustOptions { print, output }),
+            TargetLang::Ts { module_style } => Command::Typescript(TypescriptOptions {
+                print,
+                output,
+                module_style: (*module_style).into(),
+            }),
+            TargetLang::Kotlin { package } => Command::Kotlin(KotlinOptions {
+                print,
+                output,
+                package: package.clone(),
+            }),
+            TargetLang::Openapi { title, version } => Command::Openapi(OpenapiOptions {
+                print,
+                output,
+                title: title.clone().unwrap_or_else(|| api_title(spec)),
+                version: version.clone().unwrap_or(DEFAULT_API_VERSION.to_string()),
+            }),
+            TargetLang::Jsonschema { bundle } => Command::Jsonschema(JsonschemaOptions {
+                print,
+                output,
+                bundle: bundle.then(|| api_title(spec)),
+            }),
+            TargetLang::Plugin { exec, options } => Command::Plugin(PluginOptions {
+                print,
+                output,
+                exec: exec.clone(),
+                options: options.clone(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use compiler::codegen::command::Command;
+
+    use crate::config::{Config, ModuleStyle, TargetLang};
+
+    #[test]
+    fn test_targets_for_spec() {
+        let config: Config = toml::from_str(
+            r#"
+            specs = ["api.wrpc", "admin.wrpc"]
+
+            [[targets]]
+            lang = "ts"
+            output = "web/api"
+            module_style = "bare"
+
+            [[targets]]
+            lang = "kotlin"
+            spec = "admin.wrpc"
+            package = "com.example"
+            "#,
+        )
+        .unwrap();
+
+        let targets = config
+            .targets_for(Path::new("api.wrpc"))
+            .collect::<Vec<_>>();
+        assert_eq!(targets.len(), 1);
+        assert!(matches!(
+            targets[0].lang,
+            TargetLang::Ts {
+                module_style: ModuleStyle::Bare
+            }
+        ));
+        assert_eq!(config.targets_for(Path::new("admin.wrpc")).count(), 2);
+    }
+
+    #[test]
+    fn test_unknown_target_keys_are_rejected() {
+        let result = toml::from_str::<Config>(
+            r#"
+            specs = ["api.wrpc"]
+
+            [[targets]]
+            lang = "ts"
+            outpt = "web/api"
+            "#,
+        );
+
+        let error = result.expect_err("Expected an unknown key to be rejected");
+        assert!(error.to_string().contains("outpt"), "{error}");
+    }
+
+    #[test]
+    fn test_openapi_title_defaults_to_spec() {
+        let config: Config = toml::from_str(
+            r#"
+            specs = ["api.wrpc"]
+
+            [[targets]]
+            lang = "openapi"
+            output = "public"
+            "#,
+        )
+        .unwrap();
+
+        let command = config.targets[0].to_command(Path::new("specs/api.wrpc"));
+        let Command::Openapi(options) = command else {
+            panic!("Expected an OpenAPI command, but got {command:?}");
+        };
+        assert_eq!(options.title, "api");
+        assert_eq!(options.version, "1.0.0");
+    }
+
+    #[test]
+    fn test_rust_target_writes_to_output() {
+        let config: Config = toml::from_str(
+            r#"
+            specs = ["api.wrpc"]
+
+            [[targets]]
+            lang = "rust"
+            output = "server/src"
+            "#,
+        )
+        .unwrap();
+
+        let command = config.targets[0].to_command(Path::new("api.wrpc"));
+        assert!(!command.print());
+        assert_eq!(command.output(), Some(Path::new("server/src")));
+    }
+
+    #[test]
+    fn test_server_settings() {
+        let config: Config = toml::from_str(r#"specs = ["api.wrpc"]"#).unwrap();
+        assert_eq!(config.server.port, None);
+        assert!(config.server.cors_origins.is_empty());
+
+        let config: Config = toml::from_str(
+            r#"
+            specs = ["api.wrpc"]
+
+            [server]
+            port = 8080
+            base_path = "/api"
+            cors_origins = ["http://localhost:5173"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.server.port, Some(8080));
+        assert_eq!(config.server.base_path.as_deref(), Some("/api"));
+        assert_eq!(config.server.cors_origins, vec!["http://localhost:5173"]);
+    }
+
+    #[test]
+    fn test_server_chaos() {
+        let config: Config = toml::from_str(
+            r#"
+            specs = ["api.wrpc"]
+
+            [server.chaos.global]
+            latency = { min = 100, max = 500 }
+
+            [server.chaos.methods."PersonService/get"]
+            error_rate = 0.5
+            error_status = 503
+            "#,
+        )
+        .unwrap();
+        let chaos = &config.server.chaos;
+        assert_eq!(chaos.global.latency.map(|latency| latency.max), Some(500));
+        assert_eq!(chaos.methods["PersonService/get"].error_status, 503);
+        assert_eq!(chaos.methods["PersonService/get"].error_rate, 0.5);
+    }
+}
@@ -1,13 +1,36 @@
 use crate::cli::Error;
 
 mod cli;
+mod config;
+mod watch;
 
 #[tokio::main]
 async fn main() {
     let cli = cli::parse();
     if let Err(error) = cli::run(cli).await {
-        match error {
-            Error::Io(error) => println!("{}", error),
-        }
+        let code = match error {
+            Error::Io(error) => {
+                eprintln!("{}", error);
+                2
+            }
+            Error::Unformatted(files) => {
+                eprintln!("{} file(s) are not formatted or invalid.", files.len());
+                1
+            }
+            Error::Config(path, message) => {
+                eprintln!("{}: {}", path.display(), message);
+                2
+            }
+            Error::Stale(count) => {
+                eprintln!(
+                    "{count} generated file(s) are out of date, run `wrpc gen` to update them."
+                );
+                1
+            }
+            // The errors have already been reported.
+            Error::Invalid => 1,
+        };
+
+        std::process::exit(code);
     }
 }
@@ -0,0 +1,117 @@
+//! Rerun a command, whenever one of its input files changes.
+use std::collections::HashSet;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use std::{fs, io};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use crate::cli::Error;
+
+/// Editors often write a file in multiple steps, which are
+/// collected into a single change.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Run the given function once and again, whenever one of the
+/// given files changes. This only returns, if the files cannot be
+/// watched.
+pub fn watch(files: &[PathBuf], mut run: impl FnMut() -> Result<(), Error>) -> Result<(), Error> {
+    let files = files
+        .iter()
+        .map(fs::canonicalize)
+        .collect::<Result<HashSet<PathBuf>, io::Error>>()?;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(io::Error::other)?;
+
+    // Watch the directories instead of the files, since many editors
+    // replace a file instead of writing to it.
+    let directories = files
+        .iter()
+        .filter_map(|file| file.parent())
+        .collect::<HashSet<&Path>>();
+    for directory in directories {
+        watcher
+            .watch(directory, RecursiveMode::NonRecursive)
+            .map_err(io::Error::other)?;
+    }
+
+    loop {
+        run_guarded(&mut run);
+        println!("Watching for changes, press Ctrl-C to stop.");
+
+        loop {
+            let event = receiver.recv().map_err(io::Error::other)?;
+            if is_relevant(&files, event) {
+                break;
+            }
+        }
+
+        while receiver.recv_timeout(DEBOUNCE).is_ok() {}
+    }
+}
+
+fn is_relevant(files: &HashSet<PathBuf>, event: notify::Result<notify::Event>) -> bool {
+    match event {
+        Ok(event) => {
+            !matches!(event.kind, EventKind::Access(_))
+                && event.paths.iter().any(|path| files.contains(path))
+        }
+        Err(_) => false,
+    }
+}
+
+/// Run the function once and report its errors. A panic, e.g. of the
+/// compiler on a half-written spec, is reported the same way, since
+/// the next change may fix it.
+fn run_guarded(run: &mut impl FnMut() -> Result<(), Error>) {
+    match panic::catch_unwind(AssertUnwindSafe(run)) {
+        Ok(result) => report(result),
+        // The panic hook has already printed the message.
+        Err(_) => eprintln!("The run failed unexpectedly, but the files are still watched."),
+    }
+}
+
+/// Report the errors of a single run, which should not stop watching.
+fn report(result: Result<(), Error>) {
+    match result {
+        Ok(()) => {}
+        Err(Error::Io(error)) => eprintln!("{error}"),
+        Err(Error::Config(path, message)) => eprintln!("{}: {message}", path.display()),
+        Err(Error::Unformatted(_)) | Err(Error::Invalid) | Err(Error::Stale(_)) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::Error;
+    use crate::watch::run_guarded;
+
+    #[test]
+    fn test_failing_runs_are_reported() {
+        let sources = [
+            "data P {\n    #(check (not))\n    x: String,\n}\n",
+            "data P {\n    x: String,\n}\n",
+        ];
+        let mut compiled = vec![];
+        for source in sources {
+            run_guarded(&mut || {
+                let result = compiler::compile(None, source).map_err(|_| Error::Invalid);
+                compiled.push(result.is_ok());
+                result.map(|_| ())
+            });
+        }
+        assert_eq!(compiled, vec![false, true]);
+
+        let mut runs = 0;
+        for _ in 0..2 {
+            run_guarded(&mut || {
+                runs += 1;
+                panic!("not implemented");
+            });
+        }
+        assert_eq!(runs, 2);
+    }
+}
@@ -1,9 +1,11 @@
 use std::collections::HashMap;
+use std::fmt;
 
 use crate::ast::source::Name;
 use crate::reporting::Region;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use super::constraints::Constraint;
 
@@ -71,12 +73,24 @@ pub struct Record {
     pub constraints: Vec<Constraint>,
     pub property_validation_order: Vec<String>,
     pub comment: Option<String>,
+    /// The JSON values of all `#(example ...)` annotations.
+    pub examples: Vec<Value>,
     pub name: Name,
     pub properties: Vec<Property>,
     pub type_variables: Vec<Name>,
 }
 
 impl Record {
+    pub fn as_type(&self) -> Type {
+        let types = self
+            .type_variables
+            .iter()
+            .map(|type_| Type::Ref(type_.value.clone(), vec![]))
+            .collect();
+
+        Type::Ref(self.name.value.clone(), types)
+    }
+
     pub fn get_validation_ordered_properties(&self) -> Vec<&Property> {
         let mut result = vec![];
         // This is super inefficient, but we don't care for now
@@ -99,6 +113,8 @@ pub struct Property {
     pub constraints: Vec<Constraint>,
     pub deps: Vec<String>,
     pub comment: Option<String>,
+    /// The JSON values of all `#(example ...)` annotations.
+    pub examples: Vec<Value>,
     pub name: Name,
     pub type_: Type,
 }
@@ -147,6 +163,10 @@ pub struct Service {
     pub comment: Option<String>,
     pub name: Name,
     pub methods: HashMap<String, Method>,
+    /// The record managed by this service, from `#(mock :crud Record)`.
+    /// The mock server keeps its values in memory, instead of
+    /// answering with random ones.
+    pub crud: Option<String>,
 }
 
 impl Service {
@@ -169,6 +189,9 @@ pub struct Method {
     pub annotations: Vec<Expr>,
     pub name: Name,
     pub comment: Option<String>,
+    /// The JSON values of all `#(example ...)` annotations, which are
+    /// examples of the return value.
+    pub examples: Vec<Value>,
     pub parameters: Vec<Parameter>,
     pub return_type: Option<Type>,
 }
@@ -198,6 +221,27 @@ pub enum Type {
     Ref(String, Vec<Type>),
 }
 
+impl fmt::Display for Type {
+    /// Display the type as it would be written in a spec.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::String => write!(f, "String"),
+            Type::Boolean => write!(f, "Boolean"),
+            Type::Int32 => write!(f, "Int32"),
+            Type::Int64 => write!(f, "Int64"),
+            Type::Float32 => write!(f, "Float32"),
+            Type::Float64 => write!(f, "Float64"),
+            Type::Map(key, value) => write!(f, "Map<{key}, {value}>"),
+            Type::Result(error, value) => write!(f, "Result<{error}, {value}>"),
+            Type::List(value) => write!(f, "List<{value}>"),
+            Type::Set(value) => write!(f, "Set<{value}>"),
+            Type::Option(value) => write!(f, "{value}?"),
+            Type::Ref(name, variables) if variables.is_empty() => write!(f, "{name}"),
+            Type::Ref(name, variables) => write!(f, "{name}<{}>", variables.iter().join(", ")),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum Expr {
     Boolean(Region, bool),
@@ -208,3 +252,17 @@ pub enum Expr {
     Map(Region, Vec<(Expr, Expr)>),
     List(Region, Vec<Expr>),
 }
+
+impl Expr {
+    pub fn region(&self) -> Region {
+        match self {
+            Expr::Boolean(region, _)
+            | Expr::Number(region, _)
+            | Expr::Keyword(region, _)
+            | Expr::String(region, _)
+            | Expr::Symbol(region, _)
+            | Expr::Map(region, _)
+            | Expr::List(region, _) => region.clone(),
+        }
+    }
+}
@@ -1,4 +1,7 @@
 use std::collections::HashSet;
+use std::fmt;
+
+use itertools::Itertools;
 
 use serde::{Deserialize, Serialize};
 
@@ -58,6 +61,94 @@ impl Constraint {
     }
 }
 
+impl Constraint {
+    /// Describe the constraint for humans, like `length ≤ 5` or `not
+    /// blank`. The `subject` is the property the constraint belongs
+    /// to, which is left out, where the description stays clear.
+    pub fn describe(&self, subject: Option<&str>) -> String {
+        self.describe_nested(subject, false)
+    }
+
+    fn describe_nested(&self, subject: Option<&str>, nested: bool) -> String {
+        let is_subject = |constraint: &Constraint| matches!(constraint, Constraint::Access(name) if Some(name.as_str()) == subject);
+        let join = |args: &[Constraint], separator: &str| {
+            args.iter()
+                .map(|arg| arg.describe_nested(subject, true))
+                .join(separator)
+        };
+        let group = |description: String| match nested {
+            true => format!("({description})"),
+            false => description,
+        };
+
+        match self {
+            Constraint::Lt(args) => join(args, " < "),
+            Constraint::Le(args) => join(args, " ≤ "),
+            Constraint::Gt(args) => join(args, " > "),
+            Constraint::Ge(args) => join(args, " ≥ "),
+            Constraint::Eq(args) => join(args, " = "),
+            Constraint::And(args) if nested => group(join(args, " and ")),
+            Constraint::And(args) => join(args, ", "),
+            Constraint::Or(args) => group(join(args, " or ")),
+            Constraint::Xor(args) => group(format!("exactly one of {}", join(args, ", "))),
+            Constraint::Len(arg) if is_subject(arg) => "length".to_string(),
+            Constraint::Len(arg) => format!("length of {}", arg.describe_nested(subject, true)),
+            Constraint::Blank(arg) if is_subject(arg) => "blank".to_string(),
+            Constraint::Blank(arg) => format!("{} is blank", arg.describe_nested(subject, true)),
+            Constraint::Not(arg) => match arg.as_ref() {
+                Constraint::Blank(arg) if is_subject(arg) => "not blank".to_string(),
+                Constraint::Blank(arg) => {
+                    format!("{} is not blank", arg.describe_nested(subject, true))
+                }
+                Constraint::Eq(args) => join(args, " ≠ "),
+                arg => format!("not {}", arg.describe_nested(subject, true)),
+            },
+            Constraint::Access(name) => name.clone(),
+            Constraint::Number(_)
+            | Constraint::String(_)
+            | Constraint::Boolean(_)
+            | Constraint::Map(_) => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Constraint {
+    /// Display the constraint as it would be written in a `#(check ...)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let call =
+            |f: &mut fmt::Formatter<'_>, name: &str, args: &[Constraint]| match args.is_empty() {
+                true => write!(f, "({name})"),
+                false => write!(f, "({name} {})", args.iter().join(" ")),
+            };
+
+        match self {
+            Constraint::Or(args) => call(f, "or", args),
+            Constraint::Lt(args) => call(f, "<", args),
+            Constraint::Eq(args) => call(f, "=", args),
+            Constraint::Le(args) => call(f, "<=", args),
+            Constraint::Gt(args) => call(f, ">", args),
+            Constraint::Ge(args) => call(f, ">=", args),
+            Constraint::And(args) => call(f, "and", args),
+            Constraint::Xor(args) => call(f, "xor", args),
+            Constraint::Len(arg) => write!(f, "(len {arg})"),
+            Constraint::Blank(arg) => write!(f, "(blank {arg})"),
+            Constraint::Not(arg) => write!(f, "(not {arg})"),
+            Constraint::Number(value) => write!(f, "{value}"),
+            Constraint::String(value) => write!(f, "{value:?}"),
+            Constraint::Boolean(value) => write!(f, "{value}"),
+            Constraint::Map(entries) => write!(
+                f,
+                "{{{}}}",
+                entries
+                    .iter()
+                    .map(|(key, value)| format!("{key} {value}"))
+                    .join(" ")
+            ),
+            Constraint::Access(name) => write!(f, ".{name}"),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum Expr {
     And(Vec<Expr>),
@@ -86,6 +177,39 @@ mod tests {
 
     #[test]
     fn test() {}
+
+    #[test]
+    fn test_describe() {
+        let spec = r#"
+#(check (or (= .country "DE") (and (< .min .max) (not (= .min 0)))))
+data Range {
+    #(check (<= (len .name) 5) (not (blank .name)))
+    name: String,
+    #(check (< 0 .min 150) (not (blank .name)))
+    min: Int32,
+    max: Int32,
+    country: String,
+}
+"#;
+        let module = crate::compile(None, spec).unwrap();
+        let record = &module.records["Range"];
+        let describe = |property: usize| {
+            let property = &record.properties[property];
+            property
+                .constraints
+                .iter()
+                .map(|constraint| constraint.describe(Some(&property.name.value)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        assert_eq!(describe(0), "length ≤ 5, not blank");
+        assert_eq!(describe(1), "0 < min < 150, name is not blank");
+        assert_eq!(
+            record.constraints[0].describe(None),
+            "country = \"DE\" or (min < max and min ≠ 0)"
+        );
+    }
 }
 
 // Impl
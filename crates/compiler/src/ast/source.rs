@@ -9,6 +9,8 @@ pub struct Module {
     pub doc_comment: Option<String>,
     pub version: String,
     pub declarations: Vec<Decl>,
+    /// A comment at the end of the file, that is not attached to any declaration.
+    pub trailing_comment: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -25,6 +27,8 @@ pub struct Data {
     pub name: Name,
     pub properties: Vec<Property>,
     pub type_variables: Vec<Name>,
+    /// A comment after the last property, that is not attached to any property.
+    pub trailing_comment: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -33,6 +37,8 @@ pub struct Property {
     pub doc_comment: Option<String>,
     pub name: Name,
     pub type_: Type,
+    /// Whether the source separates this from the previous property by an empty line.
+    pub blank_line_before: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -42,6 +48,8 @@ pub struct Enum {
     pub name: Name,
     pub variants: Vec<Variant>,
     pub type_variables: Vec<Name>,
+    /// A comment after the last variant, that is not attached to any variant.
+    pub trailing_comment: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -50,6 +58,10 @@ pub struct Variant {
     pub doc_comment: Option<String>,
     pub name: Name,
     pub properties: Vec<Property>,
+    /// A comment after the last property, that is not attached to any property.
+    pub trailing_comment: Option<String>,
+    /// Whether the source separates this from the previous variant by an empty line.
+    pub blank_line_before: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -69,6 +81,8 @@ pub struct Service {
     pub doc_comment: Option<String>,
     pub name: Name,
     pub methods: Vec<Method>,
+    /// A comment after the last method, that is not attached to any method.
+    pub trailing_comment: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -78,18 +92,22 @@ pub struct Method {
     pub name: Name,
     pub parameters: Vec<Parameter>,
     pub return_type: Option<Type>,
+    /// A comment after the last parameter, that is not attached to any parameter.
+    pub trailing_comment: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Parameter {
     pub annotations: Vec<Annotation>,
+    pub doc_comment: Option<String>,
     pub name: Name,
     pub type_: Type,
+    /// Whether the source separates this from the previous parameter by an empty line.
+    pub blank_line_before: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Name {
-    #[serde(skip_serializing)]
     pub region: Region,
     pub value: String,
 }
@@ -5,7 +5,10 @@ use crate::ast::constraints::Constraint;
 use crate::ast::source::Decl;
 use crate::ast::{canonical as can, source as src};
 use crate::error::canonicalize;
+use crate::links;
 use crate::reporting::Region;
+use crate::value;
+use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 
 pub fn canonicalize(module: &src::Module) -> Result<can::Module, Vec<canonicalize::Error>> {
@@ -58,17 +61,346 @@ pub fn canonicalize(module: &src::Module) -> Result<can::Module, Vec<canonicaliz
         }
     }
 
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut module = can::Module {
+        records,
+        services,
+        enums,
+    };
+
+    let mut errors = canonicalize_examples(&mut module);
+    errors.extend(canonicalize_mocks(&mut module));
     if errors.is_empty() {
-        Ok(can::Module {
-            records,
-            services,
-            enums,
-        })
+        Ok(module)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Convert the `#(example ...)` annotations of all records, properties
+/// and methods to JSON and check them against their types. Checking
+/// needs all declarations, so this happens after everything else.
+fn canonicalize_examples(module: &mut can::Module) -> Vec<canonicalize::Error> {
+    let declarations = module.clone();
+    let mut errors = vec![];
+
+    let records = module
+        .records
+        .values_mut()
+        .sorted_by_key(|record| record.name.value.clone());
+    for record in records {
+        let name = record.name.clone();
+        let type_ = record.as_type();
+        match parse_examples(&declarations, &record.annotations, Some(&type_)) {
+            Ok(examples) => record.examples = examples,
+            Err(annotation_errors) => errors.extend(annotation_errors.into_iter().map(|error| {
+                canonicalize::Error::BadRecord(
+                    name.clone(),
+                    canonicalize::Record::BadAnnotation(error),
+                )
+            })),
+        }
+
+        for property in record.properties.iter_mut() {
+            match parse_examples(&declarations, &property.annotations, Some(&property.type_)) {
+                Ok(examples) => property.examples = examples,
+                Err(annotation_errors) => {
+                    errors.extend(annotation_errors.into_iter().map(|error| {
+                        canonicalize::Error::BadRecord(
+                            name.clone(),
+                            canonicalize::Record::BadProperty(
+                                property.name.clone(),
+                                canonicalize::Property::BadAnnotation(error),
+                            ),
+                        )
+                    }))
+                }
+            }
+        }
+    }
+
+    let enums = module
+        .enums
+        .values_mut()
+        .sorted_by_key(|enum_| enum_.name.value.clone());
+    for enum_ in enums {
+        for variant in enum_.variants.iter_mut() {
+            for property in variant.properties.iter_mut() {
+                match parse_examples(&declarations, &property.annotations, Some(&property.type_)) {
+                    Ok(examples) => property.examples = examples,
+                    Err(annotation_errors) => {
+                        errors.extend(annotation_errors.into_iter().map(|error| {
+                            canonicalize::Error::BadEnum(
+                                enum_.name.clone(),
+                                canonicalize::Enum::BadVariant(
+                                    variant.name.clone(),
+                                    canonicalize::Variant::BadProperty(
+                                        property.name.clone(),
+                                        canonicalize::Property::BadAnnotation(error),
+                                    ),
+                                ),
+                            )
+                        }))
+                    }
+                }
+            }
+        }
+    }
+
+    let services = module
+        .services
+        .values_mut()
+        .sorted_by_key(|service| service.name.value.clone());
+    for service in services {
+        let methods = service
+            .methods
+            .values_mut()
+            .sorted_by_key(|method| method.name.value.clone());
+        for method in methods {
+            let type_ = method.return_type.as_ref();
+            match parse_examples(&declarations, &method.annotations, type_) {
+                Ok(examples) => method.examples = examples,
+                Err(annotation_errors) => {
+                    errors.extend(annotation_errors.into_iter().map(|error| {
+                        canonicalize::Error::BadService(
+                            service.name.clone(),
+                            canonicalize::Service::BadMethod(
+                                method.name.clone(),
+                                canonicalize::Method::BadAnnotation(error),
+                            ),
+                        )
+                    }))
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+/// Resolve the `#(mock :crud Record)` annotations of all services.
+fn canonicalize_mocks(module: &mut can::Module) -> Vec<canonicalize::Error> {
+    let records = module.records.clone();
+    let mut errors = vec![];
+    let services = module
+        .services
+        .values_mut()
+        .sorted_by_key(|service| service.name.value.clone());
+    for service in services {
+        for annotation in &service.annotations {
+            let Expr::List(region, expressions) = annotation else {
+                continue;
+            };
+
+            let [Expr::Symbol(_, value), args @ ..] = expressions.as_slice() else {
+                continue;
+            };
+
+            if value != "mock" {
+                continue;
+            }
+
+            match parse_mock(&records, region, args) {
+                Ok(record) => service.crud = Some(record),
+                Err(error) => errors.push(canonicalize::Error::BadService(
+                    service.name.clone(),
+                    canonicalize::Service::BadAnnotation(error),
+                )),
+            }
+        }
+    }
+
+    errors
+}
+
+/// Returns a warning for every link in a doc comment, which does not
+/// point to a record, enum, service or method of the module, see
+/// [`links`](crate::links).
+pub fn check_links(module: &can::Module) -> Vec<canonicalize::Warning> {
+    let mut warnings = vec![];
+    let mut check = |comment: &Option<String>, name: &src::Name, location: String| {
+        let Some(comment) = comment else {
+            return;
+        };
+        for link in links::parse(comment) {
+            if !links::resolves(module, &link) {
+                warnings.push(canonicalize::Warning::BrokenLink {
+                    region: name.region.clone(),
+                    location: location.clone(),
+                    target: link.target,
+                });
+            }
+        }
+    };
+
+    for record in module.records.values() {
+        let name = &record.name.value;
+        check(&record.comment, &record.name, format!("`{name}`"));
+        for property in &record.properties {
+            let location = format!("the property `{}` of `{name}`", property.name.value);
+            check(&property.comment, &property.name, location);
+        }
+    }
+
+    for enum_ in module.enums.values() {
+        let name = &enum_.name.value;
+        check(&enum_.comment, &enum_.name, format!("`{name}`"));
+        for variant in &enum_.variants {
+            let variant_name = &variant.name.value;
+            let location = format!("the variant `{name}.{variant_name}`");
+            check(&variant.comment, &variant.name, location);
+            for property in &variant.properties {
+                let location = format!(
+                    "the property `{}` of `{name}.{variant_name}`",
+                    property.name.value
+                );
+                check(&property.comment, &property.name, location);
+            }
+        }
+    }
+
+    for service in module.services.values() {
+        let name = &service.name.value;
+        check(&service.comment, &service.name, format!("`{name}`"));
+        for method in service.methods.values() {
+            let method_name = &method.name.value;
+            let location = format!("the method `{name}.{method_name}`");
+            check(&method.comment, &method.name, location);
+            for parameter in &method.parameters {
+                let location = format!(
+                    "the parameter `{}` of `{name}.{method_name}`",
+                    parameter.name.value
+                );
+                check(&parameter.comment, &parameter.name, location);
+            }
+        }
+    }
+
+    warnings.sort_by_key(|warning| warning.region());
+    warnings
+}
+
+fn parse_mock(
+    records: &HashMap<String, can::Record>,
+    region: &Region,
+    args: &[Expr],
+) -> Result<String, canonicalize::Annotation> {
+    let bad_mock = |region: &Region, message: String| {
+        canonicalize::Annotation::BadMock(region.clone(), message)
+    };
+
+    let name = match args {
+        [Expr::Keyword(_, kind), Expr::Symbol(_, name) | Expr::Keyword(_, name)]
+            if kind == "crud" =>
+        {
+            name
+        }
+        _ => {
+            let message = "A mock needs the record it manages, like `#(mock :crud Person)`.";
+            return Err(bad_mock(region, message.to_string()));
+        }
+    };
+
+    let region = args[1].region();
+    let Some(record) = records.get(name) else {
+        return Err(bad_mock(
+            &region,
+            format!("I cannot find a record `{name}`."),
+        ));
+    };
+
+    if !record.type_variables.is_empty() {
+        let message = format!("`{name}` has type parameters, so it cannot be stored.");
+        return Err(bad_mock(&region, message));
+    }
+
+    let id = record
+        .properties
+        .iter()
+        .find(|property| property.name.value == "id");
+    match id.map(|property| &property.type_) {
+        Some(can::Type::Int32 | can::Type::Int64 | can::Type::String) => Ok(name.clone()),
+        _ => {
+            let message = format!(
+                "Values are stored by their `id`, so `{name}` needs an `id` of type Int32, Int64 or String."
+            );
+            Err(bad_mock(&region, message))
+        }
+    }
+}
+
+fn parse_examples(
+    module: &can::Module,
+    annotations: &[Expr],
+    type_: Option<&can::Type>,
+) -> Result<Vec<Value>, Vec<canonicalize::Annotation>> {
+    let mut examples = vec![];
+    let mut errors = vec![];
+    for annotation in annotations {
+        let Expr::List(region, expressions) = annotation else {
+            continue;
+        };
+
+        match expressions.as_slice() {
+            [Expr::Symbol(_, value), args @ ..] if value == "example" => {
+                match parse_example(module, region, args, type_) {
+                    Ok(example) => examples.push(example),
+                    Err(error) => errors.push(error),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(examples)
     } else {
         Err(errors)
     }
 }
 
+fn parse_example(
+    module: &can::Module,
+    region: &Region,
+    args: &[Expr],
+    type_: Option<&can::Type>,
+) -> Result<Value, canonicalize::Annotation> {
+    let [expr] = args else {
+        let message = "An example needs exactly one value, like `#(example {:name \"Test\"})`.";
+        return Err(canonicalize::Annotation::BadExample(
+            region.clone(),
+            message.to_string(),
+        ));
+    };
+
+    let Some(type_) = type_ else {
+        let message = "This method has no return type, so there is nothing to give an example of.";
+        return Err(canonicalize::Annotation::BadExample(
+            region.clone(),
+            message.to_string(),
+        ));
+    };
+
+    let value = value::from_expr(expr)
+        .map_err(|(region, message)| canonicalize::Annotation::BadExample(region, message))?;
+
+    value::check(module, type_, &value, "").map_err(|mismatch| {
+        let message = match mismatch.path.as_str() {
+            "" => format!("The example is not a valid `{type_}`. {}", mismatch.message),
+            path => format!(
+                "The example is not a valid `{type_}`. At `{path}`: {}",
+                mismatch.message
+            ),
+        };
+        canonicalize::Annotation::BadExample(expr.region(), message)
+    })?;
+
+    Ok(value)
+}
+
 fn canonicalize_property<E, F>(
     property: &src::Property,
     map_err: F,
@@ -88,13 +420,14 @@ where
     };
 
     let deps = compute_property_dependency(&property.name.value, &constraints)
-        .iter()
-        .cloned()
+        .into_iter()
+        .sorted()
         .collect_vec();
 
     if errors.is_empty() {
         Ok(can::Property {
             comment: property.doc_comment.clone(),
+            examples: vec![],
             name: property.name.clone(),
             type_: parse_type(&property.type_),
             annotations,
@@ -146,6 +479,7 @@ fn canonicalize_data(data: &src::Data) -> Result<can::Record, Vec<canonicalize::
                     constraints,
                     property_validation_order,
                     comment: data.doc_comment.clone(),
+                    examples: vec![],
                     name: data.name.clone(),
                     properties,
                     type_variables: data.type_variables.clone(),
@@ -251,7 +585,8 @@ fn sorted_by_topology(graph: &HashMap<String, &Vec<String>>) -> Vec<String> {
     let mut visited = HashSet::new();
     let mut temp_marks = HashSet::new();
 
-    for node in graph.keys() {
+    // Sort the keys, so the order is the same for every compilation.
+    for node in graph.keys().sorted() {
         if visited.contains(node) {
             continue;
         }
@@ -341,7 +676,7 @@ fn parse_constraint(expr: &src::Expr) -> Result<Constraint, canonicalize::Annota
             }
         }
         src::Expr::Keyword(_, value) => Constraint::String(value.clone()),
-        src::Expr::List(_, expressions) => match expressions.as_slice() {
+        src::Expr::List(region, expressions) => match expressions.as_slice() {
             [src::Expr::Symbol(_, value), args @ ..] if value == "<" => Constraint::Lt(
                 args.iter()
                     .map(parse_constraint)
@@ -372,6 +707,11 @@ fn parse_constraint(expr: &src::Expr) -> Result<Constraint, canonicalize::Annota
                     .map(parse_constraint)
                     .collect::<Result<Vec<Constraint>, canonicalize::Annotation>>()?,
             ),
+            [src::Expr::Symbol(_, value), args @ ..] if value == "xor" => Constraint::Xor(
+                args.iter()
+                    .map(parse_constraint)
+                    .collect::<Result<Vec<Constraint>, canonicalize::Annotation>>()?,
+            ),
             [src::Expr::Symbol(_, value), arg, _args @ ..] if value == "len" => {
                 Constraint::Len(Box::new(parse_constraint(arg)?))
             }
@@ -388,7 +728,20 @@ fn parse_constraint(expr: &src::Expr) -> Result<Constraint, canonicalize::Annota
                 let constraint = parse_constraint(arg)?;
                 Constraint::Not(Box::new(constraint))
             }
-            _ => unimplemented!(),
+            [src::Expr::Symbol(_, value)] if ["len", "blank", "not"].contains(&value.as_str()) => {
+                return Err(canonicalize::Annotation::MissingArgument(
+                    region.clone(),
+                    value.clone(),
+                ));
+            }
+            [src::Expr::Symbol(region, value), ..] => {
+                return Err(canonicalize::Annotation::UnknownSymbol(
+                    region.clone(),
+                    value.clone(),
+                ));
+            }
+            [] => return Err(canonicalize::Annotation::Empty(region.clone())),
+            _ => return Err(canonicalize::Annotation::InvalidAnnotation(region.clone())),
         },
         src::Expr::Map(_, values) => Constraint::Map(
             values
@@ -560,7 +913,7 @@ fn canonicalize_service(
 
     if !constraints.is_empty() {
         errors.push(canonicalize::Service::BadAnnotation(
-            canonicalize::Annotation::InvalidAnnotation(Region::line(1, 0, 0)),
+            canonicalize::Annotation::InvalidAnnotation(service.name.region.clone()),
         ));
     }
 
@@ -570,6 +923,7 @@ fn canonicalize_service(
             name: service.name.clone(),
             comment: service.doc_comment.clone(),
             methods,
+            crud: None,
         })
     } else {
         Err(errors)
@@ -596,7 +950,7 @@ fn canonicalize_method(method: &src::Method) -> Result<can::Method, Vec<canonica
                 errors.append(&mut annotation_errors);
             }
             Ok(_) => parameters.push(can::Parameter {
-                comment: None,
+                comment: parameter.doc_comment.clone(),
                 annotations,
                 constraints,
                 name: parameter.name.clone(),
@@ -623,6 +977,7 @@ fn canonicalize_method(method: &src::Method) -> Result<can::Method, Vec<canonica
                 Ok(can::Method {
                     annotations,
                     comment: method.doc_comment.clone(),
+                    examples: vec![],
                     name: method.name.clone(),
                     return_type: method.return_type.clone().map(|type_| parse_type(&type_)),
                     parameters,
@@ -1,17 +1,275 @@
-use std::io;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use serde::{Deserialize, Serialize};
 
 use crate::ast::canonical::Module;
 use crate::codegen::command::Command;
+use crate::links;
 
 pub mod command;
+mod jsonschema;
 mod kotlin;
+mod openapi;
+mod plugin;
 mod rust;
 mod typescript;
 
-pub fn generate(module: &Module, options: &Command) -> Result<(), io::Error> {
+/// A generated file, with a path relative to the output directory.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct File {
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// Generate the code for the given [`Command`] and print it or
+/// write it into the output directory.
+///
+/// Returns the paths of all files, that have been written. Files,
+/// whose content did not change, are not written again.
+pub fn generate(module: &Module, options: &Command) -> Result<Vec<PathBuf>, io::Error> {
+    let files = render(module, options)?;
+    emit(options, &files)
+}
+
+/// Print the files rendered for the given [`Command`] or write them
+/// into its output directory, see [`generate`].
+pub fn emit(options: &Command, files: &[File]) -> Result<Vec<PathBuf>, io::Error> {
+    if options.print() {
+        for file in files {
+            println!("{}", file.content);
+        }
+    }
+
+    match options.output() {
+        Some(out) => write_files(out, files),
+        None => Ok(vec![]),
+    }
+}
+
+/// Generate the code for the given [`Command`] in memory.
+///
+/// Links in doc comments to missing declarations end up as plain
+/// text, see [`links::unlink_unresolved`].
+pub fn render(module: &Module, options: &Command) -> Result<Vec<File>, io::Error> {
+    let module = &links::unlink_unresolved(module);
     match options {
-        Command::Typescript(options) => typescript::generate_typescript_client(module, options),
-        Command::Rust => rust::generate_rust_server(module),
-        Command::Kotlin(options) => kotlin::generate_kotlin_server(module, options),
+        Command::Typescript(options) => Ok(typescript::generate_typescript_client(module, options)),
+        Command::Rust(_) => Ok(rust::generate_rust_server(module)),
+        Command::Kotlin(options) => Ok(kotlin::generate_kotlin_server(module, options)),
+        Command::Openapi(options) => Ok(openapi::generate_openapi(module, options)),
+        Command::Jsonschema(options) => Ok(jsonschema::generate_jsonschema(module, options)),
+        Command::Plugin(options) => plugin::generate_with_plugin(module, options),
+    }
+}
+
+/// Write the given files into the output directory, skipping all
+/// files whose content did not change, and return the written paths.
+pub fn write_files(out: &Path, files: &[File]) -> Result<Vec<PathBuf>, io::Error> {
+    let mut written = vec![];
+    for file in files {
+        let path = out.join(&file.path);
+        if fs::read_to_string(&path).is_ok_and(|content| content == file.content) {
+            continue;
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, &file.content)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// A generated file, whose content differs from the file on disk.
+#[derive(Debug, PartialEq)]
+pub struct Stale {
+    /// The output directory of the file.
+    pub output: PathBuf,
+    /// The path of the file, relative to the output directory.
+    pub path: PathBuf,
+    /// The content on disk, if the file exists.
+    pub current: Option<String>,
+    pub expected: String,
+}
+
+/// The differences between the generated files and the content of
+/// the output directory.
+#[derive(Debug, Default, PartialEq)]
+pub struct Drift {
+    pub stale: Vec<Stale>,
+    /// Files in the output directory, that are not generated anymore.
+    pub orphaned: Vec<PathBuf>,
+}
+
+impl Drift {
+    pub fn is_empty(&self) -> bool {
+        self.stale.is_empty() && self.orphaned.is_empty()
+    }
+}
+
+/// Compare the given files with the content of their output
+/// directories, without writing anything.
+///
+/// All outputs are compared at once, so the files of one target are
+/// never orphaned by another target writing into the same directory.
+/// Only files, that a generator could have written, can be orphaned,
+/// see [`collect_orphans`].
+pub fn compare_files(outputs: &[(&Path, &[File])]) -> Result<Drift, io::Error> {
+    let mut drift = Drift::default();
+    let mut generated = HashSet::new();
+    for (out, files) in outputs {
+        for file in files.iter() {
+            let path = out.join(&file.path);
+            let current = match fs::read_to_string(&path) {
+                Ok(content) => Some(content),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+                Err(error) => return Err(error),
+            };
+
+            if current.as_ref() != Some(&file.content) {
+                drift.stale.push(Stale {
+                    output: out.to_path_buf(),
+                    path: file.path.clone(),
+                    current,
+                    expected: file.content.clone(),
+                });
+            }
+
+            generated.insert(path);
+        }
+    }
+
+    drift.orphaned = collect_orphans(&generated)?;
+
+    Ok(drift)
+}
+
+/// Returns all files, that are not generated, but sit in a directory
+/// next to generated files with the same suffix, like `Removed.kt`
+/// next to `Person.kt`, or `Removed.schema.json` next to
+/// `Person.schema.json`.
+///
+/// Other files, like hidden files, a `README.md` or hand-written
+/// code in another directory, are never orphaned.
+fn collect_orphans(generated: &HashSet<PathBuf>) -> Result<Vec<PathBuf>, io::Error> {
+    let mut suffixes: BTreeMap<&Path, HashSet<&str>> = BTreeMap::new();
+    for path in generated {
+        if let (Some(dir), Some(suffix)) = (path.parent(), suffix(path)) {
+            suffixes.entry(dir).or_default().insert(suffix);
+        }
+    }
+
+    let mut orphaned = vec![];
+    for (dir, suffixes) in suffixes {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(error),
+        };
+
+        for entry in entries {
+            let path = entry?.path();
+            let hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            let plausible = suffix(&path).is_some_and(|suffix| suffixes.contains(suffix));
+            if !hidden && plausible && path.is_file() && !generated.contains(&path) {
+                orphaned.push(path);
+            }
+        }
+    }
+
+    orphaned.sort();
+    Ok(orphaned)
+}
+
+/// Returns everything after the first dot of the file name, e.g.
+/// `schema.json` for `Person.schema.json`.
+fn suffix(path: &Path) -> Option<&str> {
+    let name = path.file_name()?.to_str()?;
+    name.split_once('.').map(|(_, suffix)| suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use crate::codegen::{compare_files, write_files, File};
+
+    #[test]
+    fn test_write_files_skips_unchanged_files() {
+        let out = env::temp_dir().join(format!("wrpc-write-{}", std::process::id()));
+        let files = vec![
+            File {
+                path: "models.ts".into(),
+                content: "a".to_string(),
+            },
+            File {
+                path: "nested/client.ts".into(),
+                content: "b".to_string(),
+            },
+        ];
+
+        let first = write_files(&out, &files).unwrap();
+        fs::write(out.join("models.ts"), "changed").unwrap();
+        let second = write_files(&out, &files).unwrap();
+        fs::remove_dir_all(&out).unwrap();
+
+        assert_eq!(first.len(), 2);
+        assert_eq!(second, vec![out.join("models.ts")]);
+    }
+
+    #[test]
+    fn test_compare_files_finds_stale_and_orphaned_files() {
+        let out = env::temp_dir().join(format!("wrpc-compare-{}", std::process::id()));
+        fs::create_dir_all(out.join("models")).unwrap();
+        fs::write(out.join("client.ts"), "old").unwrap();
+        fs::write(out.join("models/Person.kt"), "same").unwrap();
+        fs::write(out.join("models/Removed.kt"), "removed").unwrap();
+        fs::write(out.join(".gitkeep"), "").unwrap();
+        fs::write(out.join("models/README.md"), "hand-written").unwrap();
+        fs::write(out.join("openapi.json"), "other target").unwrap();
+        fs::create_dir_all(out.join("custom")).unwrap();
+        fs::write(out.join("custom/Helper.kt"), "hand-written").unwrap();
+
+        let files = vec![
+            File {
+                path: "client.ts".into(),
+                content: "new".to_string(),
+            },
+            File {
+                path: "models/Person.kt".into(),
+                content: "same".to_string(),
+            },
+            File {
+                path: "models/Added.kt".into(),
+                content: "added".to_string(),
+            },
+        ];
+
+        let other = vec![File {
+            path: "openapi.json".into(),
+            content: "other target".to_string(),
+        }];
+        let drift = compare_files(&[(&out, &files), (&out, &other)]).unwrap();
+        fs::remove_dir_all(&out).unwrap();
+
+        let stale = drift
+            .stale
+            .iter()
+            .map(|stale| (stale.path.clone(), stale.current.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            stale,
+            vec![
+                ("client.ts".into(), Some("old".to_string())),
+                ("models/Added.kt".into(), None),
+            ]
+        );
+        assert_eq!(drift.orphaned, vec![out.join("models/Removed.kt")]);
     }
 }
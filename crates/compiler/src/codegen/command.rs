@@ -1,9 +1,45 @@
+use std::path::Path;
+
+pub use crate::codegen::jsonschema::Options as JsonschemaOptions;
 pub use crate::codegen::kotlin::Options as KotlinOptions;
+pub use crate::codegen::openapi::Options as OpenapiOptions;
+pub use crate::codegen::plugin::Options as PluginOptions;
+pub use crate::codegen::rust::Options as RustOptions;
+pub use crate::codegen::typescript::ModuleStyle;
 pub use crate::codegen::typescript::Options as TypescriptOptions;
 
 #[derive(Debug)]
 pub enum Command {
     Typescript(TypescriptOptions),
-    Rust,
+    Rust(RustOptions),
     Kotlin(KotlinOptions),
+    Openapi(OpenapiOptions),
+    Jsonschema(JsonschemaOptions),
+    Plugin(PluginOptions),
+}
+
+impl Command {
+    /// Whether the generated code should be printed.
+    pub fn print(&self) -> bool {
+        match self {
+            Command::Typescript(options) => options.print,
+            Command::Rust(options) => options.print,
+            Command::Kotlin(options) => options.print,
+            Command::Openapi(options) => options.print,
+            Command::Jsonschema(options) => options.print,
+            Command::Plugin(options) => options.print,
+        }
+    }
+
+    /// The directory the generated code should be written to.
+    pub fn output(&self) -> Option<&Path> {
+        match self {
+            Command::Typescript(options) => options.output.as_deref(),
+            Command::Rust(options) => options.output.as_deref(),
+            Command::Kotlin(options) => options.output.as_deref(),
+            Command::Openapi(options) => options.output.as_deref(),
+            Command::Jsonschema(options) => options.output.as_deref(),
+            Command::Plugin(options) => options.output.as_deref(),
+        }
+    }
 }
@@ -0,0 +1,601 @@
+//! JSON Schemas (draft 2020-12) of the wire format of types.
+//!
+//! Records are objects, where optional properties may be missing or
+//! `null`. Enums with data are objects tagged with `@type`, all other
+//! enums are strings. Maps are arrays of pairs and sets are arrays
+//! of unique items.
+//!
+//! JSON Schema has no generics, so every instantiation of a generic
+//! record or enum gets its own definition, like `Page_Person` for
+//! `Page<Person>`.
+use std::collections::{BTreeMap, HashMap};
+use std::iter;
+use std::path::PathBuf;
+
+use itertools::Itertools;
+use serde_json::{json, Map, Value};
+
+use crate::ast::canonical::{Enum, Expr, Module, Parameter, Property, Record, Type};
+use crate::ast::constraints::Constraint;
+use crate::docs::deprecation;
+use crate::random::Bounds;
+use crate::value::{bind, substitute};
+
+use super::File;
+
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+#[derive(Debug)]
+pub struct Options {
+    pub print: bool,
+    pub output: Option<PathBuf>,
+    /// Write all definitions into a single file with this name, like
+    /// `api` for `api.schema.json`, instead of a file per type.
+    pub bundle: Option<String>,
+}
+
+/// Generate a JSON Schema for every record and enum without type
+/// variables, like `Person.schema.json`, or a single bundle.
+///
+/// Every schema contains the definitions of all types it refers to,
+/// so it can be used on its own.
+pub fn generate_jsonschema(module: &Module, options: &Options) -> Vec<File> {
+    if let Some(name) = &options.bundle {
+        let mut schemas = Schemas::new(module, "#/$defs/");
+        schemas.define_all();
+        let document = json!({
+            "$schema": DIALECT,
+            "$id": format!("{name}.schema.json"),
+            "$defs": schemas.into_definitions(),
+        });
+        return vec![schema_file(format!("{name}.schema.json"), &document)];
+    }
+
+    let records = module.get_sorted_records().into_iter();
+    let enums = module.get_sorted_enums().into_iter();
+    let names = records
+        .filter(|record| record.type_variables.is_empty())
+        .map(|record| &record.name.value)
+        .chain(
+            enums
+                .filter(|enum_| enum_.type_variables.is_empty())
+                .map(|enum_| &enum_.name.value),
+        );
+
+    names
+        .map(|name| {
+            let mut schemas = Schemas::new(module, "#/$defs/");
+            let reference = schemas.schema(&Type::Ref(name.clone(), vec![]));
+            let document = json!({
+                "$schema": DIALECT,
+                "$id": format!("{name}.schema.json"),
+                "title": name,
+                "$ref": reference["$ref"],
+                "$defs": schemas.into_definitions(),
+            });
+            schema_file(format!("{name}.schema.json"), &document)
+        })
+        .collect()
+}
+
+fn schema_file(path: String, document: &Value) -> File {
+    let content = serde_json::to_string_pretty(document).expect("Schema should be valid JSON");
+    File {
+        path: PathBuf::from(path),
+        content: format!("{content}\n"),
+    }
+}
+
+/// Collects the schemas of all records and enums, which are referenced
+/// by the schemas of types.
+pub struct Schemas<'a> {
+    module: &'a Module,
+    /// The prefix of references to definitions, like `#/$defs/`.
+    prefix: &'a str,
+    /// Whether enums with data contain an OpenAPI `discriminator`.
+    discriminator: bool,
+    definitions: BTreeMap<String, Value>,
+}
+
+/// A property of an object, which is either a property of a record
+/// or a parameter of a method.
+struct Field<'b> {
+    name: &'b str,
+    type_: Type,
+    constraints: &'b [Constraint],
+    comment: &'b Option<String>,
+    annotations: &'b [Expr],
+    examples: &'b [Value],
+}
+
+impl<'a> Schemas<'a> {
+    pub fn new(module: &'a Module, prefix: &'a str) -> Self {
+        Schemas {
+            module,
+            prefix,
+            discriminator: false,
+            definitions: BTreeMap::new(),
+        }
+    }
+
+    /// Add an OpenAPI `discriminator` to every enum with data.
+    pub fn with_discriminator(mut self) -> Self {
+        self.discriminator = true;
+        self
+    }
+
+    /// Define every record and enum without type variables, even if
+    /// it is never referenced.
+    pub fn define_all(&mut self) {
+        let records = self.module.get_sorted_records();
+        let enums = self.module.get_sorted_enums();
+        let names = records
+            .iter()
+            .filter(|record| record.type_variables.is_empty())
+            .map(|record| record.name.value.clone())
+            .chain(
+                enums
+                    .iter()
+                    .filter(|enum_| enum_.type_variables.is_empty())
+                    .map(|enum_| enum_.name.value.clone()),
+            )
+            .collect::<Vec<_>>();
+
+        for name in names {
+            self.reference(&name, &[]);
+        }
+    }
+
+    /// Returns all definitions collected so far, by their names.
+    pub fn into_definitions(self) -> BTreeMap<String, Value> {
+        self.definitions
+    }
+
+    /// Returns the schema of the given type, where records and enums
+    /// are references to their definitions.
+    pub fn schema(&mut self, type_: &Type) -> Value {
+        match type_ {
+            Type::String => json!({"type": "string"}),
+            Type::Boolean => json!({"type": "boolean"}),
+            Type::Int32 => json!({"type": "integer", "format": "int32"}),
+            Type::Int64 => json!({"type": "integer", "format": "int64"}),
+            Type::Float32 => json!({"type": "number", "format": "float"}),
+            Type::Float64 => json!({"type": "number", "format": "double"}),
+            Type::Option(type_) => nullable(self.schema(type_)),
+            Type::List(type_) => json!({"type": "array", "items": self.schema(type_)}),
+            Type::Set(type_) => {
+                json!({"type": "array", "items": self.schema(type_), "uniqueItems": true})
+            }
+            Type::Map(key, value) => json!({
+                "type": "array",
+                "items": {
+                    "type": "array",
+                    "prefixItems": [self.schema(key), self.schema(value)],
+                    "minItems": 2,
+                    "maxItems": 2,
+                },
+            }),
+            Type::Result(error, ok) => json!({
+                "oneOf": [
+                    self.tagged("Ok", "value", ok),
+                    self.tagged("Err", "error", error),
+                ],
+            }),
+            Type::Ref(name, variables) => self.reference(name, variables),
+        }
+    }
+
+    /// Returns the schema of an object with a property per parameter,
+    /// like the body of a request.
+    pub fn parameters(&mut self, parameters: &[Parameter]) -> Value {
+        let fields = parameters
+            .iter()
+            .map(|parameter| Field {
+                name: &parameter.name.value,
+                type_: parameter.type_.clone(),
+                constraints: &parameter.constraints,
+                comment: &parameter.comment,
+                annotations: &parameter.annotations,
+                examples: &[],
+            })
+            .collect::<Vec<_>>();
+        self.object(None, fields)
+    }
+
+    /// Returns the reference to the definition of the record or enum,
+    /// which is added on first use.
+    fn reference(&mut self, name: &str, variables: &[Type]) -> Value {
+        let module = self.module;
+        let id = definition_name(name, variables);
+        if !self.definitions.contains_key(&id) {
+            // Recursive types refer to the definition, before it is done.
+            self.definitions.insert(id.clone(), Value::Null);
+            let schema = if let Some(record) = module.records.get(name) {
+                self.record(record, variables)
+            } else if let Some(enum_) = module.enums.get(name) {
+                self.enum_(&id, enum_, variables)
+            } else {
+                // An unbound type variable accepts everything.
+                self.definitions.remove(&id);
+                return json!({});
+            };
+            self.definitions.insert(id.clone(), schema);
+        }
+
+        json!({"$ref": format!("{}{id}", self.prefix)})
+    }
+
+    fn record(&mut self, record: &Record, variables: &[Type]) -> Value {
+        let types = bind(&record.type_variables, variables);
+        let fields = fields(&record.properties, &types);
+        let mut schema = self.object(None, fields);
+        annotate(
+            &mut schema,
+            &record.comment,
+            &record.annotations,
+            &record.examples,
+        );
+        schema
+    }
+
+    fn enum_(&mut self, id: &str, enum_: &Enum, variables: &[Type]) -> Value {
+        let mut schema = if enum_.is_simple() {
+            let variants = enum_.variants.iter().map(|variant| &variant.name.value);
+            json!({"type": "string", "enum": variants.collect::<Vec<_>>()})
+        } else {
+            let types = bind(&enum_.type_variables, variables);
+            let mut references = vec![];
+            for variant in &enum_.variants {
+                let name = &variant.name.value;
+                let fields = fields(&variant.properties, &types);
+                let mut schema = self.object(Some(name), fields);
+                annotate(&mut schema, &variant.comment, &variant.annotations, &[]);
+                let variant_id = format!("{id}.{name}");
+                self.definitions.insert(variant_id.clone(), schema);
+                references.push((name.clone(), format!("{}{variant_id}", self.prefix)));
+            }
+
+            let one_of = references
+                .iter()
+                .map(|(_, reference)| json!({"$ref": reference}))
+                .collect::<Vec<_>>();
+            let mut schema = json!({"oneOf": one_of});
+            if self.discriminator {
+                let mapping = references.into_iter().collect::<BTreeMap<_, _>>();
+                schema["discriminator"] = json!({"propertyName": "@type", "mapping": mapping});
+            }
+            schema
+        };
+
+        annotate(&mut schema, &enum_.comment, &enum_.annotations, &[]);
+        schema
+    }
+
+    /// Returns the schema of an object, which is tagged with `@type`,
+    /// if a tag is given.
+    fn object(&mut self, tag: Option<&str>, fields: Vec<Field>) -> Value {
+        let mut properties = Map::new();
+        let mut required = vec![];
+        if let Some(tag) = tag {
+            properties.insert("@type".to_string(), json!({"const": tag}));
+            required.push(json!("@type"));
+        }
+
+        for field in fields {
+            if !matches!(field.type_, Type::Option(_)) {
+                required.push(json!(field.name));
+            }
+
+            let bounds = Bounds::of(field.name, field.constraints);
+            let mut schema = self.bounded(&field.type_, &bounds);
+            annotate(
+                &mut schema,
+                field.comment,
+                field.annotations,
+                field.examples,
+            );
+            properties.insert(field.name.to_string(), schema);
+        }
+
+        let mut schema = json!({"type": "object", "properties": properties});
+        if !required.is_empty() {
+            schema["required"] = Value::Array(required);
+        }
+        schema
+    }
+
+    /// Returns the schema of the type, restricted by the bounds of its
+    /// constraints. Constraints of an optional value apply to the
+    /// value, if it is present.
+    fn bounded(&mut self, type_: &Type, bounds: &Bounds) -> Value {
+        let Type::Option(inner) = type_ else {
+            let mut schema = self.schema(type_);
+            restrict(&mut schema, type_, bounds);
+            return schema;
+        };
+
+        let mut schema = self.schema(inner);
+        restrict(&mut schema, inner, bounds);
+        nullable(schema)
+    }
+
+    /// Returns the schema of a variant of a `Result`, like
+    /// `{"@type": "Ok", "value": 1}`.
+    fn tagged(&mut self, tag: &str, name: &str, type_: &Type) -> Value {
+        let mut required = vec!["@type"];
+        if !matches!(type_, Type::Option(_)) {
+            required.push(name);
+        }
+
+        json!({
+            "type": "object",
+            "properties": {
+                "@type": {"const": tag},
+                name: self.schema(type_),
+            },
+            "required": required,
+        })
+    }
+}
+
+fn fields<'b>(properties: &'b [Property], types: &HashMap<&str, &Type>) -> Vec<Field<'b>> {
+    properties
+        .iter()
+        .map(|property| Field {
+            name: &property.name.value,
+            type_: substitute(&property.type_, types),
+            constraints: &property.constraints,
+            comment: &property.comment,
+            annotations: &property.annotations,
+            examples: &property.examples,
+        })
+        .collect()
+}
+
+/// Returns the name of the definition of a record or enum with the
+/// given types for its type variables, like `Page_Person`.
+fn definition_name(name: &str, variables: &[Type]) -> String {
+    iter::once(name.to_string())
+        .chain(variables.iter().map(type_name))
+        .join("_")
+}
+
+fn type_name(type_: &Type) -> String {
+    match type_ {
+        Type::List(type_) => format!("List_{}", type_name(type_)),
+        Type::Set(type_) => format!("Set_{}", type_name(type_)),
+        Type::Option(type_) => format!("Option_{}", type_name(type_)),
+        Type::Map(key, value) => format!("Map_{}_{}", type_name(key), type_name(value)),
+        Type::Result(error, ok) => format!("Result_{}_{}", type_name(error), type_name(ok)),
+        Type::Ref(name, variables) => definition_name(name, variables),
+        type_ => type_.to_string(),
+    }
+}
+
+/// Allow `null` in addition to the values of the schema.
+fn nullable(schema: Value) -> Value {
+    let type_ = schema.get("type").and_then(Value::as_str);
+    match type_ {
+        Some(type_) if schema.get("enum").is_none() => {
+            let mut schema = schema.clone();
+            schema["type"] = json!([type_, "null"]);
+            schema
+        }
+        _ => json!({"anyOf": [schema, {"type": "null"}]}),
+    }
+}
+
+/// Add the keywords of the bounds, which apply to values of the type.
+fn restrict(schema: &mut Value, type_: &Type, bounds: &Bounds) {
+    let mut keywords = Map::new();
+    match type_ {
+        Type::Int32 | Type::Int64 | Type::Float32 | Type::Float64 => {
+            if let Some(min) = bounds.min {
+                let keyword = match bounds.min_exclusive {
+                    true => "exclusiveMinimum",
+                    false => "minimum",
+                };
+                keywords.insert(keyword.to_string(), number(min));
+            }
+            if let Some(max) = bounds.max {
+                let keyword = match bounds.max_exclusive {
+                    true => "exclusiveMaximum",
+                    false => "maximum",
+                };
+                keywords.insert(keyword.to_string(), number(max));
+            }
+        }
+        Type::String => {
+            if let Some(min) = bounds.min_length {
+                keywords.insert("minLength".to_string(), json!(min));
+            }
+            if let Some(max) = bounds.max_length {
+                keywords.insert("maxLength".to_string(), json!(max));
+            }
+        }
+        Type::List(_) | Type::Set(_) | Type::Map(_, _) => {
+            if let Some(min) = bounds.min_length {
+                keywords.insert("minItems".to_string(), json!(min));
+            }
+            if let Some(max) = bounds.max_length {
+                keywords.insert("maxItems".to_string(), json!(max));
+            }
+        }
+        _ => {}
+    }
+
+    let literals = matches!(
+        type_,
+        Type::String | Type::Boolean | Type::Int32 | Type::Int64 | Type::Float32 | Type::Float64
+    );
+    if let (true, Some(choices)) = (literals, &bounds.choices) {
+        keywords.insert("enum".to_string(), json!(choices));
+    }
+
+    if let Some(schema) = schema.as_object_mut() {
+        schema.extend(keywords);
+    }
+}
+
+/// Keep integers as integers, like `5` instead of `5.0`.
+fn number(value: f64) -> Value {
+    match value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        true => json!(value as i64),
+        false => json!(value),
+    }
+}
+
+/// Add the comment, deprecation and examples of a declaration.
+fn annotate(
+    schema: &mut Value,
+    comment: &Option<String>,
+    annotations: &[Expr],
+    examples: &[Value],
+) {
+    let Some(schema) = schema.as_object_mut() else {
+        return;
+    };
+
+    if let Some(comment) = comment {
+        schema.insert("description".to_string(), json!(comment));
+    }
+    if deprecation(annotations).is_some() {
+        schema.insert("deprecated".to_string(), json!(true));
+    }
+    if !examples.is_empty() {
+        schema.insert("examples".to_string(), json!(examples));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::ast::canonical::Type;
+    use crate::codegen::jsonschema::{generate_jsonschema, Options, Schemas};
+
+    const SPEC: &str = r#"
+// A page of items.
+data Page<A> {
+    items: List<A>,
+    #(check (<= 0 .total))
+    total: Int64,
+}
+
+data Person {
+    #(check (<= 1 (len .name) 20))
+    name: String,
+    nickname: String?,
+    tags: Set<String>,
+    scores: Map<String, Int32>,
+}
+
+enum Shape {
+    Circle { radius: Float64 },
+    Point,
+}
+"#;
+
+    #[test]
+    fn test_generic_types_are_instantiated() {
+        let module = crate::compile(None, SPEC).unwrap();
+        let mut schemas = Schemas::new(&module, "#/$defs/");
+        let page = Type::Ref("Page".into(), vec![Type::Ref("Person".into(), vec![])]);
+        assert_eq!(
+            schemas.schema(&page),
+            json!({"$ref": "#/$defs/Page_Person"})
+        );
+
+        let definitions = schemas.into_definitions();
+        let names = definitions.keys().collect::<Vec<_>>();
+        assert_eq!(names, vec!["Page_Person", "Person"]);
+        assert_eq!(
+            definitions["Page_Person"]["properties"]["items"],
+            json!({"type": "array", "items": {"$ref": "#/$defs/Person"}})
+        );
+        assert_eq!(
+            definitions["Page_Person"]["properties"]["total"],
+            json!({"type": "integer", "format": "int64", "minimum": 0})
+        );
+    }
+
+    #[test]
+    fn test_schema_per_type() {
+        let module = crate::compile(None, SPEC).unwrap();
+        let options = Options {
+            print: false,
+            output: None,
+            bundle: None,
+        };
+        let files = generate_jsonschema(&module, &options);
+        let paths = files
+            .iter()
+            .map(|file| file.path.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["Person.schema.json", "Shape.schema.json"]);
+
+        let shape: Value = serde_json::from_str(&files[1].content).unwrap();
+        assert_eq!(
+            shape["$schema"],
+            "https://json-schema.org/draft/2020-12/schema"
+        );
+        assert_eq!(shape["$ref"], "#/$defs/Shape");
+        let definitions = shape["$defs"].as_object().unwrap();
+        let names = definitions.keys().collect::<Vec<_>>();
+        assert_eq!(names, vec!["Shape", "Shape.Circle", "Shape.Point"]);
+    }
+
+    #[test]
+    fn test_bundle() {
+        let module = crate::compile(None, SPEC).unwrap();
+        let options = Options {
+            print: false,
+            output: None,
+            bundle: Some("api".to_string()),
+        };
+        let files = generate_jsonschema(&module, &options);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path.to_str(), Some("api.schema.json"));
+        let bundle: Value = serde_json::from_str(&files[0].content).unwrap();
+        assert!(bundle.get("$ref").is_none());
+        assert_eq!(bundle["$defs"].as_object().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_wire_format() {
+        let module = crate::compile(None, SPEC).unwrap();
+        let mut schemas = Schemas::new(&module, "#/$defs/");
+        schemas.define_all();
+        let definitions = schemas.into_definitions();
+
+        let person = &definitions["Person"];
+        assert_eq!(person["required"], json!(["name", "tags", "scores"]));
+        assert_eq!(
+            person["properties"]["name"],
+            json!({"type": "string", "minLength": 1, "maxLength": 20})
+        );
+        assert_eq!(
+            person["properties"]["nickname"],
+            json!({"type": ["string", "null"]})
+        );
+        assert_eq!(person["properties"]["tags"]["uniqueItems"], json!(true));
+        assert_eq!(
+            person["properties"]["scores"]["items"]["prefixItems"],
+            json!([{"type": "string"}, {"type": "integer", "format": "int32"}])
+        );
+
+        assert_eq!(
+            definitions["Shape"]["oneOf"],
+            json!([{"$ref": "#/$defs/Shape.Circle"}, {"$ref": "#/$defs/Shape.Point"}])
+        );
+        assert_eq!(
+            definitions["Shape.Point"],
+            json!({
+                "type": "object",
+                "properties": {"@type": {"const": "Point"}},
+                "required": ["@type"],
+            })
+        );
+        assert!(!definitions.contains_key("Page"));
+    }
+}
@@ -1,13 +1,13 @@
 use crate::ast::canonical::Parameter;
 use crate::ast::canonical::{Enum, Module, Property, Record, Service, Type};
 use crate::ast::constraints::Constraint;
+use crate::links;
 use askama::Template; // bring trait in scope
 use itertools::Itertools;
-use std::fs::File;
-use std::io::Write;
 use std::path::PathBuf;
 use std::string::ToString;
-use std::{fs, io};
+
+use super::File;
 
 const OPEN: &'static str = "{";
 const CLOSE: &'static str = "}";
@@ -26,7 +26,7 @@ pub struct KtFile {
     content: String,
 }
 
-pub fn generate_kotlin_server(module: &Module, options: &Options) -> Result<(), io::Error> {
+pub fn generate_kotlin_server(module: &Module, options: &Options) -> Vec<File> {
     let record_package = &options.package;
     //for decl in module.declarations.iter() {}
     let mut files = vec![];
@@ -66,25 +66,13 @@ pub fn generate_kotlin_server(module: &Module, options: &Options) -> Result<(),
         content: generate_result_type(record_package),
     });
 
-    if options.print {
-        for file in &files {
-            println!("{}", file.content);
-        }
-    }
-
-    if let Some(out) = &options.output {
-        fs::create_dir_all(out)?;
-        fs::create_dir_all(out.join("json"))?;
-        fs::create_dir_all(out.join("models"))?;
-        fs::create_dir_all(out.join("services"))?;
-
-        for kt_file in files {
-            let mut file = File::create(out.join(kt_file.folder).join(kt_file.name))?;
-            file.write_all(kt_file.content.as_bytes())?;
-        }
-    }
-
-    Ok(())
+    files
+        .into_iter()
+        .map(|file| File {
+            path: PathBuf::from(file.folder).join(file.name),
+            content: file.content,
+        })
+        .collect()
 }
 
 fn generate_service(package: &String, service: &Service) -> String {
@@ -175,7 +163,7 @@ fn generate_doc_comment(indent: &str, comment: &Option<String>) -> String {
     match comment {
         None => "".to_string(),
         Some(comment) => {
-            let content = comment
+            let content = generate_doc_links(comment)
                 .split("\n")
                 .map(|line| format!("{indent} * {line}"))
                 .collect::<Vec<String>>()
@@ -185,6 +173,15 @@ fn generate_doc_comment(indent: &str, comment: &Option<String>) -> String {
     }
 }
 
+/// Translate the links of a doc comment to KDoc, like `[Person]` or
+/// `[the person][Person]`.
+fn generate_doc_links(comment: &str) -> String {
+    links::rewrite(comment, |link| match &link.text {
+        None => Some(format!("[{}]", link.target)),
+        Some(text) => Some(format!("[{text}][{}]", link.target)),
+    })
+}
+
 fn encode_type(var_expr: &str, type_: &Type) -> String {
     match type_ {
         Type::String => var_expr.to_string(),
@@ -493,7 +490,13 @@ fn condition(var_expr: &str, constraint: &Constraint) -> String {
         Constraint::Le(constraints) => binop("<=", var_expr, constraints),
         Constraint::Gt(constraints) => binop(">", var_expr, constraints),
         Constraint::Ge(constraints) => binop(">=", var_expr, constraints),
-        Constraint::Xor(_) => todo!(),
+        Constraint::Xor(constraints) => format!(
+            "listOf({}).count {{ it }} == 1",
+            constraints
+                .iter()
+                .map(|constraint| condition(var_expr, constraint))
+                .join(", ")
+        ),
         Constraint::Len(constraint) => {
             format!("{}.size", condition(var_expr, constraint))
         }
@@ -549,7 +552,9 @@ fn binop(op: &str, var_expr: &str, constraints: &Vec<Constraint>) -> String {
 #[cfg(test)]
 mod tests {
     use crate::ast::constraints::Constraint;
-    use crate::codegen::kotlin::{generate_record, EnumTemplate, RecordTemplate, ServiceTemplate};
+    use crate::codegen::kotlin::{
+        generate_doc_comment, generate_record, EnumTemplate, RecordTemplate, ServiceTemplate,
+    };
     use crate::compile;
     use crate::error::Error;
     use askama::Template; // bring trait in scope
@@ -626,4 +631,13 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_doc_links_are_kdoc_links() {
+        let comment = Some("See [Person] and [get](#PersonService.get).".to_string());
+        assert_eq!(
+            generate_doc_comment("", &comment),
+            "/**\n * See [Person] and [get][PersonService.get].\n */"
+        );
+    }
 }
@@ -0,0 +1,201 @@
+use std::path::PathBuf;
+
+use serde_json::{json, Map, Value};
+
+use crate::ast::canonical::{Method, Module, Service};
+use crate::codegen::jsonschema::Schemas;
+use crate::docs::deprecation;
+
+use super::File;
+
+#[derive(Debug)]
+pub struct Options {
+    pub print: bool,
+    pub output: Option<PathBuf>,
+    /// The title of the API, like the name of the spec.
+    pub title: String,
+    /// The version of the API, not of OpenAPI.
+    pub version: String,
+}
+
+/// Generate an OpenAPI 3.1 document, where every method is a `POST`
+/// operation on its path, like `/PersonService/get`.
+pub fn generate_openapi(module: &Module, options: &Options) -> Vec<File> {
+    let document = document(module, options);
+    let content = serde_json::to_string_pretty(&document).expect("Document should be valid JSON");
+    vec![File {
+        path: PathBuf::from("openapi.json"),
+        content: format!("{content}\n"),
+    }]
+}
+
+fn document(module: &Module, options: &Options) -> Value {
+    let mut schemas = Schemas::new(module, "#/components/schemas/").with_discriminator();
+    let mut paths = Map::new();
+    let mut tags = vec![];
+    for service in module.get_sorted_services() {
+        let mut tag = json!({"name": service.name.value});
+        if let Some(comment) = &service.comment {
+            tag["description"] = json!(comment);
+        }
+        tags.push(tag);
+
+        for method in service.get_sorted_methods() {
+            let operation = operation(&mut schemas, service, method);
+            paths.insert(service.get_method_path(method), json!({"post": operation}));
+        }
+    }
+
+    schemas.define_all();
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": options.title,
+            "version": options.version,
+        },
+        "tags": tags,
+        "paths": paths,
+        "components": {
+            "schemas": schemas.into_definitions(),
+        },
+    })
+}
+
+fn operation(schemas: &mut Schemas, service: &Service, method: &Method) -> Value {
+    let name = &method.name.value;
+    let mut operation = json!({
+        "operationId": format!("{}.{name}", service.name.value),
+        "tags": [service.name.value],
+    });
+
+    if let Some(comment) = &method.comment {
+        let summary = comment.lines().next().unwrap_or("");
+        operation["summary"] = json!(summary);
+        operation["description"] = json!(comment);
+    }
+    if deprecation(&method.annotations).is_some() {
+        operation["deprecated"] = json!(true);
+    }
+
+    if !method.parameters.is_empty() {
+        let schema = schemas.parameters(&method.parameters);
+        operation["requestBody"] = json!({
+            "required": true,
+            "content": {"application/json": {"schema": schema}},
+        });
+    }
+
+    let mut success = json!({"description": "The call succeeded."});
+    if let Some(return_type) = &method.return_type {
+        let mut content = json!({"schema": schemas.schema(return_type)});
+        if let Some(example) = method.examples.first() {
+            content["example"] = example.clone();
+        }
+        success["content"] = json!({"application/json": content});
+    }
+
+    operation["responses"] = json!({
+        "200": success,
+        "400": {"description": "The request does not satisfy the spec."},
+    });
+    operation
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::codegen::openapi::{generate_openapi, Options};
+
+    const SPEC: &str = r#"
+data Person {
+    #(check (not (blank .name)))
+    name: String,
+    #(check (< 0 .age 150))
+    age: Int32?,
+}
+
+enum Shape {
+    Circle { radius: Float64 },
+    Square { length: Float64 },
+}
+
+service PersonService {
+    // Returns the person with the id.
+    //
+    // Fails, if there is none.
+    #(deprecated "Use find instead.")
+    def get(id: Int64, shapes: Map<String, Shape>): Result<String, Person>
+
+    def ping()
+}
+"#;
+
+    fn document() -> Value {
+        let module = crate::compile(None, SPEC).unwrap();
+        let options = Options {
+            print: false,
+            output: None,
+            title: "api".to_string(),
+            version: "1.0.0".to_string(),
+        };
+        let files = generate_openapi(&module, &options);
+        assert_eq!(files[0].path.to_str(), Some("openapi.json"));
+        serde_json::from_str(&files[0].content).unwrap()
+    }
+
+    #[test]
+    fn test_methods_are_post_operations() {
+        let document = document();
+        assert_eq!(document["openapi"], "3.1.0");
+        assert_eq!(document["info"]["title"], "api");
+
+        let get = &document["paths"]["/PersonService/get"]["post"];
+        assert_eq!(get["operationId"], "PersonService.get");
+        assert_eq!(get["summary"], "Returns the person with the id.");
+        assert_eq!(get["deprecated"], true);
+
+        let body = &get["requestBody"]["content"]["application/json"]["schema"];
+        assert_eq!(body["required"], json!(["id", "shapes"]));
+        assert_eq!(
+            body["properties"]["shapes"]["items"]["prefixItems"][1],
+            json!({"$ref": "#/components/schemas/Shape"})
+        );
+
+        let response = &get["responses"]["200"]["content"]["application/json"]["schema"];
+        assert_eq!(
+            response["oneOf"][0]["properties"]["value"],
+            json!({"$ref": "#/components/schemas/Person"})
+        );
+
+        let ping = &document["paths"]["/PersonService/ping"]["post"];
+        assert!(ping.get("requestBody").is_none());
+        assert!(ping["responses"]["200"].get("content").is_none());
+    }
+
+    #[test]
+    fn test_components() {
+        let document = document();
+        let schemas = &document["components"]["schemas"];
+        assert_eq!(
+            schemas["Shape"]["discriminator"],
+            json!({
+                "propertyName": "@type",
+                "mapping": {
+                    "Circle": "#/components/schemas/Shape.Circle",
+                    "Square": "#/components/schemas/Shape.Square",
+                },
+            })
+        );
+        assert_eq!(
+            schemas["Person"]["properties"]["age"],
+            json!({
+                "type": ["integer", "null"],
+                "format": "int32",
+                "exclusiveMinimum": 0,
+                "exclusiveMaximum": 150,
+            })
+        );
+        assert_eq!(schemas["Person"]["properties"]["name"]["minLength"], 1);
+    }
+}
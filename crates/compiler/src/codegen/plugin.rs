@@ -0,0 +1,147 @@
+//! Run an external code generator, similar to `protoc` plugins.
+//!
+//! The plugin receives a JSON request on stdin, that contains the
+//! [exported](crate::export) canonical module and the options given
+//! by the user. It has to respond with a JSON list of files on
+//! stdout. See `docs/plugins.md` for the full protocol.
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::{io, thread};
+
+use crate::ast::canonical::Module;
+use crate::export;
+
+use super::File;
+
+#[derive(Debug)]
+pub struct Options {
+    pub print: bool,
+    pub output: Option<PathBuf>,
+    /// The executable of the plugin, like `wrpc-gen-elixir`.
+    pub exec: PathBuf,
+    /// Options passed on to the plugin as is.
+    pub options: BTreeMap<String, String>,
+}
+
+/// Run the plugin and return the files it generated.
+pub fn generate_with_plugin(module: &Module, options: &Options) -> Result<Vec<File>, io::Error> {
+    let mut request = export::canonical(module, true);
+    request["options"] = serde_json::to_value(&options.options)?;
+    let request = serde_json::to_vec(&request)?;
+
+    let exec = options.exec.display().to_string();
+    let mut child = Command::new(&options.exec)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|error| {
+            io::Error::new(error.kind(), format!("Could not start `{exec}`: {error}"))
+        })?;
+
+    // Write on a separate thread, so a plugin writing its response
+    // before reading the whole request cannot block forever.
+    let mut stdin = child
+        .stdin
+        .take()
+        .expect("The stdin of the plugin is piped");
+    let writer = thread::spawn(move || stdin.write_all(&request));
+    let output = child.wait_with_output()?;
+    let written = writer.join().expect("Writing the request should not panic");
+
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "The plugin `{exec}` failed with {}",
+            output.status
+        )));
+    }
+    written?;
+
+    let files: Vec<File> = serde_json::from_slice(&output.stdout).map_err(|error| {
+        io::Error::other(format!(
+            "The plugin `{exec}` returned invalid JSON: {error}"
+        ))
+    })?;
+
+    for file in &files {
+        if !is_relative_path(&file.path) {
+            return Err(io::Error::other(format!(
+                "The plugin `{exec}` returned the path `{}`, which is not inside of the output directory",
+                file.path.display()
+            )));
+        }
+    }
+
+    Ok(files)
+}
+
+/// Check, whether the path is relative and does not leave the
+/// directory it is joined to.
+fn is_relative_path(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::codegen::plugin::is_relative_path;
+
+    #[test]
+    fn test_paths_stay_inside_output_directory() {
+        assert!(is_relative_path(Path::new("lib/person.ex")));
+        assert!(is_relative_path(Path::new("./person.ex")));
+        assert!(!is_relative_path(Path::new("")));
+        assert!(!is_relative_path(Path::new("/etc/passwd")));
+        assert!(!is_relative_path(Path::new("lib/../../person.ex")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_plugin_receives_module_and_options() {
+        use std::collections::BTreeMap;
+        use std::os::unix::fs::PermissionsExt;
+        use std::{env, fs};
+
+        use crate::codegen::plugin::{generate_with_plugin, Options};
+        use crate::codegen::File;
+
+        let dir = env::temp_dir().join(format!("wrpc-plugin-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let exec = dir.join("wrpc-gen-test");
+        let request = dir.join("request.json");
+        let script = format!(
+            "#!/bin/sh\ncat > '{}'\necho '[{{\"path\": \"lib/person.ex\", \"content\": \"ok\"}}]'\n",
+            request.display()
+        );
+        fs::write(&exec, script).unwrap();
+        fs::set_permissions(&exec, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let module = crate::compile(None, "data Person {\n    name: String,\n}").unwrap();
+        let options = Options {
+            print: false,
+            output: None,
+            exec,
+            options: BTreeMap::from([("app".to_string(), "my_app".to_string())]),
+        };
+
+        let files = generate_with_plugin(&module, &options).unwrap();
+        let request = fs::read_to_string(&request).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let expected = File {
+            path: "lib/person.ex".into(),
+            content: "ok".to_string(),
+        };
+        assert_eq!(files, vec![expected]);
+        let request: serde_json::Value = serde_json::from_str(&request).unwrap();
+        assert_eq!(request["version"], 1);
+        assert_eq!(request["options"]["app"], "my_app");
+        assert!(request["module"]["records"]["Person"].is_object());
+    }
+}
@@ -1,41 +1,56 @@
+use std::path::PathBuf;
+
 use itertools::Itertools;
 
 use crate::ast::canonical::{
     Enum, Method, Module, Parameter, Property, Record, Service, Type, Variant,
 };
-use std::io;
 
-pub fn generate_rust_server(module: &Module) -> Result<(), io::Error> {
+use crate::links;
+
+use super::File;
+
+#[derive(Debug)]
+pub struct Options {
+    pub print: bool,
+    pub output: Option<PathBuf>,
+}
+
+pub fn generate_rust_server(module: &Module) -> Vec<File> {
     let record_package = "records".to_owned();
+    let mut parts = vec![];
     //for decl in module.declarations.iter() {}
-    for (_, record) in &module.records {
-        println!("{}", generate_record(&record_package, record));
+    for record in module.get_sorted_records() {
+        parts.push(generate_record(&record_package, record));
     }
 
-    for (_, enum_value) in &module.enums {
-        println!("{}", generate_enum(&record_package, enum_value));
+    for enum_value in module.get_sorted_enums() {
+        parts.push(generate_enum(&record_package, enum_value));
     }
 
-    for (_, service) in &module.services {
-        println!("{}", generate_service(&record_package, service));
-        println!("{}", generate_router(&record_package, service));
+    for service in module.get_sorted_services() {
+        parts.push(generate_service(&record_package, service));
+        parts.push(generate_router(&record_package, service));
     }
 
-    Ok(())
+    vec![File {
+        path: "lib.rs".into(),
+        content: parts.join("\n"),
+    }]
 }
 
 fn generate_service(package: &String, service: &Service) -> String {
     let methods = service
-        .methods
-        .iter()
-        .map(|(_, method)| generate_method(package, &method))
+        .get_sorted_methods()
+        .into_iter()
+        .map(|method| generate_method(package, &method))
         .collect::<Vec<String>>()
         .join("\n\n");
 
     let requests = service
-        .methods
-        .iter()
-        .map(|(_, method)| generate_request(package, &method))
+        .get_sorted_methods()
+        .into_iter()
+        .map(|method| generate_request(package, &method))
         .collect::<Vec<String>>()
         .join("\n\n");
 
@@ -73,16 +88,16 @@ fn generate_method(package: &String, method: &Method) -> String {
 
 fn generate_router(package: &String, service: &Service) -> String {
     let methods = service
-        .methods
-        .iter()
-        .map(|(_, method)| generate_router_method(package, &service, &method))
+        .get_sorted_methods()
+        .into_iter()
+        .map(|method| generate_router_method(package, &service, &method))
         .collect::<Vec<String>>()
         .join("\n\n");
 
     let routes = service
-        .methods
-        .iter()
-        .map(|(_, method)| {
+        .get_sorted_methods()
+        .into_iter()
+        .map(|method| {
             let service_name = service.name.value.clone();
             let name = method.name.value.clone();
             format!("        .route(\"/{service_name}/{name}\", post({name}))")
@@ -232,7 +247,7 @@ fn generate_doc_comment(indent: &str, comment: &Option<String>) -> String {
     match comment {
         None => "".to_string(),
         Some(comment) => {
-            let comment = comment
+            let comment = generate_doc_links(comment)
                 .split("\n")
                 .map(|line| format!("{indent}/// {line}"))
                 .collect::<Vec<String>>()
@@ -242,3 +257,15 @@ fn generate_doc_comment(indent: &str, comment: &Option<String>) -> String {
         }
     }
 }
+
+/// Translate the links of a doc comment to intra-doc links of
+/// rustdoc, like `[Person]` or `[get](PersonService::get)`.
+fn generate_doc_links(comment: &str) -> String {
+    links::rewrite(comment, |link| {
+        let path = link.target.replace('.', "::");
+        match &link.text {
+            None => Some(format!("[{path}]")),
+            Some(text) => Some(format!("[{text}]({path})")),
+        }
+    })
+}
@@ -2,55 +2,74 @@ use crate::ast::{
     canonical::{Enum, Module, Record, Service, Type},
     source::Name,
 };
+use crate::links;
 use askama::Template;
 use itertools::Itertools;
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::Write;
 use std::path::PathBuf;
-use std::{fs, io};
+
+use super::File;
 
 #[derive(Debug)]
 pub struct Options {
     pub print: bool,
     pub output: Option<PathBuf>,
+    pub module_style: ModuleStyle,
 }
 
-pub fn generate_typescript_client(module: &Module, options: &Options) -> Result<(), io::Error> {
-    let record_package = "records".to_string();
-    let models = generate_models(&record_package, module);
-    let client = generate_client(&record_package, module);
-
-    if options.print {
-        println!("{}", models);
-        println!("{}", client);
-    }
-
-    if let Some(out) = &options.output {
-        fs::create_dir_all(out)?;
-        let mut file = File::create(out.join("models.ts"))?;
-        file.write_all(models.as_bytes())?;
+/// How the client imports the generated models, which depends on
+/// the module resolution of the project.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ModuleStyle {
+    /// `import { .. } from './models.ts'`, e.g. for Deno.
+    #[default]
+    Ts,
+    /// `import { .. } from './models.js'`, e.g. for Node with ESM.
+    Js,
+    /// `import { .. } from './models'`, e.g. for bundlers.
+    Bare,
+}
 
-        let mut file = File::create(out.join("client.ts"))?;
-        file.write_all(client.as_bytes())?;
+impl ModuleStyle {
+    fn import_path(&self, module: &str) -> String {
+        match self {
+            ModuleStyle::Ts => format!("./{module}.ts"),
+            ModuleStyle::Js => format!("./{module}.js"),
+            ModuleStyle::Bare => format!("./{module}"),
+        }
     }
+}
 
-    Ok(())
+pub fn generate_typescript_client(module: &Module, options: &Options) -> Vec<File> {
+    let record_package = "records".to_string();
+    let models = generate_models(&record_package, module);
+    let client = generate_client(&record_package, module, options.module_style);
+
+    vec![
+        File {
+            path: "models.ts".into(),
+            content: models,
+        },
+        File {
+            path: "client.ts".into(),
+            content: client,
+        },
+    ]
 }
 
 fn generate_models(package: &String, module: &Module) -> String {
     //for decl in module.declarations.iter() {}
     let records = &module
-        .records
-        .iter()
-        .map(|(_, record)| generate_record(&package, record))
+        .get_sorted_records()
+        .into_iter()
+        .map(|record| generate_record(&package, record))
         .collect::<Vec<String>>()
         .join("\n\n");
 
     let enums = &module
-        .enums
-        .iter()
-        .map(|(_, record)| generate_enum(&package, record))
+        .get_sorted_enums()
+        .into_iter()
+        .map(|record| generate_enum(&package, record))
         .collect::<Vec<String>>()
         .join("\n\n");
 
@@ -113,13 +132,14 @@ fn generate_enum(package: &String, record: &Enum) -> String {
         .expect("Should render Enum")
 }
 
-fn generate_client(package: &String, module: &Module) -> String {
-    let imports = find_used_types(module).iter().join(", ");
+fn generate_client(package: &String, module: &Module, module_style: ModuleStyle) -> String {
+    let imports = find_used_types(module).iter().sorted().join(", ");
 
     ServiceTemplate {
         package,
         services: &module.get_sorted_services(),
         imports: &imports,
+        models_path: &module_style.import_path("models"),
     }
     .render()
     .expect("Should render Client")
@@ -142,7 +162,7 @@ fn generate_doc_comment(indent: &str, comment: &Option<String>) -> String {
     match comment {
         None => "".to_string(),
         Some(comment) => {
-            let content = comment
+            let content = generate_doc_links(comment)
                 .split("\n")
                 .map(|line| format!("{indent} * {line}"))
                 .join("\n");
@@ -151,6 +171,26 @@ fn generate_doc_comment(indent: &str, comment: &Option<String>) -> String {
     }
 }
 
+/// Translate the links of a doc comment to TSDoc, like
+/// `{@link Person}` or `{@link Person | the person}`.
+fn generate_doc_links(comment: &str) -> String {
+    links::rewrite(comment, |link| {
+        // Methods are uncapitalized in the client.
+        let target = match link.method() {
+            Some((service, method)) => {
+                let mut chars = method.chars();
+                let first = chars.next().map(|c| c.to_lowercase().to_string());
+                format!("{service}.{}{}", first.unwrap_or_default(), chars.as_str())
+            }
+            None => link.target.clone(),
+        };
+        match &link.text {
+            None => Some(format!("{{@link {target}}}")),
+            Some(text) => Some(format!("{{@link {target} | {text}}}")),
+        }
+    })
+}
+
 fn generate_type_ref(package: &String, type_: &Type) -> String {
     match type_ {
         Type::String => "string".to_string(),
@@ -205,12 +245,15 @@ struct ServiceTemplate<'a> {
     services: &'a Vec<&'a Service>,
     package: &'a String,
     imports: &'a String,
+    models_path: &'a String,
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        codegen::typescript::{EnumTemplate, RecordTemplate, ServiceTemplate},
+        codegen::typescript::{
+            generate_doc_comment, EnumTemplate, RecordTemplate, ServiceTemplate,
+        },
         compile,
         error::Error,
     };
@@ -298,6 +341,7 @@ mod tests {
             package: &package,
             services: &vec![&session_service],
             imports: &"".to_string(),
+            models_path: &"./models.ts".to_string(),
         }
         .render()
         .unwrap();
@@ -306,4 +350,13 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_doc_links_are_tsdoc_links() {
+        let comment = Some("See [Person] and [get](#PersonService.Get).".to_string());
+        assert_eq!(
+            generate_doc_comment("", &comment),
+            "/**\n * See {@link Person} and {@link PersonService.get | get}.\n */"
+        );
+    }
 }
@@ -0,0 +1,399 @@
+//! A [`Diagnostic`] is a flat representation of a single compiler
+//! error or warning, which is easier to consume for tools like
+//! editors than the nested [`Error`] itself.
+//!
+//! Diagnostics can be rendered for humans or as JSON and [SARIF] for
+//! other tools, see [`MessageFormat`].
+//!
+//! ## Error codes
+//!
+//! Every diagnostic has a stable code. Codes starting with `E01` are
+//! syntax errors, codes starting with `E02` are found while
+//! canonicalizing a module. Codes starting with `W` are warnings,
+//! which do not stop a module from being compiled.
+//!
+//! | Code  | Problem                                              |
+//! |:------|:-----------------------------------------------------|
+//! | E0100 | A declaration does not start with `data`, `enum` or `service`. |
+//! | E0101 | A declaration is missing its opening `{`.            |
+//! | E0102 | A declaration is missing its closing `}`.            |
+//! | E0103 | A name was expected.                                 |
+//! | E0104 | A comma is missing.                                  |
+//! | E0105 | A property is missing the `:` before its type.       |
+//! | E0106 | A property is missing its type.                      |
+//! | E0107 | A method does not start with `def`.                  |
+//! | E0108 | The parameters are missing their opening `(`.        |
+//! | E0109 | The parameters are missing their closing `)`.        |
+//! | E0110 | A string is not terminated or has an unknown escape. |
+//! | E0111 | A number is malformed.                               |
+//! | E0112 | A comment is malformed.                              |
+//! | E0113 | An unexpected character was found.                   |
+//! | E0114 | The file ended unexpectedly.                         |
+//! | E0115 | An annotation is not closed.                         |
+//! | E0116 | An annotation contains an unexpected token.          |
+//! | E0200 | An annotation uses an unknown symbol.                |
+//! | E0201 | An annotation is empty.                              |
+//! | E0202 | An annotation is not allowed in its place.           |
+//! | E0203 | An example does not match its type.                  |
+//! | E0204 | A mock does not refer to a record with an `id`.      |
+//! | E0205 | An operator of a constraint is missing its argument. |
+//! | W0200 | A link in a doc comment points to nothing.           |
+//!
+//! [SARIF]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+use std::path::Path;
+
+use itertools::Itertools;
+use serde::{Serialize, Serializer};
+use serde_json::json;
+
+use crate::error::canonicalize::Warning;
+use crate::error::{syntax, Error};
+use crate::reporting::{Position, Region, Report, Target, WrpcDocBuilder};
+
+/// The version of the JSON output, which is increased on every
+/// incompatible change.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub code: &'static str,
+    pub severity: Severity,
+    #[serde(serialize_with = "serialize_region")]
+    pub region: Region,
+    pub title: String,
+    pub message: String,
+}
+
+/// The format diagnostics are printed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// Colored reports with source snippets.
+    Human,
+    /// Reports with source snippets, without any colors.
+    Text,
+    /// A JSON document, containing all diagnostics.
+    Json,
+    /// A [SARIF](https://sarifweb.azurewebsites.net/) log.
+    Sarif,
+    /// Reports with source snippets as HTML fragments, e.g. to show
+    /// them in the browser.
+    Html,
+}
+
+/// Returns a [`Diagnostic`] for each problem contained in the given [`Error`].
+///
+/// The message of a diagnostic does not contain any source snippets,
+/// since the region already points to the problematic code.
+pub fn from_error(error: &Error) -> Vec<Diagnostic> {
+    let alloc = WrpcDocBuilder::new("");
+    to_diagnostics(reports(error, &alloc), Severity::Error)
+}
+
+/// Returns a [`Diagnostic`] for each of the given [`Warning`]s.
+pub fn from_warnings(warnings: &[Warning]) -> Vec<Diagnostic> {
+    let alloc = WrpcDocBuilder::new("");
+    to_diagnostics(warning_reports(warnings, &alloc), Severity::Warning)
+}
+
+/// Render the given [`Error`] of the file with the given `source`
+/// in the given [`MessageFormat`].
+pub fn render(filename: &Path, source: &str, error: &Error, format: MessageFormat) -> String {
+    let target = match format {
+        MessageFormat::Human => Target::Terminal,
+        MessageFormat::Text => Target::Text,
+        MessageFormat::Html => Target::Html,
+        MessageFormat::Json => return to_json(filename, &from_error(error)),
+        MessageFormat::Sarif => return to_sarif(filename, &from_error(error)),
+    };
+
+    let alloc = WrpcDocBuilder::new(source);
+    render_reports(filename, reports(error, &alloc), target)
+}
+
+/// Render the given [`Warning`]s of the file with the given `source`
+/// in the given [`MessageFormat`].
+pub fn render_warnings(
+    filename: &Path,
+    source: &str,
+    warnings: &[Warning],
+    format: MessageFormat,
+) -> String {
+    let target = match format {
+        MessageFormat::Human => Target::Terminal,
+        MessageFormat::Text => Target::Text,
+        MessageFormat::Html => Target::Html,
+        MessageFormat::Json => return to_json(filename, &from_warnings(warnings)),
+        MessageFormat::Sarif => return to_sarif(filename, &from_warnings(warnings)),
+    };
+
+    let alloc = WrpcDocBuilder::new(source);
+    render_reports(filename, warning_reports(warnings, &alloc), target)
+}
+
+fn to_diagnostics(
+    reports: Vec<(&'static str, Region, Report)>,
+    severity: Severity,
+) -> Vec<Diagnostic> {
+    reports
+        .into_iter()
+        .map(|(code, region, report)| Diagnostic {
+            code,
+            severity,
+            region,
+            title: report.title.clone(),
+            message: report.doc.pretty(80).to_string().trim_end().to_string(),
+        })
+        .collect()
+}
+
+fn render_reports(
+    filename: &Path,
+    reports: Vec<(&'static str, Region, Report)>,
+    target: Target,
+) -> String {
+    let filename = Some(filename.to_path_buf());
+    reports
+        .into_iter()
+        .map(|(_, _, report)| format!("{}\n", report.render(&filename, target)))
+        .join("\n")
+}
+
+/// Render the diagnostics of the given file as JSON.
+///
+/// The document has the following shape, where `region` contains
+/// 1-based lines and columns and the end is inclusive.
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "file": "spec.wrpc",
+///   "diagnostics": [
+///     {
+///       "code": "E0105",
+///       "severity": "error",
+///       "region": { "start": { "line": 2, "col": 10 }, "end": { "line": 2, "col": 10 } },
+///       "title": "MISSING PROPERTY NAME AND TYPE SEPARATOR",
+///       "message": "..."
+///     }
+///   ]
+/// }
+/// ```
+pub fn to_json(filename: &Path, diagnostics: &[Diagnostic]) -> String {
+    let value = json!({
+        "version": FORMAT_VERSION,
+        "file": filename.to_string_lossy(),
+        "diagnostics": diagnostics,
+    });
+
+    serde_json::to_string_pretty(&value).expect("Diagnostics should be serializable")
+}
+
+/// Render the diagnostics of the given file as a SARIF 2.1.0 log.
+pub fn to_sarif(filename: &Path, diagnostics: &[Diagnostic]) -> String {
+    let rules = diagnostics
+        .iter()
+        .unique_by(|diagnostic| diagnostic.code)
+        .sorted_by_key(|diagnostic| diagnostic.code)
+        .map(|diagnostic| {
+            json!({
+                "id": diagnostic.code,
+                "shortDescription": { "text": diagnostic.title },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let results = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            json!({
+                "ruleId": diagnostic.code,
+                "level": diagnostic.severity,
+                "message": { "text": format!("{}\n\n{}", diagnostic.title, diagnostic.message) },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": filename.to_string_lossy() },
+                        "region": {
+                            "startLine": diagnostic.region.start.line,
+                            "startColumn": diagnostic.region.start.col,
+                            "endLine": diagnostic.region.end.line,
+                            // SARIF columns end exclusively.
+                            "endColumn": diagnostic.region.end.col + 1,
+                        },
+                    },
+                }],
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let value = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "wrpc",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/noobymatze/wrpc",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+
+    serde_json::to_string_pretty(&value).expect("Diagnostics should be serializable")
+}
+
+/// Returns the code, region and report of every problem in the given [`Error`].
+fn reports<'a>(
+    error: &Error,
+    alloc: &'a WrpcDocBuilder,
+) -> Vec<(&'static str, Region, Report<'a>)> {
+    match error {
+        Error::BadSyntax(errors) => errors
+            .iter()
+            .map(|error| {
+                let report = match error {
+                    syntax::Error::ParseError(error) => error.to_report(alloc),
+                };
+                (error.code(), error.region(), report)
+            })
+            .collect(),
+        Error::BadCanonicalization(errors) => errors
+            .iter()
+            .map(|error| (error.code(), error.region(), error.to_report(alloc)))
+            .collect(),
+    }
+}
+
+/// Returns the code, region and report of every given [`Warning`].
+fn warning_reports<'a>(
+    warnings: &[Warning],
+    alloc: &'a WrpcDocBuilder,
+) -> Vec<(&'static str, Region, Report<'a>)> {
+    warnings
+        .iter()
+        .map(|warning| (warning.code(), warning.region(), warning.to_report(alloc)))
+        .collect()
+}
+
+fn serialize_region<S>(region: &Region, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    #[derive(Serialize)]
+    struct Span<'a> {
+        start: &'a Position,
+        end: &'a Position,
+    }
+
+    Span {
+        start: &region.start,
+        end: &region.end,
+    }
+    .serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::canonicalize::check_links;
+    use crate::compile;
+    use crate::diagnostic::{
+        from_error, from_warnings, render, render_warnings, MessageFormat, Severity,
+    };
+    use crate::reporting::Region;
+
+    #[test]
+    fn test_syntax_error_has_region() {
+        let error = compile(None, "data Test {\n    name String,\n}").unwrap_err();
+        let diagnostics = from_error(&error);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].region.start.line, 2);
+    }
+
+    #[test]
+    fn test_canonicalization_error_points_to_annotation() {
+        let source = "data Test {\n    #(check (= foo 1))\n    name: String,\n}";
+        let error = compile(None, source).unwrap_err();
+        let diagnostics = from_error(&error);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].region, Region::line(2, 16, 18));
+        assert!(diagnostics[0].message.contains("`foo`"));
+    }
+
+    #[test]
+    fn test_json_contains_code_and_region() {
+        let error = compile(None, "data Test {\n    name String,\n}").unwrap_err();
+        let json = render(Path::new("test.wrpc"), "", &error, MessageFormat::Json);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"], 1);
+        assert_eq!(value["file"], "test.wrpc");
+        assert_eq!(value["diagnostics"][0]["code"], "E0105");
+        assert_eq!(value["diagnostics"][0]["severity"], "error");
+        assert_eq!(value["diagnostics"][0]["region"]["start"]["line"], 2);
+    }
+
+    #[test]
+    fn test_sarif_columns_end_exclusively() {
+        let source = "data Test {\n    #(check (= foo 1))\n    name: String,\n}";
+        let error = compile(None, source).unwrap_err();
+        let sarif = render(Path::new("test.wrpc"), source, &error, MessageFormat::Sarif);
+        let value: serde_json::Value = serde_json::from_str(&sarif).unwrap();
+        let run = &value["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "E0200");
+        let region = &run["results"][0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startColumn"], 16);
+        assert_eq!(region["endColumn"], 19);
+    }
+
+    #[test]
+    fn test_text_has_no_colors() {
+        let source = "data Test {\n    name String,\n}";
+        let error = compile(None, source).unwrap_err();
+        let text = render(Path::new("test.wrpc"), source, &error, MessageFormat::Text);
+        assert!(!text.contains('\x1b'));
+        assert!(text.contains("2|      name String,"));
+    }
+
+    #[test]
+    fn test_html_is_escaped() {
+        let source = "data Test {\n    name List<String>,\n}";
+        let error = compile(None, source).unwrap_err();
+        let html = render(Path::new("test.wrpc"), source, &error, MessageFormat::Html);
+        assert!(html.starts_with("<pre class=\"report\">"));
+        assert!(html.contains("2|      name List&lt;String&gt;,"));
+        assert!(!html.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_broken_links_are_warnings() {
+        let source = "// Has an [Id](#Id) and a [Name].\ndata Person {\n    // See [Person].\n    name: String,\n}";
+        let module = compile(None, source).unwrap();
+        let warnings = check_links(&module);
+        let diagnostics = from_warnings(&warnings);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].code, "W0200");
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].region, Region::line(2, 6, 11));
+        assert!(diagnostics[0].message.contains("`Id`"));
+        assert!(diagnostics[1].message.contains("`Name`"));
+
+        let json = render_warnings(
+            Path::new("test.wrpc"),
+            source,
+            &warnings,
+            MessageFormat::Json,
+        );
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["diagnostics"][0]["severity"], "warning");
+    }
+}
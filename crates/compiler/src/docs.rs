@@ -1,40 +1,175 @@
-use crate::ast::canonical::{Enum, Module, Parameter, Record, Type};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::ast::canonical::{
+    Enum, Expr, Method, Module, Parameter, Property, Record, Service, Type,
+};
+use crate::ast::constraints::Constraint;
+use crate::ast::source::Name;
+use crate::diagnostic::{self, MessageFormat};
+use crate::error::Error;
+use crate::links;
+use crate::random::Generator;
 
 use askama::Template;
 use itertools::Itertools; // bring trait in scope
+use serde_json::{json, Map, Value};
 
 #[derive(Template)] // this will generate the code...
 #[template(path = "index.html")] // using the template in this path, relative
                                  // to the `templates` dir in the crate root
 struct DocTemplate<'a> {
     module: &'a Module,
+    /// The URL of server-sent events, which reload the page.
+    events: Option<&'a str>,
+    /// The types and methods for the "try it" console, see
+    /// [`render_console`].
+    console: &'a str,
+    links: &'a Links,
+}
+
+/// A page of the static site written by [`render_site`].
+#[derive(Template)]
+#[template(path = "site.html")]
+struct SiteTemplate<'a> {
+    module: &'a Module,
+    title: String,
+    page: SitePage<'a>,
+    console: &'a str,
+    links: &'a Links,
+    /// All declarations for the client-side search, see [`render_search`].
+    search: &'a str,
+}
+
+#[derive(Template)]
+#[template(path = "docs.md", escape = "txt")]
+struct MarkdownTemplate<'a> {
+    module: &'a Module,
+}
+
+enum SitePage<'a> {
+    Index,
+    Service(&'a Service),
+    Types,
+}
+
+/// The pages declarations are documented on, so links like
+/// `#Person` work on a single page as well as on a static site.
+#[derive(Debug, Default)]
+pub struct Links {
+    /// The file of every declaration, which is not on the current page.
+    pages: HashMap<String, String>,
+}
+
+impl Links {
+    /// Link all records, enums and services of the module to a
+    /// single page.
+    pub fn single_page(module: &Module) -> Links {
+        let names = module
+            .records
+            .keys()
+            .chain(module.enums.keys())
+            .chain(module.services.keys())
+            .map(|name| (name.clone(), "".to_string()));
+        Links {
+            pages: names.collect(),
+        }
+    }
+
+    /// Link all records, enums and services of the module to their
+    /// pages of the static site.
+    pub fn site(module: &Module) -> Links {
+        let types = module
+            .records
+            .keys()
+            .chain(module.enums.keys())
+            .map(|name| (name.clone(), TYPES_PAGE.to_string()));
+        let services = module
+            .services
+            .values()
+            .map(|service| (service.name.value.clone(), service_page(service)));
+        Links {
+            pages: types.chain(services).collect(),
+        }
+    }
+
+    /// Returns the link to the declaration with the given name.
+    pub fn href(&self, name: &str) -> String {
+        format!("{}#{name}", self.page(name))
+    }
+
+    /// Returns the file of the declaration with the given name, which
+    /// is empty for the current page.
+    pub fn page(&self, name: &str) -> &str {
+        self.pages.get(name).map(String::as_str).unwrap_or("")
+    }
+
+    /// Render the markdown of a comment, where links like `[Person]`,
+    /// `[Person](#Person)` or `[get](#PersonService.get)` point to the
+    /// page of the declaration.
+    pub fn md_to_html(&self, markdown: &str) -> String {
+        let markdown = links::rewrite(markdown, |link| {
+            let name = link
+                .method()
+                .map_or(link.target.as_str(), |(service, _)| service);
+            let page = self.pages.get(name)?;
+            Some(format!("[{}]({page}#{})", link.text(), link.target))
+        });
+        md_to_html(&markdown)
+    }
+}
+
+/// The page of all records and enums of the static site.
+const TYPES_PAGE: &str = "types.html";
+
+/// Returns the file name of the page of a service.
+fn service_page(service: &Service) -> String {
+    let name = &service.name.value;
+    // `index.html` and `types.html` are taken, even on a file system,
+    // which ignores the case.
+    match name.to_lowercase().as_str() {
+        "index" | "types" => format!("{name}Service.html"),
+        _ => format!("{name}.html"),
+    }
+}
+
+#[derive(Template)]
+#[template(path = "errors.html")]
+struct ErrorTemplate<'a> {
+    diagnostics: &'a str,
+    events: Option<&'a str>,
 }
 
 pub fn md_to_html(val: &String) -> String {
     markdown::to_html(val)
 }
 
-pub fn render_return_type(type_: &Option<Type>) -> String {
+/// Render an example as pretty JSON, which is escaped by the template.
+pub fn render_example(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+pub fn render_return_type(type_: &Option<Type>, links: &Links) -> String {
     match type_ {
-        Some(type_) => format!(": {}", render_type(type_)),
+        Some(type_) => format!(": {}", render_type(type_, links)),
         None => "".to_string(),
     }
 }
 
-pub fn render_parameters(parameters: &Vec<Parameter>) -> String {
+pub fn render_parameters(parameters: &Vec<Parameter>, links: &Links) -> String {
     parameters
         .iter()
         .map(|parameter| {
             format!(
                 "{}: {}",
                 parameter.name.value,
-                render_type(&parameter.type_)
+                render_type(&parameter.type_, links)
             )
         })
         .join(", ")
 }
 
-pub fn render_type(type_: &Type) -> String {
+pub fn render_type(type_: &Type, links: &Links) -> String {
     match type_ {
         Type::String => format!("<span class=\"type\">String</span>"),
         Type::Boolean => format!("<span class=\"type\">Boolean</span>"),
@@ -45,41 +180,197 @@ pub fn render_type(type_: &Type) -> String {
         Type::Map(key, value) => {
             format!(
                 "<span class=\"type\">Map</span><{}, {}>",
-                render_type(key),
-                render_type(value)
+                render_type(key, links),
+                render_type(value, links)
             )
         }
         Type::Result(error, value) => {
             format!(
                 "<span class=\"type\">Result</span><{}, {}>",
-                render_type(error),
-                render_type(value)
+                render_type(error, links),
+                render_type(value, links)
             )
         }
-        Type::List(value) => format!("<span class=\"type\">List</span><{}>", render_type(value)),
-        Type::Set(_) => "Set".to_string(),
-        Type::Option(value) => format!("{}?", render_type(value)),
+        Type::List(value) => format!(
+            "<span class=\"type\">List</span><{}>",
+            render_type(value, links)
+        ),
+        Type::Set(value) => format!(
+            "<span class=\"type\">Set</span><{}>",
+            render_type(value, links)
+        ),
+        Type::Option(value) => format!("{}?", render_type(value, links)),
         Type::Ref(name, _) => format!(
-            "<a href=\"#{}\" class=\"type type--custom\">{}</a>",
-            name, name
+            "<a href=\"{}\" class=\"type type--custom\">{}</a>",
+            links.href(name),
+            name
         ),
     }
 }
 
-pub fn render_record(record: &Record) -> String {
+/// Describe the constraints of a property or parameter with the given
+/// name for humans, like `length ≤ 5, not blank`. Constraints of a
+/// record have an empty name.
+pub fn render_constraints(constraints: &[Constraint], name: &str) -> String {
+    constraints
+        .iter()
+        .map(|constraint| constraint.describe(Some(name)))
+        .join(", ")
+}
+
+/// Returns the note of a `#(deprecated "Use get instead.")`
+/// annotation, which is empty for a plain `#(deprecated)`.
+pub fn deprecation(annotations: &[Expr]) -> Option<String> {
+    annotations.iter().find_map(|annotation| {
+        let Expr::List(_, expressions) = annotation else {
+            return None;
+        };
+
+        match expressions.as_slice() {
+            [Expr::Symbol(_, value), Expr::String(_, note), ..] if value == "deprecated" => {
+                Some(note.clone())
+            }
+            [Expr::Symbol(_, value), ..] if value == "deprecated" => Some("".to_string()),
+            _ => None,
+        }
+    })
+}
+
+/// Whether the property has anything to show besides its name and
+/// type.
+pub fn has_details(property: &Property) -> bool {
+    property.comment.is_some()
+        || !property.constraints.is_empty()
+        || !property.deps.is_empty()
+        || !property.examples.is_empty()
+        || deprecation(&property.annotations).is_some()
+}
+
+/// Whether any parameter of a method has a comment or constraints.
+pub fn has_parameter_details(parameters: &[Parameter]) -> bool {
+    parameters.iter().any(|parameter| {
+        parameter.comment.is_some()
+            || !parameter.constraints.is_empty()
+            || deprecation(&parameter.annotations).is_some()
+    })
+}
+
+/// Describe all types and methods of the module as JSON, which the
+/// "try it" console of the documentation builds its forms from.
+///
+/// Every method has an example request, which prefers the examples
+/// of records and properties over random values. The JSON can be
+/// embedded into a `<script>` element as is.
+pub fn render_console(module: &Module) -> String {
+    let records = module
+        .records
+        .values()
+        .map(|record| {
+            let description = json!({
+                "variables": names(&record.type_variables),
+                "properties": describe_properties(&record.properties),
+            });
+            (record.name.value.clone(), description)
+        })
+        .collect::<Map<String, Value>>();
+
+    let enums = module
+        .enums
+        .values()
+        .map(|enum_| {
+            let variants = enum_
+                .variants
+                .iter()
+                .map(|variant| {
+                    json!({
+                        "name": variant.name.value,
+                        "properties": describe_properties(&variant.properties),
+                    })
+                })
+                .collect::<Vec<_>>();
+            let description = json!({
+                "variables": names(&enum_.type_variables),
+                "simple": enum_.is_simple(),
+                "variants": variants,
+            });
+            (enum_.name.value.clone(), description)
+        })
+        .collect::<Map<String, Value>>();
+
+    let mut generator = Generator::new(module, 0).with_examples();
+    let methods = module
+        .services
+        .values()
+        .flat_map(|service| {
+            service
+                .methods
+                .values()
+                .map(move |method| (service, method))
+        })
+        .map(|(service, method)| {
+            let parameters = method
+                .parameters
+                .iter()
+                .map(|parameter| describe(&parameter.name, &parameter.type_))
+                .collect::<Vec<_>>();
+            let example = method
+                .parameters
+                .iter()
+                .map(|parameter| {
+                    let value = generator.generate(&parameter.type_);
+                    (parameter.name.value.clone(), value)
+                })
+                .collect::<Map<String, Value>>();
+            let description = json!({"parameters": parameters, "example": example});
+            (service.get_method_path(method), description)
+        })
+        .collect::<Map<String, Value>>();
+
+    let console = json!({"records": records, "enums": enums, "methods": methods});
+    // A `</script>` in a string must not end the element early.
+    console.to_string().replace('<', "\\u003c")
+}
+
+fn names(names: &[Name]) -> Vec<&str> {
+    names.iter().map(|name| name.value.as_str()).collect()
+}
+
+fn describe_properties(properties: &[Property]) -> Vec<Value> {
+    properties
+        .iter()
+        .map(|property| describe(&property.name, &property.type_))
+        .collect()
+}
+
+/// Describe a parameter or property with its type for the console.
+fn describe(name: &Name, type_: &Type) -> Value {
+    json!({"name": name.value, "type": type_, "label": type_.to_string()})
+}
+
+pub fn render_record(record: &Record, links: &Links) -> String {
+    let keyword = "<span class=\"keyword\">data</span>";
+    record_source(record, keyword, &|type_| render_type(type_, links))
+}
+
+pub fn render_enum(record: &Enum, links: &Links) -> String {
+    let keyword = "<span class=\"keyword\">enum</span>";
+    enum_source(record, keyword, &|type_| render_type(type_, links))
+}
+
+/// Render the declaration of a record, where the keyword and types
+/// are already rendered for HTML or Markdown.
+fn record_source(record: &Record, keyword: &str, render: &dyn Fn(&Type) -> String) -> String {
     let props = record
         .properties
         .iter()
-        .map(|prop| format!("    {}: {},\n", prop.name.value, render_type(&prop.type_)))
+        .map(|prop| format!("    {}: {},\n", prop.name.value, render(&prop.type_)))
         .join("");
 
-    format!(
-        "<span class=\"keyword\">data</span> {} {{\n{}}}",
-        record.name.value, props
-    )
+    format!("{keyword} {} {{\n{}}}", record.name.value, props)
 }
 
-pub fn render_enum(record: &Enum) -> String {
+/// Render the declaration of an enum like [`record_source`].
+fn enum_source(record: &Enum, keyword: &str, render: &dyn Fn(&Type) -> String) -> String {
     let variants = record
         .variants
         .iter()
@@ -91,13 +382,7 @@ pub fn render_enum(record: &Enum) -> String {
                 let props = variant
                     .properties
                     .iter()
-                    .map(|prop| {
-                        format!(
-                            "        {}: {},\n",
-                            prop.name.value,
-                            render_type(&prop.type_)
-                        )
-                    })
+                    .map(|prop| format!("        {}: {},\n", prop.name.value, render(&prop.type_)))
                     .join("");
 
                 format!("    {} {{\n{}    }},", variant.name.value, props)
@@ -105,14 +390,355 @@ pub fn render_enum(record: &Enum) -> String {
         })
         .join("\n");
 
-    format!(
-        "<span class=\"keyword\">enum</span> {} {{\n{}\n}}",
-        record.name.value, variants
-    )
+    format!("{keyword} {} {{\n{}\n}}", record.name.value, variants)
 }
 
-/// Render the
+/// Render the documentation of the module as a single HTML page.
 pub fn render(module: &Module) -> String {
-    let doc = DocTemplate { module: module };
+    render_live(module, None)
+}
+
+/// Render the documentation of the module, which reloads itself on
+/// every `reload` event sent to the given URL of server-sent events.
+pub fn render_live(module: &Module, events: Option<&str>) -> String {
+    let module = &links::unlink_unresolved(module);
+    let console = render_console(module);
+    let doc = DocTemplate {
+        module,
+        events,
+        console: &console,
+        links: &Links::single_page(module),
+    };
     doc.render().unwrap()
 }
+
+/// Render the documentation as a static site with an `index.html`,
+/// a page per service and a page of all types. Returns the file name
+/// and content of every page.
+pub fn render_site(module: &Module) -> Vec<(String, String)> {
+    let module = &links::unlink_unresolved(module);
+    let links = Links::site(module);
+    let console = render_console(module);
+    let search = render_search(module, &links);
+    let page = |title: String, page: SitePage| {
+        let site = SiteTemplate {
+            module,
+            title,
+            page,
+            console: &console,
+            links: &links,
+            search: &search,
+        };
+        site.render().unwrap()
+    };
+
+    let mut pages = vec![
+        (
+            "index.html".to_string(),
+            page("Docs".to_string(), SitePage::Index),
+        ),
+        (
+            TYPES_PAGE.to_string(),
+            page("Types".to_string(), SitePage::Types),
+        ),
+    ];
+    for service in module.get_sorted_services() {
+        let title = service.name.value.clone();
+        pages.push((
+            service_page(service),
+            page(title, SitePage::Service(service)),
+        ));
+    }
+
+    pages
+}
+
+/// Describe every service, method, record and enum for the search of
+/// the static site as JSON, which can be embedded into a `<script>`.
+fn render_search(module: &Module, links: &Links) -> String {
+    let summary = |comment: &Option<String>| {
+        let comment = comment.as_deref().unwrap_or("");
+        comment.lines().next().unwrap_or("").to_string()
+    };
+    let entry = |name: String, kind: &str, href: String, comment: &Option<String>| json!({"name": name, "kind": kind, "href": href, "text": summary(comment)});
+
+    let mut entries = vec![];
+    for service in module.get_sorted_services() {
+        let name = &service.name.value;
+        entries.push(entry(
+            name.clone(),
+            "service",
+            links.href(name),
+            &service.comment,
+        ));
+        for method in service.get_sorted_methods() {
+            let anchor = format!("{name}.{}", method.name.value);
+            let href = format!("{}#{anchor}", links.page(name));
+            entries.push(entry(anchor, "method", href, &method.comment));
+        }
+    }
+
+    for record in module.get_sorted_records() {
+        let name = &record.name.value;
+        entries.push(entry(
+            name.clone(),
+            "data",
+            links.href(name),
+            &record.comment,
+        ));
+    }
+
+    for enum_ in module.get_sorted_enums() {
+        let name = &enum_.name.value;
+        entries.push(entry(
+            name.clone(),
+            "enum",
+            links.href(name),
+            &enum_.comment,
+        ));
+    }
+
+    Value::Array(entries).to_string().replace('<', "\\u003c")
+}
+
+/// Render the documentation of the module as a single Markdown
+/// document, which shows the same as the HTML documentation.
+pub fn render_markdown(module: &Module) -> String {
+    let module = &links::unlink_unresolved(module);
+    let markdown = MarkdownTemplate { module }.render().unwrap();
+
+    // The template leaves blank lines, wherever something is missing.
+    let mut document = String::new();
+    let mut blank = true;
+    for line in markdown.lines().map(str::trim_end) {
+        if line.is_empty() && blank {
+            continue;
+        }
+        blank = line.is_empty();
+        document.push_str(line);
+        document.push('\n');
+    }
+
+    document.trim_end().to_string() + "\n"
+}
+
+pub fn markdown_signature(method: &Method) -> String {
+    let parameters = method
+        .parameters
+        .iter()
+        .map(|parameter| format!("{}: {}", parameter.name.value, parameter.type_))
+        .join(", ");
+    match &method.return_type {
+        Some(type_) => format!("{}({parameters}): {type_}", method.name.value),
+        None => format!("{}({parameters})", method.name.value),
+    }
+}
+
+pub fn markdown_record(record: &Record) -> String {
+    record_source(record, "data", &|type_| type_.to_string())
+}
+
+pub fn markdown_enum(record: &Enum) -> String {
+    enum_source(record, "enum", &|type_| type_.to_string())
+}
+
+pub fn markdown_deprecation(note: &str) -> String {
+    match note {
+        "" => "**Deprecated**".to_string(),
+        note => format!("**Deprecated:** {note}"),
+    }
+}
+
+/// Describe a property in a single cell of a Markdown table, like in
+/// the table of properties of the HTML documentation.
+pub fn markdown_property(property: &Property) -> String {
+    let mut parts = vec![];
+    if let Some(note) = deprecation(&property.annotations) {
+        parts.push(markdown_deprecation(&note));
+    }
+    parts.extend(property.comment.clone());
+    if !property.constraints.is_empty() {
+        let constraints = render_constraints(&property.constraints, &property.name.value);
+        parts.push(format!("Constraints: {constraints}"));
+    }
+    if !property.deps.is_empty() {
+        let deps = property
+            .deps
+            .iter()
+            .map(|dep| format!("`{dep}`"))
+            .join(", ");
+        parts.push(format!("Depends on {deps}"));
+    }
+    for example in &property.examples {
+        parts.push(format!("Example: `{example}`"));
+    }
+    markdown_cell(&parts)
+}
+
+/// Describe a parameter like [`markdown_property`].
+pub fn markdown_parameter(parameter: &Parameter) -> String {
+    let mut parts = vec![];
+    if let Some(note) = deprecation(&parameter.annotations) {
+        parts.push(markdown_deprecation(&note));
+    }
+    parts.extend(parameter.comment.clone());
+    if !parameter.constraints.is_empty() {
+        let constraints = render_constraints(&parameter.constraints, &parameter.name.value);
+        parts.push(format!("Constraints: {constraints}"));
+    }
+    markdown_cell(&parts)
+}
+
+/// Join the parts into a cell of a table, which cannot span multiple
+/// lines.
+fn markdown_cell(parts: &[String]) -> String {
+    parts
+        .iter()
+        .map(|part| part.split_whitespace().join(" ").replace('|', "\\|"))
+        .join("<br>")
+}
+
+/// Render a page showing the diagnostics of the given error, which
+/// reloads itself like [`render_live`].
+pub fn render_errors(filename: &Path, source: &str, error: &Error, events: Option<&str>) -> String {
+    let diagnostics = diagnostic::render(filename, source, error, MessageFormat::Html);
+    let page = ErrorTemplate {
+        diagnostics: &diagnostics,
+        events,
+    };
+    page.render().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::docs::{render, render_console, render_markdown, render_site};
+
+    #[test]
+    fn test_console_is_prefilled_with_examples() {
+        let spec = r#"
+data Person {
+    #(example "</script>")
+    name: String,
+}
+
+service PersonService {
+    def insert(person: Person, notify: Boolean?)
+}
+"#;
+        let module = crate::compile(None, spec).unwrap();
+        let console = render_console(&module);
+        assert!(!console.contains("</script>"));
+
+        let console: Value = serde_json::from_str(&console).unwrap();
+        let method = &console["methods"]["/PersonService/insert"];
+        assert_eq!(method["example"]["person"], json!({"name": "</script>"}));
+        assert_eq!(method["parameters"][1]["label"], "Boolean?");
+        assert_eq!(
+            console["records"]["Person"]["properties"][0]["type"],
+            "String"
+        );
+    }
+
+    #[test]
+    fn test_constraints_and_deprecations_are_shown() {
+        let spec = r#"
+#(check (< .min .max))
+data Range {
+    min: Int32,
+    #(check (<= .max 10))
+    max: Int32,
+    #(check (<= (len .name) 5) (not (blank .name)) (< .min (len .name)))
+    #(deprecated "Use label instead.")
+    name: String,
+}
+"#;
+        let module = crate::compile(None, spec).unwrap();
+        let page = render(&module);
+        assert!(page.contains("min &lt; max"));
+        assert!(page.contains("max ≤ 10"));
+        assert!(page.contains("length ≤ 5, not blank, min &lt; length"));
+        assert!(page.contains("Depends on min"));
+        assert!(page.contains("<strong>Deprecated</strong>: Use label instead."));
+    }
+
+    #[test]
+    fn test_site_links_between_pages() {
+        let spec = r#"
+// Stored with an [Id](#Id).
+data Person {
+    id: Id,
+}
+
+data Id {
+    value: Int64,
+}
+
+service Types {
+    def get(id: Id): Person
+}
+"#;
+        let module = crate::compile(None, spec).unwrap();
+        let pages = render_site(&module);
+        let names = pages
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["index.html", "types.html", "TypesService.html"]);
+
+        let (_, service) = &pages[2];
+        assert!(service.contains("href=\"types.html#Person\""));
+        assert!(service.contains("data-path=\"/Types/get\""));
+
+        let (_, types) = &pages[1];
+        assert!(types.contains("href='types.html#Id'"));
+        assert!(types.contains("\"href\":\"TypesService.html#Types.get\""));
+    }
+
+    #[test]
+    fn test_bare_links_point_to_declarations() {
+        let spec = r#"
+// Returned by [PersonService.get], unlike [Unknown].
+data Person {
+    name: String,
+}
+
+service PersonService {
+    def get(name: String): Person
+}
+"#;
+        let module = crate::compile(None, spec).unwrap();
+        let html = render(&module);
+        assert!(html.contains("<a href='#PersonService.get'>PersonService.get</a>"));
+        assert!(html.contains("unlike Unknown."));
+
+        let (_, types) = &render_site(&module)[1];
+        assert!(types.contains("href='PersonService.html#PersonService.get'"));
+    }
+
+    #[test]
+    fn test_markdown_shows_the_same_as_html() {
+        let spec = r#"
+data Range {
+    // The smallest value.
+    #(check (>= .min 0))
+    min: Int32,
+    #(deprecated "Use | instead.")
+    max: Int32,
+}
+
+service RangeService {
+    def get(#(check (> .id 0)) id: Int64): Range?
+}
+"#;
+        let module = crate::compile(None, spec).unwrap();
+        let markdown = render_markdown(&module);
+        assert!(markdown.contains("```\nget(id: Int64): Range?\n```"));
+        assert!(markdown.contains("| `id` | Constraints: id > 0 |"));
+        assert!(markdown.contains("data Range {\n    min: Int32,\n    max: Int32,\n}"));
+        assert!(markdown.contains("| `min` | The smallest value.<br>Constraints: min ≥ 0 |"));
+        assert!(markdown.contains("| `max` | **Deprecated:** Use \\| instead. |"));
+        assert!(!markdown.contains("\n\n\n"));
+    }
+}
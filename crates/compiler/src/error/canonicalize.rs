@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{ast::source::Name, reporting::Region};
+use crate::{
+    ast::source::Name,
+    reporting::{Region, Report, WrpcDocBuilder},
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum Error {
@@ -54,4 +57,180 @@ pub enum Annotation {
     UnknownSymbol(Region, String),
     Empty(Region),
     InvalidAnnotation(Region),
+    /// An `#(example ...)`, which cannot be converted to JSON or does
+    /// not match its type.
+    BadExample(Region, String),
+    /// A `#(mock ...)`, which does not refer to a record with an `id`.
+    BadMock(Region, String),
+    /// A constraint, whose operator, like `not`, has no argument.
+    MissingArgument(Region, String),
+}
+
+/// A problem, which does not stop a module from being compiled.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum Warning {
+    /// A link in the doc comment of a declaration, which points to
+    /// nothing. The region is the one of the name of the declaration,
+    /// since comments have no regions.
+    BrokenLink {
+        region: Region,
+        location: String,
+        target: String,
+    },
+}
+
+impl Error {
+    /// Returns the [`Region`] in the source, where this error occurred.
+    pub fn region(&self) -> Region {
+        self.annotation().region()
+    }
+
+    /// Returns the stable code of this error, see
+    /// [`diagnostic`](crate::diagnostic) for a list of all codes.
+    pub fn code(&self) -> &'static str {
+        match self.annotation() {
+            Annotation::UnknownSymbol(_, _) => "E0200",
+            Annotation::Empty(_) => "E0201",
+            Annotation::InvalidAnnotation(_) => "E0202",
+            Annotation::BadExample(_, _) => "E0203",
+            Annotation::BadMock(_, _) => "E0204",
+            Annotation::MissingArgument(_, _) => "E0205",
+        }
+    }
+
+    pub fn to_report<'a>(&self, alloc: &'a WrpcDocBuilder) -> Report<'a> {
+        let annotation = self.annotation();
+        let location = match self {
+            Error::BadRecord(name, Record::BadProperty(property, _)) => {
+                format!("the property `{}` of `{}`", property.value, name.value)
+            }
+            Error::BadRecord(name, Record::BadAnnotation(_)) => format!("`{}`", name.value),
+            Error::BadEnum(name, Enum::BadVariant(variant, Variant::BadProperty(property, _))) => {
+                format!(
+                    "the property `{}` of `{}.{}`",
+                    property.value, name.value, variant.value
+                )
+            }
+            Error::BadEnum(name, Enum::BadVariant(variant, Variant::BadAnnotation(_))) => {
+                format!("the variant `{}.{}`", name.value, variant.value)
+            }
+            Error::BadEnum(name, Enum::BadAnnotation(_)) => format!("`{}`", name.value),
+            Error::BadService(name, Service::BadMethod(method, Method::BadParameter(param, _))) => {
+                format!(
+                    "the parameter `{}` of `{}.{}`",
+                    param.value, name.value, method.value
+                )
+            }
+            Error::BadService(name, Service::BadMethod(method, Method::BadAnnotation(_))) => {
+                format!("the method `{}.{}`", name.value, method.value)
+            }
+            Error::BadService(name, Service::BadAnnotation(_)) => format!("`{}`", name.value),
+        };
+
+        let hint = match annotation {
+            Annotation::UnknownSymbol(_, symbol) => alloc.reflow(format!(
+                "I don't know the symbol `{symbol}`. Other properties can be \
+                 accessed with a leading dot, like `.name`."
+            )),
+            Annotation::Empty(_) => alloc.reflow(
+                "The annotation is empty. An annotation needs at least a \
+                 name, like `#(check (not (blank .name)))`.",
+            ),
+            Annotation::InvalidAnnotation(_) => {
+                alloc.reflow("This kind of annotation is not allowed here.")
+            }
+            Annotation::BadExample(_, message) | Annotation::BadMock(_, message) => {
+                alloc.reflow(message.clone())
+            }
+            Annotation::MissingArgument(_, operator) => alloc.reflow(format!(
+                "`{operator}` needs an argument, like `({operator} .name)`."
+            )),
+        };
+
+        Report {
+            title: "BAD ANNOTATION".to_owned(),
+            doc: alloc.stack([
+                alloc.reflow(format!(
+                    "I found a problem with an annotation of {location}:"
+                )),
+                alloc.snippet(&annotation.region()),
+                hint,
+            ]),
+        }
+    }
+
+    /// Returns the annotation, which caused this error.
+    pub fn annotation(&self) -> &Annotation {
+        match self {
+            Error::BadRecord(_, Record::BadProperty(_, Property::BadAnnotation(annotation)))
+            | Error::BadRecord(_, Record::BadAnnotation(annotation))
+            | Error::BadEnum(
+                _,
+                Enum::BadVariant(_, Variant::BadProperty(_, Property::BadAnnotation(annotation))),
+            )
+            | Error::BadEnum(_, Enum::BadVariant(_, Variant::BadAnnotation(annotation)))
+            | Error::BadEnum(_, Enum::BadAnnotation(annotation))
+            | Error::BadService(
+                _,
+                Service::BadMethod(
+                    _,
+                    Method::BadParameter(_, Parameter::BadAnnotation(annotation)),
+                ),
+            )
+            | Error::BadService(_, Service::BadMethod(_, Method::BadAnnotation(annotation)))
+            | Error::BadService(_, Service::BadAnnotation(annotation)) => annotation,
+        }
+    }
+}
+
+impl Annotation {
+    fn region(&self) -> Region {
+        match self {
+            Annotation::UnknownSymbol(region, _)
+            | Annotation::Empty(region)
+            | Annotation::InvalidAnnotation(region)
+            | Annotation::BadExample(region, _)
+            | Annotation::BadMock(region, _)
+            | Annotation::MissingArgument(region, _) => region.clone(),
+        }
+    }
+}
+
+impl Warning {
+    /// Returns the [`Region`] in the source, where this warning occurred.
+    pub fn region(&self) -> Region {
+        match self {
+            Warning::BrokenLink { region, .. } => region.clone(),
+        }
+    }
+
+    /// Returns the stable code of this warning, see
+    /// [`diagnostic`](crate::diagnostic) for a list of all codes.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Warning::BrokenLink { .. } => "W0200",
+        }
+    }
+
+    pub fn to_report<'a>(&self, alloc: &'a WrpcDocBuilder) -> Report<'a> {
+        match self {
+            Warning::BrokenLink {
+                region,
+                location,
+                target,
+            } => Report {
+                title: "BROKEN LINK".to_owned(),
+                doc: alloc.stack([
+                    alloc.reflow(format!(
+                        "The doc comment of {location} links to `{target}`, but I cannot find it:"
+                    )),
+                    alloc.snippet(region),
+                    alloc.reflow(
+                        "Links can point to records, enums, services and methods, \
+                         like `[Person]` or `[get](#PersonService.get)`.",
+                    ),
+                ]),
+            },
+        }
+    }
 }
@@ -299,3 +299,285 @@ impl Decl {
         }
     }
 }
+
+impl Error {
+    /// Returns the [`Region`] in the source, where this error occurred.
+    pub fn region(&self) -> Region {
+        match self {
+            Error::ParseError(Module::Decl(decl)) => decl.region(),
+        }
+    }
+
+    /// Returns the stable code of this error, see
+    /// [`diagnostic`](crate::diagnostic) for a list of all codes.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::ParseError(Module::Decl(decl)) => decl.code(),
+        }
+    }
+}
+
+impl Decl {
+    fn region(&self) -> Region {
+        match self {
+            Decl::BadStart(line, col) => Region::line(*line, *col, *col),
+            Decl::BadData(data) => data.region(),
+            Decl::BadService(service) => service.region(),
+            Decl::BadEnum(enum_) => enum_.region(),
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Decl::BadStart(_, _) => "E0100",
+            Decl::BadData(data) => data.code(),
+            Decl::BadService(service) => service.code(),
+            Decl::BadEnum(enum_) => enum_.code(),
+        }
+    }
+}
+
+impl Data {
+    fn region(&self) -> Region {
+        match self {
+            Data::BadName(name) => name.region(),
+            Data::BadComment(token) => token.region(),
+            Data::BadProperty(property) => property.region(),
+            Data::BadType(type_) => type_.region(),
+            Data::MissingStart(line, col) | Data::MissingEnd(_, line, col) => {
+                Region::line(*line, *col, *col)
+            }
+            Data::BadAnnotation(annotation) => annotation.region(),
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Data::BadName(name) => name.code(),
+            Data::BadComment(token) => token.code(),
+            Data::BadProperty(property) => property.code(),
+            Data::BadType(type_) => type_.code(),
+            Data::MissingStart(_, _) => "E0101",
+            Data::MissingEnd(_, _, _) => "E0102",
+            Data::BadAnnotation(annotation) => annotation.code(),
+        }
+    }
+}
+
+impl Enum {
+    fn region(&self) -> Region {
+        match self {
+            Enum::BadName(name) => name.region(),
+            Enum::BadComment(token) => token.region(),
+            Enum::BadVariant(variant) => variant.region(),
+            Enum::BadType(type_) => type_.region(),
+            Enum::MissingStart(line, col) | Enum::MissingEnd(line, col) => {
+                Region::line(*line, *col, *col)
+            }
+            Enum::BadAnnotation(annotation) => annotation.region(),
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Enum::BadName(name) => name.code(),
+            Enum::BadComment(token) => token.code(),
+            Enum::BadVariant(variant) => variant.code(),
+            Enum::BadType(type_) => type_.code(),
+            Enum::MissingStart(_, _) => "E0101",
+            Enum::MissingEnd(_, _) => "E0102",
+            Enum::BadAnnotation(annotation) => annotation.code(),
+        }
+    }
+}
+
+impl Service {
+    fn region(&self) -> Region {
+        match self {
+            Service::BadName(name) => name.region(),
+            Service::BadMethod(method) => method.region(),
+            Service::MissingStart(line, col) | Service::MissingEnd(_, line, col) => {
+                Region::line(*line, *col, *col)
+            }
+            Service::BadAnnotation(annotation) => annotation.region(),
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Service::BadName(name) => name.code(),
+            Service::BadMethod(method) => method.code(),
+            Service::MissingStart(_, _) => "E0101",
+            Service::MissingEnd(_, _, _) => "E0102",
+            Service::BadAnnotation(annotation) => annotation.code(),
+        }
+    }
+}
+
+impl Variant {
+    fn region(&self) -> Region {
+        match self {
+            Variant::BadName(name) => name.region(),
+            Variant::BadComment(token) => token.region(),
+            Variant::BadAnnotation(annotation) => annotation.region(),
+            Variant::BadProperty(property) => property.region(),
+            Variant::BadReturnType(type_) => type_.region(),
+            Variant::MissingComma(line, col)
+            | Variant::MissingParamStart(_, line, col)
+            | Variant::MissingParamEnd(_, line, col) => Region::line(*line, *col, *col),
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Variant::BadName(name) => name.code(),
+            Variant::BadComment(token) => token.code(),
+            Variant::BadAnnotation(annotation) => annotation.code(),
+            Variant::BadProperty(property) => property.code(),
+            Variant::BadReturnType(type_) => type_.code(),
+            Variant::MissingComma(_, _) => "E0104",
+            Variant::MissingParamStart(_, _, _) => "E0108",
+            Variant::MissingParamEnd(_, _, _) => "E0109",
+        }
+    }
+}
+
+impl Method {
+    fn region(&self) -> Region {
+        match self {
+            Method::BadName(name) => name.region(),
+            Method::BadComment(token) => token.region(),
+            Method::BadParam(property) => property.region(),
+            Method::BadReturnType(type_) => type_.region(),
+            Method::MissingDef(line, col)
+            | Method::MissingParamStart(_, line, col)
+            | Method::MissingParamEnd(_, line, col) => Region::line(*line, *col, *col),
+            Method::BadAnnotation(annotation) => annotation.region(),
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Method::BadName(name) => name.code(),
+            Method::BadComment(token) => token.code(),
+            Method::BadParam(property) => property.code(),
+            Method::BadReturnType(type_) => type_.code(),
+            Method::MissingDef(_, _) => "E0107",
+            Method::MissingParamStart(_, _, _) => "E0108",
+            Method::MissingParamEnd(_, _, _) => "E0109",
+            Method::BadAnnotation(annotation) => annotation.code(),
+        }
+    }
+}
+
+impl Annotation {
+    fn region(&self) -> Region {
+        match self {
+            Annotation::BadExpr(expr) => expr.region(),
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Annotation::BadExpr(expr) => expr.code(),
+        }
+    }
+}
+
+impl Type {
+    fn region(&self) -> Region {
+        match self {
+            Type::BadName(name) => name.region(),
+            Type::MissingComma(position) => Region::from_position(position, position),
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Type::BadName(name) => name.code(),
+            Type::MissingComma(_) => "E0104",
+        }
+    }
+}
+
+impl Property {
+    fn region(&self) -> Region {
+        match self {
+            Property::BadName(name) => name.region(),
+            Property::BadType(_, type_) => type_.region(),
+            Property::BadComment(token) => token.region(),
+            Property::MissingComma(line, col) | Property::MissingColon(_, line, col) => {
+                Region::line(*line, *col, *col)
+            }
+            Property::MissingType(region) => region.clone(),
+            Property::BadAnnotation(annotation) => annotation.region(),
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Property::BadName(name) => name.code(),
+            Property::BadType(_, type_) => type_.code(),
+            Property::BadComment(token) => token.code(),
+            Property::MissingComma(_, _) => "E0104",
+            Property::MissingColon(_, _, _) => "E0105",
+            Property::MissingType(_) => "E0106",
+            Property::BadAnnotation(annotation) => annotation.code(),
+        }
+    }
+}
+
+impl Token {
+    fn region(&self) -> Region {
+        let position = self.position();
+        Region::from_position(&position, &position)
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Token::String(_, _, _) => "E0110",
+            Token::Number(_, _, _) => "E0111",
+            Token::Comment(_, _, _) => "E0112",
+            Token::BadChar(_, _, _) => "E0113",
+            Token::Eof(_, _) => "E0114",
+        }
+    }
+}
+
+impl Name {
+    fn region(&self) -> Region {
+        match self {
+            Name::BadToken(token) => token.region(),
+            Name::ExpectedName(line, col) => Region::line(*line, *col, *col),
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Name::BadToken(token) => token.code(),
+            Name::ExpectedName(_, _) => "E0103",
+        }
+    }
+}
+
+impl Expr {
+    fn region(&self) -> Region {
+        match self {
+            Expr::String(_, line, col) | Expr::Number(_, line, col) | Expr::Endless(line, col) => {
+                Region::line(*line, *col, *col)
+            }
+            Expr::Unexpected(region, _) => region.clone(),
+            Expr::BadToken(token) => token.region(),
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Expr::String(_, _, _) => "E0110",
+            Expr::Number(_, _, _) => "E0111",
+            Expr::Endless(_, _) => "E0115",
+            Expr::Unexpected(_, _) => "E0116",
+            Expr::BadToken(token) => token.code(),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! A stable, versioned JSON representation of a module, meant to be
+//! consumed by other tools.
+//!
+//! Every document is wrapped in an envelope, that contains the
+//! [`FORMAT_VERSION`] and the [`Stage`] of the module.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "stage": "canonical",
+//!   "module": { "enums": {}, "records": {}, "services": {} }
+//! }
+//! ```
+//!
+//! The keys of all objects are sorted. If regions are included, each
+//! name contains a `region` of the form `[startLine, startCol,
+//! endLine, endCol]`, where lines and columns start at 1 and the end
+//! is inclusive.
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::ast::canonical as can;
+use crate::ast::source as src;
+
+/// The version of the exported JSON, which is increased on every
+/// incompatible change.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The stage of the compiler, a module is exported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// The module as written by the user, see [`src::Module`].
+    Source,
+    /// The module after canonicalization, see [`can::Module`].
+    Canonical,
+}
+
+impl Stage {
+    fn name(&self) -> &'static str {
+        match self {
+            Stage::Source => "source",
+            Stage::Canonical => "canonical",
+        }
+    }
+}
+
+/// Export the given source module, including the region of each name
+/// if `regions` is set.
+pub fn source(module: &src::Module, regions: bool) -> Value {
+    envelope(Stage::Source, module, regions)
+}
+
+/// Export the given canonical module, including the region of each
+/// name if `regions` is set.
+pub fn canonical(module: &can::Module, regions: bool) -> Value {
+    envelope(Stage::Canonical, module, regions)
+}
+
+fn envelope<T: Serialize>(stage: Stage, module: &T, regions: bool) -> Value {
+    let mut module = serde_json::to_value(module).expect("A module should be serializable");
+    if !regions {
+        remove_regions(&mut module);
+    }
+
+    json!({
+        "version": FORMAT_VERSION,
+        "stage": stage.name(),
+        "module": module,
+    })
+}
+
+/// Remove the region of every name. Examples are values of the spec,
+/// which may look like names, so they are left as they are.
+fn remove_regions(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            if is_name(object) {
+                object.remove("region");
+            }
+            for (key, value) in object.iter_mut() {
+                if !(key == "examples" && value.is_array()) {
+                    remove_regions(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(remove_regions),
+        _ => {}
+    }
+}
+
+/// Check, if the object is a serialized [`src::Name`].
+fn is_name(object: &Map<String, Value>) -> bool {
+    let region = object.get("region").and_then(Value::as_array);
+    object.len() == 2
+        && object.get("value").is_some_and(Value::is_string)
+        && region.is_some_and(|region| region.len() == 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export;
+
+    const SOURCE: &str =
+        "data Person {\n    name: String,\n}\n\nservice Geo {\n    def region(): String\n}\n\n#(example {:value 1 :region \"EU\"})\ndata Price {\n    value: Int32,\n    region: String,\n}\n";
+
+    #[test]
+    fn test_canonical_without_regions() {
+        let module = crate::compile(None, SOURCE).unwrap();
+        let value = export::canonical(&module, false);
+        assert_eq!(value["version"], 1);
+        assert_eq!(value["stage"], "canonical");
+        let name = &value["module"]["records"]["Person"]["name"];
+        assert_eq!(name["value"], "Person");
+        assert!(name.get("region").is_none());
+        assert!(value["module"]["services"]["Geo"]["methods"]["region"].is_object());
+        let example = &value["module"]["records"]["Price"]["examples"][0];
+        assert_eq!(example["region"], "EU");
+    }
+
+    #[test]
+    fn test_names_keep_their_region_outside_of_exports() {
+        let module = crate::parse(None, SOURCE).unwrap();
+        let value = serde_json::to_value(&module).unwrap();
+        let name = &value["declarations"][0]["Data"]["name"];
+        assert_eq!(name["region"], serde_json::json!([1, 6, 1, 11]));
+    }
+
+    #[test]
+    fn test_source_with_regions() {
+        let module = crate::parse(None, SOURCE).unwrap();
+        let value = export::source(&module, true);
+        assert_eq!(value["stage"], "source");
+        let name = &value["module"]["declarations"][0]["Data"]["name"];
+        assert_eq!(name["region"], serde_json::json!([1, 6, 1, 11]));
+    }
+}
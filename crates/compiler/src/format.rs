@@ -0,0 +1,582 @@
+//! A pretty-printer for wRPC source files.
+//!
+//! The formatter works on the [source AST](crate::ast::source), which
+//! keeps all doc comments, annotations and dangling comments of a
+//! file. That means formatting a file only changes whitespace,
+//! trailing commas and the layout of annotations, but never its
+//! meaning.
+use itertools::Itertools;
+use pretty::RcDoc;
+
+use crate::ast::source::{
+    Annotation, Data, Decl, Enum, Expr, Method, Module, Name, Parameter, Property, Service, Type,
+    Variant,
+};
+
+type Doc = RcDoc<'static, ()>;
+
+/// The maximum width of a line, before the formatter tries to
+/// break a group into multiple lines.
+const WIDTH: usize = 80;
+
+/// The number of spaces used to indent nested elements.
+const INDENT: isize = 4;
+
+/// Format the given [`Module`] into its canonical textual representation.
+///
+/// ## Example
+///
+/// ```
+/// let module = compiler::parse(None, "data   Person{name:String}").unwrap();
+/// let result = compiler::format::format_module(&module);
+/// assert_eq!(result, "data Person {\n    name: String,\n}\n");
+/// ```
+pub fn format_module(module: &Module) -> String {
+    let mut docs = module.declarations.iter().map(decl).collect::<Vec<Doc>>();
+    if let Some(comment) = &module.trailing_comment {
+        docs.push(comment_lines(comment));
+    }
+
+    let doc = RcDoc::intersperse(docs, RcDoc::hardline().append(RcDoc::hardline()));
+    let mut result = render(doc);
+    if !result.is_empty() {
+        result.push('\n');
+    }
+
+    result
+}
+
+/// Format a single [`Decl`], including its doc comment and annotations.
+pub fn format_decl(value: &Decl) -> String {
+    render(decl(value))
+}
+
+/// Format a single [`Type`], like `List<String>?`.
+pub fn format_type(value: &Type) -> String {
+    render(type_(value))
+}
+
+fn render(doc: Doc) -> String {
+    let rendered = doc.pretty(WIDTH).to_string();
+    // Empty lines inside of a block are indented by the printer.
+    rendered.lines().map(str::trim_end).join("\n")
+}
+
+fn decl(decl: &Decl) -> Doc {
+    match decl {
+        Decl::Data(data) => data_decl(data),
+        Decl::Enum(enum_) => enum_decl(enum_),
+        Decl::Service(service) => service_decl(service),
+    }
+}
+
+fn data_decl(data: &Data) -> Doc {
+    let header = RcDoc::text("data ")
+        .append(name(&data.name))
+        .append(type_variables(&data.type_variables));
+
+    let mut members = data
+        .properties
+        .iter()
+        .map(|value| spaced(value.blank_line_before, property(value).append(",")))
+        .collect::<Vec<Doc>>();
+    if let Some(comment) = &data.trailing_comment {
+        members.push(comment_lines(comment));
+    }
+
+    preamble(&data.doc_comment, &data.annotations)
+        .append(header)
+        .append(RcDoc::space())
+        .append(block(members, RcDoc::hardline()))
+}
+
+fn enum_decl(enum_: &Enum) -> Doc {
+    let header = RcDoc::text("enum ")
+        .append(name(&enum_.name))
+        .append(type_variables(&enum_.type_variables));
+
+    let mut members = enum_
+        .variants
+        .iter()
+        .map(|value| spaced(value.blank_line_before, variant(value)))
+        .collect::<Vec<Doc>>();
+    if let Some(comment) = &enum_.trailing_comment {
+        members.push(comment_lines(comment));
+    }
+
+    preamble(&enum_.doc_comment, &enum_.annotations)
+        .append(header)
+        .append(RcDoc::space())
+        .append(block(members, RcDoc::hardline()))
+}
+
+fn variant(variant: &Variant) -> Doc {
+    let head = preamble(&variant.doc_comment, &variant.annotations).append(name(&variant.name));
+    if variant.properties.is_empty() && variant.trailing_comment.is_none() {
+        return head.append(",");
+    }
+
+    let properties = variant
+        .properties
+        .iter()
+        .map(|value| spaced(value.blank_line_before, property(value)));
+    let separator = RcDoc::text(",").append(RcDoc::line());
+    let mut members = RcDoc::intersperse(properties, separator);
+    if !variant.properties.is_empty() {
+        members = members.append(RcDoc::text(",").flat_alt(RcDoc::nil()));
+    }
+
+    if let Some(comment) = &variant.trailing_comment {
+        members = members
+            .append(RcDoc::hardline())
+            .append(comment_lines(comment));
+    }
+
+    // Variants with few properties are kept on a single line, like
+    // `Hello { name: String },`, as long as they fit.
+    let fields = RcDoc::text("{")
+        .append(RcDoc::line().append(members).nest(INDENT))
+        .append(RcDoc::line())
+        .append("}")
+        .group();
+
+    head.append(" ").append(fields).append(",")
+}
+
+fn property(property: &Property) -> Doc {
+    preamble(&property.doc_comment, &property.annotations)
+        .append(name(&property.name))
+        .append(": ")
+        .append(type_(&property.type_))
+}
+
+fn service_decl(service: &Service) -> Doc {
+    let header = RcDoc::text("service ").append(name(&service.name));
+    let mut members = service.methods.iter().map(method).collect::<Vec<Doc>>();
+    if let Some(comment) = &service.trailing_comment {
+        members.push(comment_lines(comment));
+    }
+
+    preamble(&service.doc_comment, &service.annotations)
+        .append(header)
+        .append(RcDoc::space())
+        .append(block(members, RcDoc::hardline().append(RcDoc::hardline())))
+}
+
+fn method(method: &Method) -> Doc {
+    // The last parameter only gets a trailing comma, if the
+    // parameters are split across multiple lines.
+    let separator = RcDoc::text(",").append(RcDoc::line());
+    let parameters = method
+        .parameters
+        .iter()
+        .map(|value| spaced(value.blank_line_before, parameter(value)));
+    let mut parameters = RcDoc::intersperse(parameters, separator);
+    if !method.parameters.is_empty() {
+        parameters = parameters.append(RcDoc::text(",").flat_alt(RcDoc::nil()));
+    }
+
+    if let Some(comment) = &method.trailing_comment {
+        parameters = parameters
+            .append(RcDoc::hardline())
+            .append(comment_lines(comment));
+    }
+
+    let parameters = RcDoc::text("(")
+        .append(RcDoc::line_().append(parameters).nest(INDENT))
+        .append(RcDoc::line_())
+        .append(")");
+
+    let return_type = match &method.return_type {
+        Some(return_type) => RcDoc::text(": ").append(type_(return_type)),
+        None => RcDoc::nil(),
+    };
+
+    preamble(&method.doc_comment, &method.annotations)
+        .append("def ")
+        .append(name(&method.name))
+        .append(parameters.group())
+        .append(return_type)
+}
+
+fn parameter(parameter: &Parameter) -> Doc {
+    let comment = match &parameter.doc_comment {
+        Some(comment) => comment_lines(comment).append(RcDoc::hardline()),
+        None => RcDoc::nil(),
+    };
+
+    let annotations = parameter
+        .annotations
+        .iter()
+        .map(|value| annotation(value).append(RcDoc::line()))
+        .collect::<Vec<Doc>>();
+
+    comment
+        .append(RcDoc::concat(annotations))
+        .append(name(&parameter.name))
+        .append(": ")
+        .append(type_(&parameter.type_))
+}
+
+/// Returns a braced block, where each member is placed on its own
+/// line, separated by the given `separator`.
+fn block(members: Vec<Doc>, separator: Doc) -> Doc {
+    if members.is_empty() {
+        return RcDoc::text("{}");
+    }
+
+    RcDoc::text("{")
+        .append(
+            RcDoc::hardline()
+                .append(RcDoc::intersperse(members, separator))
+                .nest(INDENT),
+        )
+        .append(RcDoc::hardline())
+        .append("}")
+}
+
+/// Returns the given member, preceded by an empty line, if it was
+/// separated from the previous member in the source.
+fn spaced(blank_line_before: bool, member: Doc) -> Doc {
+    if blank_line_before {
+        RcDoc::hardline().append(member)
+    } else {
+        member
+    }
+}
+
+/// Returns the doc comment and the annotations, each on its own
+/// line, that precede a declaration or one of its members.
+fn preamble(comment: &Option<String>, annotations: &[Annotation]) -> Doc {
+    let comment = match comment {
+        Some(comment) => comment_lines(comment).append(RcDoc::hardline()),
+        None => RcDoc::nil(),
+    };
+
+    let annotations = annotations
+        .iter()
+        .map(|value| annotation(value).append(RcDoc::hardline()))
+        .collect::<Vec<Doc>>();
+
+    comment.append(RcDoc::concat(annotations))
+}
+
+fn comment_lines(comment: &str) -> Doc {
+    let lines = comment.split('\n').map(|line| {
+        if line.is_empty() {
+            RcDoc::text("//")
+        } else {
+            RcDoc::text(format!("// {line}"))
+        }
+    });
+
+    RcDoc::intersperse(lines, RcDoc::hardline())
+}
+
+fn annotation(annotation: &Annotation) -> Doc {
+    RcDoc::text("#").append(expr(&annotation.expr))
+}
+
+fn expr(value: &Expr) -> Doc {
+    match value {
+        Expr::Boolean(_, value) => RcDoc::as_string(value),
+        Expr::Number(_, value) => RcDoc::as_string(value),
+        Expr::String(_, value) => RcDoc::text(string_literal(value)),
+        Expr::Keyword(_, value) => RcDoc::text(format!(":{value}")),
+        Expr::Symbol(_, value) => RcDoc::text(value.clone()),
+        Expr::List(_, expressions) => match expressions.split_first() {
+            None => RcDoc::text("()"),
+            Some((head, args)) => {
+                let args = args
+                    .iter()
+                    .map(|arg| RcDoc::line().append(expr(arg)))
+                    .collect::<Vec<Doc>>();
+
+                RcDoc::text("(")
+                    .append(expr(head))
+                    .append(RcDoc::concat(args).nest(2))
+                    .append(")")
+                    .group()
+            }
+        },
+        Expr::Map(_, entries) => {
+            let entries = entries
+                .iter()
+                .map(|(key, value)| expr(key).append(" ").append(expr(value)))
+                .collect::<Vec<Doc>>();
+
+            RcDoc::text("{")
+                .append(RcDoc::intersperse(entries, RcDoc::line()).nest(1))
+                .append("}")
+                .group()
+        }
+    }
+}
+
+fn string_literal(value: &str) -> String {
+    let mut result = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn type_(type_: &Type) -> Doc {
+    match (type_.name.value.as_str(), type_.variables.as_slice()) {
+        // `Option<A>` is always written as `A?`, except for nested
+        // options, which cannot be expressed with a `?`.
+        ("Option", [inner]) if !is_option(inner) => type_ref(inner).append("?"),
+        _ => type_ref(type_),
+    }
+}
+
+fn type_ref(value: &Type) -> Doc {
+    if value.variables.is_empty() {
+        return name(&value.name);
+    }
+
+    let variables = value.variables.iter().map(type_);
+    name(&value.name)
+        .append("<")
+        .append(RcDoc::intersperse(variables, RcDoc::text(", ")))
+        .append(">")
+}
+
+fn is_option(type_: &Type) -> bool {
+    type_.name.value == "Option" && type_.variables.len() == 1
+}
+
+fn type_variables(variables: &[Name]) -> Doc {
+    if variables.is_empty() {
+        return RcDoc::nil();
+    }
+
+    RcDoc::text("<")
+        .append(RcDoc::intersperse(
+            variables.iter().map(name),
+            RcDoc::text(", "),
+        ))
+        .append(">")
+}
+
+fn name(name: &Name) -> Doc {
+    RcDoc::text(name.value.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format;
+
+    fn fmt(source: &str) -> String {
+        format(None, source).expect("Source should parse")
+    }
+
+    #[test]
+    fn test_normalizes_spacing_and_trailing_commas() {
+        let source = r#"
+            data   Address{
+                street :String,
+
+                land: String
+            }
+        "#;
+
+        let expected = "data Address {\n    street: String,\n\n    land: String,\n}\n";
+        assert_eq!(fmt(source), expected);
+    }
+
+    #[test]
+    fn test_preserves_comments_and_annotations() {
+        let source = r#"
+// A person.
+//
+// With a second paragraph.
+#(example {:name "Test"})
+data Person {
+    // The name.
+    #(check (not (blank .name))) #(check (<= (len .name) 5))
+    name: String?,
+    // Dangling comment.
+}
+
+// End of file.
+"#;
+
+        let expected = r#"// A person.
+//
+// With a second paragraph.
+#(example {:name "Test"})
+data Person {
+    // The name.
+    #(check (not (blank .name)))
+    #(check (<= (len .name) 5))
+    name: String?,
+    // Dangling comment.
+}
+
+// End of file.
+"#;
+        assert_eq!(fmt(source), expected);
+    }
+
+    #[test]
+    fn test_keeps_single_blank_lines_between_members() {
+        let source = r#"
+            enum Shape {
+                Circle,
+
+
+                Square { width: Float32,
+
+                    height: Float32 },
+            }
+
+            service Canvas {
+                def draw(shape: Shape,
+
+                    color: String)
+            }
+        "#;
+
+        let expected = r#"enum Shape {
+    Circle,
+
+    Square {
+        width: Float32,
+
+        height: Float32,
+    },
+}
+
+service Canvas {
+    def draw(
+        shape: Shape,
+
+        color: String,
+    )
+}
+"#;
+        assert_eq!(fmt(source), expected);
+    }
+
+    #[test]
+    fn test_preserves_trailing_comments_of_enums_and_services() {
+        let source = r#"
+            enum Color {
+                Red,
+                // Blue is still missing.
+            }
+
+            service ColorService {
+                def all(): List<Color>
+                // Add more methods.
+            }
+        "#;
+
+        let expected = r#"enum Color {
+    Red,
+    // Blue is still missing.
+}
+
+service ColorService {
+    def all(): List<Color>
+
+    // Add more methods.
+}
+"#;
+        assert_eq!(fmt(source), expected);
+    }
+
+    #[test]
+    fn test_services_are_separated_by_blank_lines() {
+        let source = r#"
+            service ClubService {
+
+                // Returns a club.
+                def get(id: Int64): Club?
+                def insert(data: ClubData): Result<Error, Club>
+                def delete(id: Int64)
+
+            }
+        "#;
+
+        let expected = r#"service ClubService {
+    // Returns a club.
+    def get(id: Int64): Club?
+
+    def insert(data: ClubData): Result<Error, Club>
+
+    def delete(id: Int64)
+}
+"#;
+        assert_eq!(fmt(source), expected);
+    }
+
+    #[test]
+    fn test_breaks_long_lines() {
+        let source = r#"
+            enum Error {
+                Hello { name: String, foo: Int32, },
+                Long { firstName: String, lastName: String, street: String, houseNo: Int32 },
+            }
+
+            service Foo {
+                def update(association: AssociationData, club: ClubData, season: Season): Club
+            }
+        "#;
+
+        let expected = r#"enum Error {
+    Hello { name: String, foo: Int32 },
+    Long {
+        firstName: String,
+        lastName: String,
+        street: String,
+        houseNo: Int32,
+    },
+}
+
+service Foo {
+    def update(
+        association: AssociationData,
+        club: ClubData,
+        season: Season,
+    ): Club
+}
+"#;
+        assert_eq!(fmt(source), expected);
+    }
+
+    #[test]
+    fn test_breaks_long_annotations() {
+        let source = r#"
+            data Address {
+                #(check (or (and (= .country "DE") (= (len .zipcode) 5)) (and (= .country "CH") (= (len .zipcode) 4))))
+                zipcode: String,
+            }
+        "#;
+
+        let expected = r#"data Address {
+    #(check
+      (or
+        (and (= .country "DE") (= (len .zipcode) 5))
+        (and (= .country "CH") (= (len .zipcode) 4))))
+    zipcode: String,
+}
+"#;
+        assert_eq!(fmt(source), expected);
+    }
+
+    #[test]
+    fn test_formatting_is_idempotent() {
+        let source = include_str!("../../../test.wrpc");
+        let once = fmt(source);
+        assert_eq!(fmt(&once), once);
+    }
+}
@@ -0,0 +1,6 @@
+//! Translate the specs of other formats into wRPC.
+//!
+//! An import produces a [source module](crate::ast::source::Module),
+//! so it can be printed with [`crate::format::format_module`], like a
+//! spec written by hand.
+pub mod openapi;
@@ -0,0 +1,1963 @@
+//! Import an OpenAPI 3.0 or 3.1 document.
+//!
+//! Component schemas become records and enums, where a `oneOf` with
+//! a discriminator becomes an enum with a variant per schema. Every
+//! operation becomes a method of the service named after its first
+//! tag, and validation keywords, like `maxLength`, become
+//! `#(check ...)` annotations.
+//!
+//! wRPC has no type aliases, so schemas, which are neither objects
+//! nor enums, like a `string` with a `format`, are inlined wherever
+//! they are referenced. Everything without an equivalent in wRPC,
+//! like a `pattern`, is left out and reported as [`Unsupported`].
+//! wRPC calls every method with a `POST` of its parameters to
+//! `/Service/method`, so operations, which are called differently,
+//! are reported as well.
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use itertools::Itertools;
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Map, Value};
+
+use crate::ast::source::{
+    Annotation, Data, Decl, Enum, Expr, Method, Module, Name, Parameter, Property, Service, Type,
+    Variant,
+};
+use crate::reporting::Region;
+
+/// The result of importing a document.
+#[derive(Debug)]
+pub struct Import {
+    pub module: Module,
+    /// Everything, that could not be translated, in the order it was found.
+    pub unsupported: Vec<Unsupported>,
+}
+
+/// A part of the document without an equivalent in wRPC, or which is
+/// translated with a different wire format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsupported {
+    /// A JSON pointer to the part, like
+    /// `#/components/schemas/Pet/properties/name/pattern`.
+    pub pointer: String,
+    pub message: String,
+}
+
+/// An OpenAPI document, parsed from JSON or YAML.
+///
+/// Unlike a [`Value`], it keeps the order of the keys of all objects,
+/// so schemas, properties and operations are imported in the order
+/// of the document.
+#[derive(Debug)]
+pub struct Document {
+    value: Value,
+    /// The keys of every object in the order of the document, by the
+    /// JSON pointer of the object, like `#/components/schemas`.
+    order: HashMap<String, Vec<String>>,
+}
+
+impl<'de> Deserialize<'de> for Document {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let node = Node::deserialize(deserializer)?;
+        let mut order = HashMap::new();
+        let value = node.into_value("#".to_string(), &mut order);
+        Ok(Document { value, order })
+    }
+}
+
+/// A value, whose objects keep the order of their keys.
+enum Node {
+    Value(Value),
+    Array(Vec<Node>),
+    Object(Vec<(String, Node)>),
+}
+
+impl Node {
+    /// Convert the node into a [`Value`] and collect the order of the
+    /// keys of all objects.
+    fn into_value(self, pointer: String, order: &mut HashMap<String, Vec<String>>) -> Value {
+        match self {
+            Node::Value(value) => value,
+            Node::Array(nodes) => nodes
+                .into_iter()
+                .enumerate()
+                .map(|(index, node)| node.into_value(format!("{pointer}/{index}"), order))
+                .collect(),
+            Node::Object(entries) => {
+                let keys = entries.iter().map(|(key, _)| key.clone()).collect();
+                let object = entries
+                    .into_iter()
+                    .map(|(key, node)| {
+                        let value = node.into_value(pointer_to(&pointer, &key), order);
+                        (key, value)
+                    })
+                    .collect::<Map<_, _>>();
+                order.insert(pointer, keys);
+                Value::Object(object)
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NodeVisitor)
+    }
+}
+
+struct NodeVisitor;
+
+impl<'de> Visitor<'de> for NodeVisitor {
+    type Value = Node;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Node, E> {
+        Ok(Node::Value(Value::Bool(value)))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Node, E> {
+        Ok(Node::Value(Value::from(value)))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Node, E> {
+        Ok(Node::Value(Value::from(value)))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Node, E> {
+        Ok(Node::Value(Value::from(value)))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Node, E> {
+        Ok(Node::Value(Value::from(value)))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Node, E> {
+        Ok(Node::Value(Value::String(value)))
+    }
+
+    fn visit_none<E>(self) -> Result<Node, E> {
+        Ok(Node::Value(Value::Null))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Node, D::Error> {
+        Node::deserialize(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> Result<Node, E> {
+        Ok(Node::Value(Value::Null))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Node, A::Error> {
+        let mut nodes = vec![];
+        while let Some(node) = seq.next_element()? {
+            nodes.push(node);
+        }
+        Ok(Node::Array(nodes))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Node, A::Error> {
+        let mut entries: Vec<(String, Node)> = vec![];
+        while let Some((key, node)) = map.next_entry::<String, Node>()? {
+            // Like in a `Value`, the last of duplicate keys wins.
+            entries.retain(|(other, _)| *other != key);
+            entries.push((key, node));
+        }
+        Ok(Node::Object(entries))
+    }
+}
+
+/// The builtin types, which cannot be the name of a declaration.
+const BUILTINS: [&str; 11] = [
+    "String", "Int32", "Int64", "Float32", "Float64", "Boolean", "List", "Set", "Map", "Option",
+    "Result",
+];
+
+/// The keywords of a schema, which are translated.
+const KEYWORDS: [&str; 24] = [
+    "$ref",
+    "type",
+    "nullable",
+    "enum",
+    "const",
+    "items",
+    "uniqueItems",
+    "properties",
+    "required",
+    "additionalProperties",
+    "allOf",
+    "oneOf",
+    "anyOf",
+    "discriminator",
+    "minLength",
+    "maxLength",
+    "minItems",
+    "maxItems",
+    "minProperties",
+    "maxProperties",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+];
+
+/// The keywords of a schema, which only document it and are left out
+/// without a report.
+const DOCUMENTATION: [&str; 12] = [
+    "title",
+    "description",
+    "format",
+    "example",
+    "examples",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+    "xml",
+    "externalDocs",
+    "$comment",
+    "$schema",
+];
+
+/// The operations of a path item in the order, they are imported.
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Import the given OpenAPI document.
+///
+/// Returns an error, if the document is no OpenAPI 3.0 or 3.1
+/// document. Everything else is translated as far as possible.
+pub fn import(document: &Document) -> Result<Import, String> {
+    let order = &document.order;
+    let document = &document.value;
+    let version = document.get("openapi").and_then(Value::as_str);
+    if !version.is_some_and(|version| version.starts_with("3.")) {
+        let found = match (version, document.get("swagger").and_then(Value::as_str)) {
+            (Some(version), _) => format!("OpenAPI {version}"),
+            (None, Some(version)) => format!("Swagger {version}"),
+            (None, None) => return Err("The document has no `openapi` version.".to_string()),
+        };
+        return Err(format!(
+            "Only OpenAPI 3.0 and 3.1 documents can be imported, not {found}."
+        ));
+    }
+
+    let mut importer = Importer {
+        document,
+        order,
+        declared: HashMap::new(),
+        inlining: vec![],
+        names: BUILTINS.iter().map(|name| name.to_string()).collect(),
+        declarations: vec![],
+        unsupported: vec![],
+    };
+    importer.components();
+    importer.paths();
+
+    if document.get("security").is_some() {
+        importer.report("#/security", "Authentication is not part of a wRPC spec.");
+    }
+    if document.pointer("/components/securitySchemes").is_some() {
+        let message = "Authentication is not part of a wRPC spec.";
+        importer.report("#/components/securitySchemes", message);
+    }
+    if document.get("webhooks").is_some() {
+        importer.report("#/webhooks", "wRPC has no webhooks.");
+    }
+
+    Ok(Import {
+        module: Module {
+            doc_comment: None,
+            version: "1".into(),
+            declarations: importer.declarations,
+            trailing_comment: None,
+        },
+        unsupported: importer.unsupported,
+    })
+}
+
+/// A validation keyword, which is checked by an annotation.
+#[derive(Debug, Clone)]
+enum Check {
+    /// The bounds of `len`, like `minLength` and `maxItems`.
+    Length(Option<f64>, Option<f64>),
+    /// The bounds of a number, which are exclusive, if the flag is set.
+    Range(Option<(f64, bool)>, Option<(f64, bool)>),
+    /// The allowed values of `enum` and `const`.
+    OneOf(Vec<Expr>),
+}
+
+/// The translation of a schema.
+struct Translated {
+    type_: Type,
+    /// Whether the schema allows `null`.
+    nullable: bool,
+    checks: Vec<Check>,
+}
+
+impl Translated {
+    fn new(type_: Type) -> Self {
+        Translated {
+            type_,
+            nullable: false,
+            checks: vec![],
+        }
+    }
+
+    /// Returns the type, which is optional, if the value may be
+    /// missing or `null`.
+    fn type_(&self, required: bool) -> Type {
+        match required && !self.nullable {
+            true => self.type_.clone(),
+            false => optional(self.type_.clone()),
+        }
+    }
+}
+
+/// A property of an object schema, which may be declared by one of
+/// the schemas it combines with `allOf`.
+struct Field<'a> {
+    name: &'a str,
+    schema: &'a Value,
+    pointer: String,
+}
+
+struct Importer<'a> {
+    document: &'a Value,
+    order: &'a HashMap<String, Vec<String>>,
+    /// The declaration of every component, which is not inlined.
+    declared: HashMap<String, String>,
+    /// The components, which are currently inlined.
+    inlining: Vec<String>,
+    /// The names of all declarations and builtin types.
+    names: HashSet<String>,
+    declarations: Vec<Decl>,
+    unsupported: Vec<Unsupported>,
+}
+
+impl<'a> Importer<'a> {
+    /// Returns the entries of the object at the given pointer in the
+    /// order of the document.
+    fn entries(
+        &self,
+        object: &'a Map<String, Value>,
+        pointer: &str,
+    ) -> Vec<(&'a String, &'a Value)> {
+        match self.order.get(pointer) {
+            Some(keys) => keys
+                .iter()
+                .filter_map(|key| object.get_key_value(key))
+                .collect(),
+            None => object.iter().collect(),
+        }
+    }
+
+    fn report(&mut self, pointer: &str, message: impl Into<String>) {
+        let unsupported = Unsupported {
+            pointer: pointer.to_string(),
+            message: message.into(),
+        };
+
+        // Inlined schemas are translated once for every reference.
+        if !self.unsupported.contains(&unsupported) {
+            self.unsupported.push(unsupported);
+        }
+    }
+
+    /// Returns an unused name for a declaration, like `PetOwner` for
+    /// `pet-owner`.
+    fn declare(&mut self, name: &str) -> String {
+        unique(&mut self.names, type_name(name))
+    }
+
+    /// Declare a record or enum for every component schema, which is
+    /// an object or enum.
+    fn components(&mut self) {
+        let document = self.document;
+        let Some(schemas) = document
+            .pointer("/components/schemas")
+            .and_then(Value::as_object)
+        else {
+            return;
+        };
+
+        // The names are needed before translating any schema, since
+        // schemas refer to each other.
+        let variants = variants_only(document);
+        let mut components = vec![];
+        for (component, schema) in self.entries(schemas, "#/components/schemas") {
+            let declared = match schema.as_object() {
+                Some(object) if is_enum(object) => true,
+                Some(object) => is_record(object) && !variants.contains(component),
+                None => false,
+            };
+
+            if declared {
+                let name = self.declare(component);
+                self.declared.insert(component.clone(), name.clone());
+                components.push((name, component, schema));
+            }
+        }
+
+        for (name, component, schema) in components {
+            let pointer = pointer_to("#/components/schemas", component);
+            let index = self.declarations.len();
+            let declaration = match schema.as_object().is_some_and(is_enum) {
+                true => Decl::Enum(self.enum_(name, schema, &pointer)),
+                false => Decl::Data(self.record(name, schema, &pointer)),
+            };
+
+            // Inlined declarations follow the declaration using them.
+            self.declarations.insert(index, declaration);
+        }
+    }
+
+    fn record(&mut self, name: String, schema: &'a Value, pointer: &str) -> Data {
+        let mut names = HashSet::new();
+        let properties = self.properties(&[(schema, pointer.to_string())], &name, None, &mut names);
+        Data {
+            annotations: deprecated(schema),
+            doc_comment: description(schema),
+            name: Name::from_str(&name),
+            properties,
+            type_variables: vec![],
+            trailing_comment: None,
+        }
+    }
+
+    fn enum_(&mut self, name: String, schema: &'a Value, pointer: &str) -> Enum {
+        let variants = match schema.as_object().and_then(branches) {
+            Some((key, branches)) => self.variants(&name, schema, key, branches, pointer),
+            None => {
+                let mut names = HashSet::new();
+                let values = schema.get("enum").and_then(Value::as_array);
+                values
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .filter(|value| names.insert(value.to_string()))
+                    .map(|value| Variant {
+                        annotations: vec![],
+                        doc_comment: None,
+                        name: Name::from_str(value),
+                        properties: vec![],
+                        trailing_comment: None,
+                        blank_line_before: false,
+                    })
+                    .collect()
+            }
+        };
+
+        Enum {
+            annotations: deprecated(schema),
+            doc_comment: description(schema),
+            name: Name::from_str(&name),
+            variants,
+            type_variables: vec![],
+            trailing_comment: None,
+        }
+    }
+
+    /// Returns a variant for every object in the `oneOf` or `anyOf`,
+    /// named after its tag.
+    fn variants(
+        &mut self,
+        owner: &str,
+        schema: &'a Value,
+        key: &str,
+        branches: &'a [Value],
+        pointer: &str,
+    ) -> Vec<Variant> {
+        let discriminator = schema.get("discriminator");
+        let tag = discriminator
+            .and_then(|discriminator| discriminator.get("propertyName"))
+            .and_then(Value::as_str);
+        let mapping = discriminator
+            .and_then(|discriminator| discriminator.get("mapping"))
+            .and_then(Value::as_object);
+
+        // Without a discriminator, the variants may still be tagged
+        // with a constant `@type`, like in documents generated by wRPC.
+        let tagged = branches
+            .iter()
+            .filter(|branch| !is_null(branch))
+            .all(|branch| const_tag(branch, "@type").is_some());
+        match tag {
+            Some("@type") => {}
+            Some(tag) => {
+                let message = format!("wRPC tags variants with `@type` instead of `{tag}`.");
+                self.report(&format!("{pointer}/discriminator/propertyName"), message);
+            }
+            None if tagged => {}
+            None => {
+                let message = format!(
+                    "`{key}` without a discriminator becomes an enum, whose variants are tagged with `@type`."
+                );
+                self.report(&format!("{pointer}/{key}"), message);
+            }
+        }
+
+        let tag = tag.unwrap_or("@type");
+
+        let mut names = HashSet::new();
+        let mut variants = vec![];
+        for (index, branch) in branches.iter().enumerate() {
+            if is_null(branch) {
+                continue;
+            }
+
+            let branch_pointer = format!("{pointer}/{key}/{index}");
+            let Some((target, _)) = self.follow(branch, "schemas", branch_pointer.clone()) else {
+                continue;
+            };
+            if !target.as_object().is_some_and(is_object) {
+                self.report(&branch_pointer, "Only objects can be variants of an enum.");
+                continue;
+            }
+
+            let value = match branch.get("$ref").and_then(Value::as_str) {
+                // Without a mapping, the tag is the name of the schema.
+                Some(reference) => mapping
+                    .and_then(|mapping| {
+                        mapping
+                            .iter()
+                            .find(|(_, target)| target.as_str() == Some(reference))
+                    })
+                    .map(|(value, _)| value.clone())
+                    .or_else(|| reference.rsplit('/').next().map(unescape)),
+                None => const_tag(branch, tag).map(str::to_string),
+            };
+
+            let Some(value) = value else {
+                let message = "A variant needs to be a `$ref` or to have a `const` tag.";
+                self.report(&branch_pointer, message);
+                continue;
+            };
+
+            let name = match is_identifier(&value) {
+                true => value.clone(),
+                false => type_name(&value),
+            };
+            let name = unique(&mut names, name);
+            if name != value {
+                let message = format!(
+                    "The tag `{value}` becomes the variant `{name}`, which changes the wire format."
+                );
+                self.report(&branch_pointer, message);
+            }
+
+            let schemas = [(schema, pointer.to_string()), (branch, branch_pointer)];
+            let mut properties = HashSet::new();
+            let properties = self.properties(
+                &schemas,
+                &format!("{owner}{name}"),
+                Some(tag),
+                &mut properties,
+            );
+            variants.push(Variant {
+                annotations: deprecated(target),
+                doc_comment: description(target),
+                name: Name::from_str(&name),
+                properties,
+                trailing_comment: None,
+                blank_line_before: false,
+            });
+        }
+
+        variants
+    }
+
+    /// Returns the properties of all given object schemas, except for
+    /// the one to `skip`, like the tag of a variant.
+    fn properties(
+        &mut self,
+        schemas: &[(&'a Value, String)],
+        owner: &str,
+        skip: Option<&str>,
+        names: &mut HashSet<String>,
+    ) -> Vec<Property> {
+        let mut fields = vec![];
+        let mut required = HashSet::new();
+        let mut visited = HashSet::new();
+        for (schema, pointer) in schemas {
+            self.fields(schema, pointer, &mut fields, &mut required, &mut visited);
+        }
+
+        let mut properties = vec![];
+        for field in fields {
+            if Some(field.name) == skip {
+                continue;
+            }
+
+            let name = self.member(field.name, &field.pointer, names);
+            let context = format!("{owner}{}", capitalize(&name));
+            let translated = self.translate(field.schema, &field.pointer, &context);
+            let mut annotations = deprecated(field.schema);
+            annotations.extend(checks(&translated.checks, &name));
+            properties.push(Property {
+                annotations,
+                doc_comment: description(field.schema),
+                name: Name::from_str(&name),
+                type_: translated.type_(required.contains(field.name)),
+                blank_line_before: false,
+            });
+        }
+
+        properties
+    }
+
+    /// Collect the properties of an object schema and of all schemas,
+    /// it references or combines with `allOf`.
+    fn fields(
+        &mut self,
+        schema: &'a Value,
+        pointer: &str,
+        fields: &mut Vec<Field<'a>>,
+        required: &mut HashSet<&'a str>,
+        visited: &mut HashSet<String>,
+    ) {
+        if schema.get("$ref").is_some() {
+            let Some((target, pointer)) = self.follow(schema, "schemas", pointer.to_string())
+            else {
+                return;
+            };
+            if visited.insert(pointer.clone()) {
+                self.fields(target, &pointer, fields, required, visited);
+            }
+            return;
+        }
+
+        let Some(object) = schema.as_object() else {
+            return;
+        };
+
+        self.keywords(object, pointer);
+        let all = object.get("allOf").and_then(Value::as_array);
+        for (index, branch) in all.into_iter().flatten().enumerate() {
+            let pointer = format!("{pointer}/allOf/{index}");
+            self.fields(branch, &pointer, fields, required, visited);
+        }
+
+        let properties = object.get("properties").and_then(Value::as_object);
+        let properties =
+            properties.map(|properties| self.entries(properties, &format!("{pointer}/properties")));
+        for (name, schema) in properties.into_iter().flatten() {
+            fields.retain(|field| field.name != name);
+            fields.push(Field {
+                name,
+                schema,
+                pointer: pointer_to(&format!("{pointer}/properties"), name),
+            });
+        }
+
+        let names = object.get("required").and_then(Value::as_array);
+        required.extend(names.into_iter().flatten().filter_map(Value::as_str));
+
+        match object.get("additionalProperties") {
+            None | Some(Value::Bool(false)) => {}
+            Some(_) => {
+                let message = "A record cannot have additional properties.";
+                self.report(&format!("{pointer}/additionalProperties"), message);
+            }
+        }
+        for key in ["minProperties", "maxProperties"] {
+            if object.contains_key(key) {
+                let message = "The number of properties of a record is fixed.";
+                self.report(&format!("{pointer}/{key}"), message);
+            }
+        }
+        if object.contains_key("discriminator") && branches(object).is_none() {
+            let message = "A discriminator is only translated together with a `oneOf`.";
+            self.report(&format!("{pointer}/discriminator"), message);
+        }
+    }
+
+    /// Returns the name of a property or parameter, which is renamed,
+    /// if it is not a valid name in wRPC.
+    fn member(&mut self, name: &str, pointer: &str, names: &mut HashSet<String>) -> String {
+        let member = match is_identifier(name) {
+            true => name.to_string(),
+            false => member_name(name),
+        };
+
+        let member = unique(names, member);
+        if member != name {
+            let message =
+                format!("`{name}` is renamed to `{member}`, which changes the wire format.");
+            self.report(pointer, message);
+        }
+
+        member
+    }
+
+    /// Translate the schema into a type. Nested objects and enums are
+    /// declared with the given `context` as their name.
+    fn translate(&mut self, schema: &'a Value, pointer: &str, context: &str) -> Translated {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            return self.reference(reference, pointer);
+        }
+
+        let Some(object) = schema.as_object() else {
+            return self.any(pointer);
+        };
+
+        self.keywords(object, pointer);
+        let mut types = match object.get("type") {
+            Some(Value::String(type_)) => vec![type_.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        let values = object.get("enum").and_then(Value::as_array);
+        let nullable = object.get("nullable") == Some(&Value::Bool(true))
+            || types.contains(&"null")
+            || values.is_some_and(|values| values.contains(&Value::Null));
+        types.retain(|type_| *type_ != "null");
+
+        let all = object.get("allOf").and_then(Value::as_array);
+        let mut translated = if let Some((key, branches)) = branches(object) {
+            self.union(schema, key, branches, pointer, context)
+        } else if is_record(object) {
+            let name = self.declare(context);
+            let index = self.declarations.len();
+            let record = self.record(name.clone(), schema, pointer);
+            self.declarations.insert(index, Decl::Data(record));
+            Translated::new(named(&name))
+        } else if let Some([branch]) = all.map(Vec::as_slice) {
+            let mut translated = self.translate(branch, &format!("{pointer}/allOf/0"), context);
+            translated.checks.extend(self.checks(object, pointer));
+            translated
+        } else if is_simple_enum(object) {
+            let name = self.declare(context);
+            let index = self.declarations.len();
+            let enum_ = self.enum_(name.clone(), schema, pointer);
+            self.declarations.insert(index, Decl::Enum(enum_));
+            Translated::new(named(&name))
+        } else if let Some(type_) = self.primitive(object, &types, pointer, context) {
+            let mut translated = Translated::new(type_);
+            translated.checks = self.checks(object, pointer);
+            translated
+        } else {
+            // The checks of an unknown type are meaningless for a `String`.
+            Translated::new(named("String"))
+        };
+
+        translated.nullable |= nullable;
+        translated
+    }
+
+    /// Returns the declared type of the referenced component, or the
+    /// inlined type of the component, if it is not declared.
+    fn reference(&mut self, reference: &'a str, pointer: &str) -> Translated {
+        let component = reference
+            .strip_prefix("#/components/schemas/")
+            .map(unescape);
+        if let Some(name) = component.as_ref().and_then(|name| self.declared.get(name)) {
+            return Translated::new(named(name));
+        }
+
+        let Some((schema, target)) = self.follow_schema(reference, pointer) else {
+            return Translated::new(named("String"));
+        };
+        let component = component.unwrap_or_default();
+        if self.inlining.contains(&component) {
+            let message =
+                format!("`{component}` refers to itself, but only records and enums can.");
+            self.report(&target, message);
+            return Translated::new(named("String"));
+        }
+
+        self.inlining.push(component.clone());
+        let translated = self.translate(schema, &target, &component);
+        self.inlining.pop();
+        translated
+    }
+
+    /// Returns a record or enum for a `oneOf` or `anyOf`, unless it
+    /// only allows a single schema or `null`.
+    fn union(
+        &mut self,
+        schema: &'a Value,
+        key: &str,
+        branches: &'a [Value],
+        pointer: &str,
+        context: &str,
+    ) -> Translated {
+        let nullable = branches.iter().any(is_null);
+        let mut others = branches
+            .iter()
+            .enumerate()
+            .filter(|(_, branch)| !is_null(branch));
+
+        let mut translated = match (others.next(), others.next()) {
+            (Some((index, branch)), None) => {
+                let pointer = format!("{pointer}/{key}/{index}");
+                self.translate(branch, &pointer, context)
+            }
+            _ => {
+                let name = self.declare(context);
+                let index = self.declarations.len();
+                let enum_ = self.enum_(name.clone(), schema, pointer);
+                self.declarations.insert(index, Decl::Enum(enum_));
+                Translated::new(named(&name))
+            }
+        };
+
+        translated.nullable |= nullable;
+        translated
+    }
+
+    /// Returns the type of a schema, which is neither an object nor
+    /// an enum, or `None`, if wRPC has no type for its values.
+    fn primitive(
+        &mut self,
+        object: &'a Map<String, Value>,
+        types: &[&str],
+        pointer: &str,
+        context: &str,
+    ) -> Option<Type> {
+        let format = object.get("format").and_then(Value::as_str);
+        let type_ = match types {
+            ["string"] => named("String"),
+            ["integer"] if format == Some("int32") => named("Int32"),
+            ["integer"] => named("Int64"),
+            ["number"] if format == Some("float") => named("Float32"),
+            ["number"] => named("Float64"),
+            ["boolean"] => named("Boolean"),
+            ["array"] => self.array(object, pointer, context),
+            ["object"] => self.map(object, pointer, context)?,
+            [] if object.contains_key("items") => self.array(object, pointer, context),
+            [] if object.contains_key("additionalProperties") => {
+                self.map(object, pointer, context)?
+            }
+            // Without a type, the values of an `enum` or `const` tell it.
+            [] => {
+                let values = object.get("enum").and_then(Value::as_array);
+                let value = values
+                    .into_iter()
+                    .flatten()
+                    .chain(object.get("const"))
+                    .find(|value| !value.is_null());
+                match value {
+                    Some(Value::String(_)) => named("String"),
+                    Some(Value::Bool(_)) => named("Boolean"),
+                    Some(Value::Number(number)) if number.is_f64() => named("Float64"),
+                    Some(Value::Number(_)) => named("Int64"),
+                    _ => {
+                        self.any(pointer);
+                        return None;
+                    }
+                }
+            }
+            types => {
+                let types = types.iter().map(|type_| format!("`{type_}`")).join(", ");
+                let message = format!(
+                    "wRPC has no type for values of the types {types}, so it is a `String`."
+                );
+                self.report(&format!("{pointer}/type"), message);
+                return None;
+            }
+        };
+
+        Some(type_)
+    }
+
+    fn array(&mut self, object: &'a Map<String, Value>, pointer: &str, context: &str) -> Type {
+        let Some(items) = object.get("items") else {
+            return generic("List", vec![self.any(pointer).type_]);
+        };
+
+        let pointer = format!("{pointer}/items");
+        if let Some((key, value)) = pair(items) {
+            let key_pointer = format!("{pointer}/prefixItems/0");
+            let key = self.translate(key, &key_pointer, &format!("{context}Key"));
+            let value_pointer = format!("{pointer}/prefixItems/1");
+            let value = self.translate(value, &value_pointer, &format!("{context}Value"));
+            if !key.checks.is_empty() || !value.checks.is_empty() {
+                let message = "The constraints of keys and values cannot be checked.";
+                self.report(&pointer, message);
+            }
+            return generic("Map", vec![key.type_(true), value.type_(true)]);
+        }
+
+        let item = self.translate(items, &pointer, &format!("{context}Item"));
+        if !item.checks.is_empty() {
+            self.report(&pointer, "The constraints of items cannot be checked.");
+        }
+
+        match object.get("uniqueItems") == Some(&Value::Bool(true)) {
+            true => generic("Set", vec![item.type_(true)]),
+            false => generic("List", vec![item.type_(true)]),
+        }
+    }
+
+    /// Returns a map for an object, whose properties are not known.
+    fn map(
+        &mut self,
+        object: &'a Map<String, Value>,
+        pointer: &str,
+        context: &str,
+    ) -> Option<Type> {
+        let Some(values) = object
+            .get("additionalProperties")
+            .filter(|values| values.is_object())
+        else {
+            self.any(pointer);
+            return None;
+        };
+
+        let pointer = format!("{pointer}/additionalProperties");
+        let value = self.translate(values, &pointer, &format!("{context}Value"));
+        if !value.checks.is_empty() {
+            self.report(&pointer, "The constraints of values cannot be checked.");
+        }
+
+        Some(generic("Map", vec![named("String"), value.type_(true)]))
+    }
+
+    fn any(&mut self, pointer: &str) -> Translated {
+        let message = "wRPC has no type for any value, so it is a `String`.";
+        self.report(pointer, message);
+        Translated::new(named("String"))
+    }
+
+    /// Returns the checks of the validation keywords of a schema.
+    fn checks(&mut self, object: &Map<String, Value>, pointer: &str) -> Vec<Check> {
+        let mut checks = vec![];
+        let mut bound = |key: &str, exclusive: bool| match object.get(key).and_then(Value::as_f64) {
+            Some(value) if value < 0.0 => {
+                let message = "wRPC has no negative numbers, so this bound is not checked.";
+                self.report(&format!("{pointer}/{key}"), message);
+                None
+            }
+            value => value.map(|value| (value, exclusive)),
+        };
+
+        let min = bound("minLength", false)
+            .or_else(|| bound("minItems", false))
+            .or_else(|| bound("minProperties", false));
+        let max = bound("maxLength", false)
+            .or_else(|| bound("maxItems", false))
+            .or_else(|| bound("maxProperties", false));
+        if min.is_some() || max.is_some() {
+            checks.push(Check::Length(min.map(|min| min.0), max.map(|max| max.0)));
+        }
+
+        // OpenAPI 3.0 marks the `minimum` as exclusive, while 3.1
+        // has an exclusive bound of its own.
+        let lower = match object.get("exclusiveMinimum") {
+            Some(Value::Bool(exclusive)) => bound("minimum", *exclusive),
+            Some(_) => bound("exclusiveMinimum", true),
+            None => bound("minimum", false),
+        };
+        let upper = match object.get("exclusiveMaximum") {
+            Some(Value::Bool(exclusive)) => bound("maximum", *exclusive),
+            Some(_) => bound("exclusiveMaximum", true),
+            None => bound("maximum", false),
+        };
+        if lower.is_some() || upper.is_some() {
+            checks.push(Check::Range(lower, upper));
+        }
+
+        let values = object.get("enum").and_then(Value::as_array);
+        let values = values.into_iter().flatten().chain(object.get("const"));
+        let mut allowed = vec![];
+        for value in values.filter(|value| !value.is_null()) {
+            match literal(value) {
+                Some(expr) => allowed.push(expr),
+                None => {
+                    let key = if object.contains_key("enum") {
+                        "enum"
+                    } else {
+                        "const"
+                    };
+                    let message = "Only positive numbers, strings and booleans can be checked.";
+                    self.report(&format!("{pointer}/{key}"), message);
+                    allowed.clear();
+                    break;
+                }
+            }
+        }
+        if !allowed.is_empty() {
+            checks.push(Check::OneOf(allowed));
+        }
+
+        checks
+    }
+
+    /// Report all keywords of a schema, which are not translated.
+    fn keywords(&mut self, object: &Map<String, Value>, pointer: &str) {
+        for key in object.keys() {
+            let key = key.as_str();
+            if KEYWORDS.contains(&key) || DOCUMENTATION.contains(&key) || key.starts_with("x-") {
+                continue;
+            }
+
+            let message = match key {
+                "pattern" => {
+                    "wRPC has no regular expressions, so the `pattern` is not checked.".to_string()
+                }
+                "default" => "wRPC has no default values.".to_string(),
+                key => format!("`{key}` has no equivalent in wRPC."),
+            };
+            self.report(&pointer_to(pointer, key), message);
+        }
+    }
+
+    /// Follow the `$ref` of the value to a component of the given
+    /// kind, like `parameters`. Returns the value itself, if it is no
+    /// reference.
+    fn follow(
+        &mut self,
+        value: &'a Value,
+        kind: &str,
+        pointer: String,
+    ) -> Option<(&'a Value, String)> {
+        let Some(reference) = value.get("$ref").and_then(Value::as_str) else {
+            return Some((value, pointer));
+        };
+
+        if !reference.starts_with(&format!("#/components/{kind}/")) {
+            let message = format!(
+                "Only references to `#/components/{kind}` are translated, not `{reference}`."
+            );
+            self.report(&pointer, message);
+            return None;
+        }
+
+        self.follow_schema(reference, &pointer)
+    }
+
+    /// Returns the value at the reference within the document.
+    fn follow_schema(&mut self, reference: &str, pointer: &str) -> Option<(&'a Value, String)> {
+        let document = self.document;
+        let target = reference
+            .strip_prefix("#/components/")
+            .and_then(|_| document.pointer(&reference[1..]));
+        match target {
+            Some(target) => Some((target, reference.to_string())),
+            None => {
+                let message = format!("The reference `{reference}` cannot be resolved.");
+                self.report(pointer, message);
+                None
+            }
+        }
+    }
+
+    /// Declare a service for every tag, with a method for every
+    /// operation of the tag.
+    fn paths(&mut self) {
+        let document = self.document;
+        let Some(paths) = document.get("paths").and_then(Value::as_object) else {
+            return;
+        };
+
+        let title = document.pointer("/info/title").and_then(Value::as_str);
+        let default_tag = title.unwrap_or("Api");
+        let tags = document.get("tags").and_then(Value::as_array);
+        let mut services: Vec<(Service, HashSet<String>)> = vec![];
+        let mut indices: HashMap<&str, usize> = HashMap::new();
+        for (path, item) in self.entries(paths, "#/paths") {
+            let item_pointer = pointer_to("#/paths", path);
+            let shared = item.get("parameters").and_then(Value::as_array);
+            let shared = shared
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .map(|(index, parameter)| (parameter, format!("{item_pointer}/parameters/{index}")))
+                .collect::<Vec<_>>();
+
+            for verb in METHODS {
+                let Some(operation) = item.get(verb) else {
+                    continue;
+                };
+
+                let tag = operation
+                    .get("tags")
+                    .and_then(|tags| tags.get(0))
+                    .and_then(Value::as_str)
+                    .unwrap_or(default_tag);
+                let index = match indices.get(tag) {
+                    Some(index) => *index,
+                    None => {
+                        let name = type_name(tag);
+                        let name = match name.ends_with("Service") {
+                            true => self.declare(&name),
+                            false => self.declare(&format!("{name}Service")),
+                        };
+                        let doc_comment = tags
+                            .into_iter()
+                            .flatten()
+                            .find(|value| value.get("name").and_then(Value::as_str) == Some(tag))
+                            .and_then(description);
+                        let service = Service {
+                            annotations: vec![],
+                            doc_comment,
+                            name: Name::from_str(&name),
+                            methods: vec![],
+                            trailing_comment: None,
+                        };
+                        services.push((service, HashSet::new()));
+                        indices.insert(tag, services.len() - 1);
+                        services.len() - 1
+                    }
+                };
+
+                let pointer = format!("{item_pointer}/{verb}");
+                let (service, names) = &mut services[index];
+                let name = method_name(operation, &format!("{verb} {path}"), tag, service);
+                let name = unique(names, name);
+                let method = self.operation(name, operation, &shared, &pointer);
+                if verb != "post" {
+                    let verb = verb.to_uppercase();
+                    let message = format!("wRPC calls every method with a `POST`, not a `{verb}`.");
+                    self.report(&pointer, message);
+                }
+                let expected = format!("/{}/{}", service.name.value, method.name.value);
+                if path != &expected {
+                    let message =
+                        format!("wRPC calls this method at `{expected}`, not at `{path}`.");
+                    self.report(&pointer, message);
+                }
+                service.methods.push(method);
+            }
+        }
+
+        for (service, _) in services {
+            self.declarations.push(Decl::Service(service));
+        }
+    }
+
+    fn operation(
+        &mut self,
+        name: String,
+        operation: &'a Value,
+        shared: &[(&'a Value, String)],
+        pointer: &str,
+    ) -> Method {
+        let context = capitalize(&name);
+
+        // The parameters of an operation replace the ones of its path
+        // with the same name and location.
+        let own = operation.get("parameters").and_then(Value::as_array);
+        let own = own
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .map(|(index, parameter)| (parameter, format!("{pointer}/parameters/{index}")));
+        let mut resolved: Vec<(&'a Value, String)> = vec![];
+        for (parameter, pointer) in shared.iter().cloned().chain(own) {
+            let Some((parameter, pointer)) = self.follow(parameter, "parameters", pointer) else {
+                continue;
+            };
+            let key = |value: &Value| (value.get("name").cloned(), value.get("in").cloned());
+            resolved.retain(|(other, _)| key(other) != key(parameter));
+            resolved.push((parameter, pointer));
+        }
+
+        let mut parameter_names = HashSet::new();
+        let mut parameters = vec![];
+        for (parameter, pointer) in resolved {
+            let parameter = self.parameter(parameter, &pointer, &context, &mut parameter_names);
+            parameters.extend(parameter);
+        }
+
+        if let Some(body) = operation.get("requestBody") {
+            let pointer = format!("{pointer}/requestBody");
+            let body = self.request_body(body, pointer, &context, &mut parameter_names);
+            parameters.extend(body);
+        }
+
+        let return_type = self.responses(operation, pointer, &context);
+        if operation.get("callbacks").is_some() {
+            self.report(&format!("{pointer}/callbacks"), "wRPC has no callbacks.");
+        }
+
+        // Exported documents repeat the first line of the description
+        // as summary.
+        let summary = operation.get("summary").and_then(Value::as_str);
+        let description = operation.get("description").and_then(Value::as_str);
+        let summary = summary.map(str::trim).filter(|summary| {
+            !description.is_some_and(|description| description.trim().starts_with(summary))
+        });
+        let comment = [summary, description]
+            .into_iter()
+            .flatten()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .join("\n\n");
+
+        Method {
+            annotations: deprecated(operation),
+            doc_comment: (!comment.is_empty()).then(|| comment.replace('\r', "")),
+            name: Name::from_str(&name),
+            parameters,
+            return_type,
+            trailing_comment: None,
+        }
+    }
+
+    fn parameter(
+        &mut self,
+        parameter: &'a Value,
+        pointer: &str,
+        context: &str,
+        names: &mut HashSet<String>,
+    ) -> Option<Parameter> {
+        let name = parameter.get("name").and_then(Value::as_str)?;
+        match parameter.get("in").and_then(Value::as_str) {
+            Some(location @ ("path" | "query")) => {
+                let message = format!(
+                    "`{name}` is sent in the body, since wRPC has no {location} parameters."
+                );
+                self.report(pointer, message);
+            }
+            location => {
+                let location = location.unwrap_or("unknown");
+                let message =
+                    format!("`{name}` is left out, since wRPC has no {location} parameters.");
+                self.report(pointer, message);
+                return None;
+            }
+        }
+
+        let Some(schema) = parameter.get("schema") else {
+            let message = "Only parameters with a `schema` are translated.";
+            self.report(pointer, message);
+            return None;
+        };
+
+        let member = self.member(name, &format!("{pointer}/name"), names);
+        let context = format!("{context}{}", capitalize(&member));
+        let translated = self.translate(schema, &format!("{pointer}/schema"), &context);
+        let required = parameter.get("required") == Some(&Value::Bool(true));
+        let mut annotations = deprecated(parameter);
+        annotations.extend(checks(&translated.checks, &member));
+        Some(Parameter {
+            annotations,
+            doc_comment: description(parameter),
+            name: Name::from_str(&member),
+            type_: translated.type_(required),
+            blank_line_before: false,
+        })
+    }
+
+    /// Returns the parameters of a request body. The properties of an
+    /// object become parameters of their own, which keeps the wire
+    /// format of the body. Every other body becomes a single
+    /// parameter.
+    fn request_body(
+        &mut self,
+        body: &'a Value,
+        pointer: String,
+        context: &str,
+        names: &mut HashSet<String>,
+    ) -> Vec<Parameter> {
+        let Some((body, pointer)) = self.follow(body, "requestBodies", pointer) else {
+            return vec![];
+        };
+        let Some((media, schema)) = self.json(body, &pointer) else {
+            return vec![];
+        };
+
+        let pointer = format!(
+            "{}/schema",
+            pointer_to(&format!("{pointer}/content"), media)
+        );
+        let required = body.get("required") == Some(&Value::Bool(true));
+        let object = schema.as_object();
+        if schema.get("$ref").is_none() && object.is_some_and(|o| is_record(o) && !is_enum(o)) {
+            let properties = self.properties(&[(schema, pointer)], context, None, names);
+            return properties
+                .into_iter()
+                .map(|property| Parameter {
+                    annotations: property.annotations,
+                    doc_comment: property.doc_comment,
+                    name: property.name,
+                    type_: match required {
+                        true => property.type_,
+                        false => optional(property.type_),
+                    },
+                    blank_line_before: false,
+                })
+                .collect();
+        }
+
+        let reference = schema.get("$ref").and_then(Value::as_str);
+        let name = match reference.and_then(|r| r.strip_prefix("#/components/schemas/")) {
+            Some(component) => Name::from_str(&type_name(&unescape(component))).uncapitalized(),
+            None => "body".to_string(),
+        };
+        let name = unique(names, name);
+        let message = format!(
+            "The body becomes the parameter `{name}`, so it is sent as a property of the body."
+        );
+        self.report(&pointer, message);
+        let translated = self.translate(schema, &pointer, &format!("{context}Body"));
+        vec![Parameter {
+            annotations: checks(&translated.checks, &name),
+            doc_comment: description(body),
+            name: Name::from_str(&name),
+            type_: translated.type_(required),
+            blank_line_before: false,
+        }]
+    }
+
+    /// Returns the type of the first successful response. wRPC
+    /// methods have a single result, so all other responses are
+    /// reported.
+    fn responses(&mut self, operation: &'a Value, pointer: &str, context: &str) -> Option<Type> {
+        let responses = operation.get("responses").and_then(Value::as_object)?;
+        let mut success = false;
+        let mut return_type = None;
+        for (status, response) in self.entries(responses, &format!("{pointer}/responses")) {
+            let pointer = pointer_to(&format!("{pointer}/responses"), status);
+            let Some((response, pointer)) = self.follow(response, "responses", pointer) else {
+                continue;
+            };
+
+            if !status.starts_with('2') {
+                if response.get("content").is_some() {
+                    let message = format!(
+                        "The `{status}` response is left out, but could be declared with a `Result`."
+                    );
+                    self.report(&pointer, message);
+                }
+                continue;
+            }
+
+            if success {
+                if response.get("content").is_some() {
+                    let message = "Only the first successful response is translated.";
+                    self.report(&pointer, message);
+                }
+                continue;
+            }
+
+            success = true;
+            if let Some((media, schema)) = self.json(response, &pointer) {
+                let pointer = format!(
+                    "{}/schema",
+                    pointer_to(&format!("{pointer}/content"), media)
+                );
+                let translated = self.translate(schema, &pointer, &format!("{context}Response"));
+                if !translated.checks.is_empty() {
+                    let message = "The constraints of a response cannot be checked.";
+                    self.report(&pointer, message);
+                }
+                return_type = Some(translated.type_(true));
+            }
+        }
+
+        return_type
+    }
+
+    /// Returns the media type and schema of the JSON content of a
+    /// request body or response. All other content is reported.
+    fn json(&mut self, value: &'a Value, pointer: &str) -> Option<(&'a str, &'a Value)> {
+        let content = value.get("content").and_then(Value::as_object)?;
+        let mut json = None;
+        for (media, content) in self.entries(content, &format!("{pointer}/content")) {
+            let essence = media.split(';').next().unwrap_or_default().trim();
+            let is_json = essence.ends_with("/json") || essence.ends_with("+json");
+            match content.get("schema") {
+                Some(schema) if is_json && json.is_none() => json = Some((media.as_str(), schema)),
+                _ if is_json => {}
+                _ => {
+                    let message = format!("Only JSON content is translated, not `{media}`.");
+                    self.report(&pointer_to(&format!("{pointer}/content"), media), message);
+                }
+            }
+        }
+
+        json
+    }
+}
+
+/// Returns the components, which are only referenced as variants of a
+/// `oneOf` or `anyOf` with a discriminator. They do not need to be
+/// declared as records on their own.
+fn variants_only(document: &Value) -> HashSet<String> {
+    let mut variants = vec![];
+    let mut others = vec![];
+    collect_references(document, &mut variants, &mut others);
+    variants
+        .into_iter()
+        .filter(|variant| !others.contains(variant))
+        .filter_map(|variant| variant.strip_prefix("#/components/schemas/"))
+        .map(unescape)
+        .collect()
+}
+
+fn collect_references<'a>(
+    value: &'a Value,
+    variants: &mut Vec<&'a str>,
+    others: &mut Vec<&'a str>,
+) {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                collect_references(value, variants, others);
+            }
+        }
+        Value::Object(object) => {
+            let tagged = object.contains_key("discriminator");
+            for (key, value) in object {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => others.push(reference),
+                    ("oneOf" | "anyOf", Value::Array(branches)) if tagged => {
+                        for branch in branches {
+                            match branch.get("$ref").and_then(Value::as_str) {
+                                Some(reference) => variants.push(reference),
+                                None => collect_references(branch, variants, others),
+                            }
+                        }
+                    }
+                    _ => collect_references(value, variants, others),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns the `oneOf` or `anyOf` of a schema.
+fn branches(object: &Map<String, Value>) -> Option<(&'static str, &Vec<Value>)> {
+    ["oneOf", "anyOf"]
+        .into_iter()
+        .find_map(|key| Some((key, object.get(key)?.as_array()?)))
+}
+
+/// Returns the constant value of the tag property of an inline
+/// variant, like `circle` for `{"kind": {"const": "circle"}}`.
+fn const_tag<'v>(branch: &'v Value, tag: &str) -> Option<&'v str> {
+    let property = branch.get("properties")?.get(tag)?;
+    let value = property
+        .get("const")
+        .or_else(|| property.get("enum")?.get(0))?;
+    value.as_str()
+}
+
+/// Check, if the schema is declared as an enum.
+fn is_enum(object: &Map<String, Value>) -> bool {
+    match branches(object) {
+        Some((_, branches)) => branches.iter().filter(|branch| !is_null(branch)).count() > 1,
+        None => is_simple_enum(object),
+    }
+}
+
+/// Check, if the schema is an `enum` of strings, which are valid
+/// names of variants.
+fn is_simple_enum(object: &Map<String, Value>) -> bool {
+    let Some(values) = object.get("enum").and_then(Value::as_array) else {
+        return false;
+    };
+
+    let mut values = values.iter().filter(|value| !value.is_null()).peekable();
+    values.peek().is_some() && values.all(|value| value.as_str().is_some_and(is_identifier))
+}
+
+/// Check, if the schema is declared as a record.
+fn is_record(object: &Map<String, Value>) -> bool {
+    let all = object.get("allOf").and_then(Value::as_array);
+    object.contains_key("properties") || all.is_some_and(|all| all.len() > 1)
+}
+
+/// Check, if the schema describes an object, which can be a variant.
+fn is_object(object: &Map<String, Value>) -> bool {
+    is_record(object)
+        || object.contains_key("allOf")
+        || object.get("type").and_then(Value::as_str) == Some("object")
+}
+
+/// Returns the name of the method for an operation, which is its
+/// `operationId` or, if it has none, the given fallback.
+fn method_name(operation: &Value, fallback: &str, tag: &str, service: &Service) -> String {
+    // Exported documents prefix the id with the service, like
+    // `PersonService.get`.
+    let id = operation.get("operationId").and_then(Value::as_str);
+    let id = id.map(|id| match id.split_once('.') {
+        Some((prefix, id)) if prefix == tag || prefix == service.name.value => id,
+        _ => id,
+    });
+    match id {
+        Some(id) if is_identifier(id) => id.to_string(),
+        Some(id) => member_name(id),
+        None => member_name(fallback),
+    }
+}
+
+/// Returns the schemas of the key and value, if the schema is a pair,
+/// as which the entries of a `Map` are exported.
+fn pair(schema: &Value) -> Option<(&Value, &Value)> {
+    let object = schema.as_object()?;
+    let [key, value] = object.get("prefixItems")?.as_array()?.as_slice() else {
+        return None;
+    };
+
+    let keys = ["type", "prefixItems", "minItems", "maxItems"];
+    let is_pair = object.keys().all(|key| keys.contains(&key.as_str()))
+        && object.get("type") == Some(&json!("array"))
+        && object.get("minItems") == Some(&json!(2))
+        && object.get("maxItems") == Some(&json!(2));
+    is_pair.then_some((key, value))
+}
+
+/// Check, if the schema only allows `null`.
+fn is_null(schema: &Value) -> bool {
+    let type_ = schema.get("type");
+    type_.and_then(Value::as_str) == Some("null")
+        || type_
+            .and_then(Value::as_array)
+            .is_some_and(|types| !types.is_empty() && types.iter().all(|type_| type_ == "null"))
+        || schema.get("enum").and_then(Value::as_array) == Some(&vec![Value::Null])
+}
+
+/// Returns the annotations checking the property or parameter with
+/// the given name.
+fn checks(checks: &[Check], name: &str) -> Vec<Annotation> {
+    let subject = || symbol(&format!(".{name}"));
+    let length = || list(vec![symbol("len"), subject()]);
+    let operator = |exclusive: bool| if exclusive { "<" } else { "<=" };
+    let mut exprs = vec![];
+    for check in checks {
+        match check {
+            Check::Length(Some(min), Some(max)) if min == max => {
+                exprs.push(list(vec![symbol("="), length(), number(*min)]))
+            }
+            Check::Length(min, max) => exprs.push(chain("<=", *min, length(), *max)),
+            Check::Range(Some((min, a)), Some((max, b))) if a == b => {
+                exprs.push(chain(operator(*a), Some(*min), subject(), Some(*max)))
+            }
+            Check::Range(min, max) => {
+                if let Some((min, exclusive)) = min {
+                    exprs.push(chain(operator(*exclusive), Some(*min), subject(), None));
+                }
+                if let Some((max, exclusive)) = max {
+                    exprs.push(chain(operator(*exclusive), None, subject(), Some(*max)));
+                }
+            }
+            Check::OneOf(values) => {
+                let mut comparisons = values
+                    .iter()
+                    .map(|value| list(vec![symbol("="), subject(), value.clone()]))
+                    .collect::<Vec<_>>();
+                match comparisons.len() {
+                    1 => exprs.append(&mut comparisons),
+                    _ => {
+                        comparisons.insert(0, symbol("or"));
+                        exprs.push(list(comparisons));
+                    }
+                }
+            }
+        }
+    }
+
+    exprs
+        .into_iter()
+        .map(|expr| Annotation {
+            expr: list(vec![symbol("check"), expr]),
+        })
+        .collect()
+}
+
+/// Returns a comparison of the subject with its bounds, like
+/// `(<= 1 (len .name) 20)`.
+fn chain(operator: &str, min: Option<f64>, subject: Expr, max: Option<f64>) -> Expr {
+    let mut exprs = vec![symbol(operator)];
+    exprs.extend(min.map(number));
+    exprs.push(subject);
+    exprs.extend(max.map(number));
+    list(exprs)
+}
+
+/// Returns the value as an expression, if a check can compare with it.
+fn literal(value: &Value) -> Option<Expr> {
+    match value {
+        Value::String(value) => Some(Expr::String(region(), value.clone())),
+        Value::Bool(value) => Some(Expr::Boolean(region(), *value)),
+        Value::Number(value) => value.as_f64().filter(|value| *value >= 0.0).map(number),
+        _ => None,
+    }
+}
+
+fn deprecated(value: &Value) -> Vec<Annotation> {
+    match value.get("deprecated") == Some(&Value::Bool(true)) {
+        true => vec![Annotation {
+            expr: list(vec![symbol("deprecated")]),
+        }],
+        false => vec![],
+    }
+}
+
+fn description(value: &Value) -> Option<String> {
+    let description = value.get("description").and_then(Value::as_str)?;
+    let description = description.trim().replace('\r', "");
+    (!description.is_empty()).then_some(description)
+}
+
+fn region() -> Region {
+    Region::new(0, 0, 0, 0)
+}
+
+fn symbol(value: &str) -> Expr {
+    Expr::Symbol(region(), value.to_string())
+}
+
+fn number(value: f64) -> Expr {
+    Expr::Number(region(), value)
+}
+
+fn list(exprs: Vec<Expr>) -> Expr {
+    Expr::List(region(), exprs)
+}
+
+fn named(name: &str) -> Type {
+    generic(name, vec![])
+}
+
+fn generic(name: &str, variables: Vec<Type>) -> Type {
+    Type {
+        name: Name::from_str(name),
+        variables,
+    }
+}
+
+/// Returns the type as an `Option`, unless it already is one.
+fn optional(type_: Type) -> Type {
+    match type_.name.value.as_str() {
+        "Option" => type_,
+        _ => generic("Option", vec![type_]),
+    }
+}
+
+fn is_identifier(value: &str) -> bool {
+    let mut chars = value.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        None => String::new(),
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+    }
+}
+
+/// Returns the value as the name of a type, like `PetOwner` for
+/// `pet-owner`.
+fn type_name(value: &str) -> String {
+    let name = value
+        .split(|c: char| !c.is_alphanumeric())
+        .map(capitalize)
+        .collect::<String>();
+    match name.chars().next() {
+        None => "Unnamed".to_string(),
+        Some(first) if first.is_numeric() => format!("T{name}"),
+        Some(_) => name,
+    }
+}
+
+/// Returns the value as the name of a property, parameter or method,
+/// like `petOwner` for `pet-owner`.
+fn member_name(value: &str) -> String {
+    let name = type_name(value);
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().collect::<String>() + chars.as_str(),
+        None => name,
+    }
+}
+
+/// Returns the name, or the name with the first free number appended.
+fn unique(names: &mut HashSet<String>, name: String) -> String {
+    if names.insert(name.clone()) {
+        return name;
+    }
+
+    let name = (2..)
+        .map(|number| format!("{name}{number}"))
+        .find(|name| !names.contains(name))
+        .expect("There should be a free name");
+    names.insert(name.clone());
+    name
+}
+
+/// Returns the pointer to the key within the value at the base
+/// pointer, where `~` and `/` in the key are escaped.
+fn pointer_to(base: &str, key: &str) -> String {
+    format!("{base}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+fn unescape(value: &str) -> String {
+    value.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::codegen;
+    use crate::codegen::command::{Command, OpenapiOptions};
+    use crate::format::format_module;
+    use crate::import::openapi::{import, Document};
+
+    /// Import the document and check, that the result compiles.
+    fn document(text: &str) -> Document {
+        serde_json::from_str(text).expect("The document should be valid JSON")
+    }
+
+    /// Import the document and check, that the result compiles.
+    fn spec(document: &Document) -> String {
+        let import = import(document).unwrap();
+        let spec = format_module(&import.module);
+        crate::compile(None, &spec).expect("The imported spec should compile");
+        spec
+    }
+
+    #[test]
+    fn test_schemas() {
+        let document = document(
+            r##"{
+                "openapi": "3.0.3",
+                "components": {"schemas": {
+                    "Id": {"type": "string", "minLength": 1, "maxLength": 36},
+                    "Pet": {
+                        "description": "A pet.",
+                        "oneOf": [
+                            {"$ref": "#/components/schemas/Cat"},
+                            {"$ref": "#/components/schemas/Dog"}
+                        ],
+                        "discriminator": {
+                            "propertyName": "@type",
+                            "mapping": {"Kitten": "#/components/schemas/Cat"}
+                        }
+                    },
+                    "Cat": {
+                        "type": "object",
+                        "required": ["id"],
+                        "properties": {
+                            "id": {"$ref": "#/components/schemas/Id"},
+                            "lives": {"type": "integer", "format": "int32", "minimum": 0, "maximum": 9}
+                        }
+                    },
+                    "Dog": {"allOf": [
+                        {"$ref": "#/components/schemas/Cat"},
+                        {"properties": {"size": {"type": "string", "enum": ["Small", "Large"]}}}
+                    ]},
+                    "Owner": {
+                        "type": "object",
+                        "required": ["pets"],
+                        "properties": {
+                            "pets": {"type": "array", "uniqueItems": true, "items": {"$ref": "#/components/schemas/Pet"}},
+                            "nickname": {"type": "string", "nullable": true, "deprecated": true}
+                        }
+                    }
+                }}
+            }"##,
+        );
+
+        // `Cat` is declared on its own, since `Dog` extends it.
+        let expected = r#"// A pet.
+enum Pet {
+    Kitten {
+        #(check (<= 1 (len .id) 36))
+        id: String,
+        #(check (<= 0 .lives 9))
+        lives: Int32?,
+    },
+    Dog {
+        #(check (<= 1 (len .id) 36))
+        id: String,
+        #(check (<= 0 .lives 9))
+        lives: Int32?,
+        size: PetDogSize?,
+    },
+}
+
+enum PetDogSize {
+    Small,
+    Large,
+}
+
+data Cat {
+    #(check (<= 1 (len .id) 36))
+    id: String,
+    #(check (<= 0 .lives 9))
+    lives: Int32?,
+}
+
+data Owner {
+    pets: Set<Pet>,
+    #(deprecated)
+    nickname: String?,
+}
+"#;
+        assert_eq!(spec(&document), expected);
+    }
+
+    #[test]
+    fn test_operations() {
+        let document = document(
+            r##"{
+                "openapi": "3.1.0",
+                "tags": [{"name": "pets", "description": "All about pets."}],
+                "paths": {"/pets/{petId}": {
+                    "parameters": [{"name": "petId", "in": "path", "required": true, "schema": {"type": "integer"}}],
+                    "put": {
+                        "tags": ["pets"],
+                        "operationId": "updatePet",
+                        "summary": "Updates a pet.",
+                        "requestBody": {"required": true, "content": {"application/json": {"schema": {
+                            "type": "object",
+                            "required": ["name"],
+                            "properties": {"name": {"type": "string", "maxLength": 20}}
+                        }}}},
+                        "responses": {"200": {"description": "The pet.", "content": {"application/json": {"schema": {
+                            "type": "object",
+                            "properties": {"name": {"type": ["string", "null"]}}
+                        }}}}}
+                    },
+                    "delete": {
+                        "tags": ["pets"],
+                        "requestBody": {"content": {"application/json": {"schema": {"type": "boolean"}}}},
+                        "responses": {"204": {"description": "Deleted."}}
+                    }
+                }}
+            }"##,
+        );
+
+        let expected = r#"data UpdatePetResponse {
+    name: String?,
+}
+
+// All about pets.
+service PetsService {
+    // Updates a pet.
+    def updatePet(
+        petId: Int64,
+        #(check (<= (len .name) 20))
+        name: String,
+    ): UpdatePetResponse
+
+    def deletePetsPetId(petId: Int64, body: Boolean?)
+}
+"#;
+        assert_eq!(spec(&document), expected);
+
+        let import = import(&document).unwrap();
+        let unsupported = import
+            .unsupported
+            .iter()
+            .map(|unsupported| (unsupported.pointer.as_str(), unsupported.message.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            unsupported,
+            vec![
+                (
+                    "#/paths/~1pets~1{petId}/parameters/0",
+                    "`petId` is sent in the body, since wRPC has no path parameters."
+                ),
+                (
+                    "#/paths/~1pets~1{petId}/put",
+                    "wRPC calls every method with a `POST`, not a `PUT`."
+                ),
+                (
+                    "#/paths/~1pets~1{petId}/put",
+                    "wRPC calls this method at `/PetsService/updatePet`, not at `/pets/{petId}`."
+                ),
+                (
+                    "#/paths/~1pets~1{petId}/delete/requestBody/content/application~1json/schema",
+                    "The body becomes the parameter `body`, so it is sent as a property of the body."
+                ),
+                (
+                    "#/paths/~1pets~1{petId}/delete",
+                    "wRPC calls every method with a `POST`, not a `DELETE`."
+                ),
+                (
+                    "#/paths/~1pets~1{petId}/delete",
+                    "wRPC calls this method at `/PetsService/deletePetsPetId`, not at `/pets/{petId}`."
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exported_documents() {
+        // Generated documents sort the properties by name.
+        let source = r#"data Person {
+    age: Int32,
+    name: String,
+    scores: Map<String, Int32>,
+}
+
+service PersonService {
+    // Returns the person.
+    //
+    // Fails, if there is none.
+    def get(id: Int64): Person
+}
+"#;
+        let module = crate::compile(None, source).unwrap();
+        let options = OpenapiOptions {
+            print: false,
+            output: None,
+            title: "api".to_string(),
+            version: "1.0.0".to_string(),
+        };
+        let files = codegen::render(&module, &Command::Openapi(options)).unwrap();
+        let document = document(&files[0].content);
+
+        let import = import(&document).unwrap();
+        assert_eq!(format_module(&import.module), source);
+        assert!(import.unsupported.is_empty());
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let document = document(
+            r##"{
+                "openapi": "3.0.0",
+                "paths": {"/pets": {"get": {
+                    "parameters": [{"name": "X-Id", "in": "header", "schema": {"type": "string"}}],
+                    "responses": {"404": {"description": "Missing.", "content": {"application/json": {"schema": {}}}}}
+                }}},
+                "components": {"schemas": {"Pet": {
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string", "pattern": "^[a-z]+$"},
+                        "first-name": {"type": "string"}
+                    }
+                }}}
+            }"##,
+        );
+
+        let import = import(&document).unwrap();
+        let unsupported = import
+            .unsupported
+            .iter()
+            .map(|unsupported| unsupported.pointer.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            unsupported,
+            vec![
+                "#/components/schemas/Pet/properties/name/pattern",
+                "#/components/schemas/Pet/properties/first-name",
+                "#/paths/~1pets/get/parameters/0",
+                "#/paths/~1pets/get/responses/404",
+                "#/paths/~1pets/get",
+                "#/paths/~1pets/get",
+            ]
+        );
+
+        let swagger = self::document(r#"{"swagger": "2.0"}"#);
+        let error = crate::import::openapi::import(&swagger).unwrap_err();
+        assert_eq!(
+            error,
+            "Only OpenAPI 3.0 and 3.1 documents can be imported, not Swagger 2.0."
+        );
+    }
+}
@@ -1,7 +1,6 @@
 use std::path::PathBuf;
 
-use error::{syntax, Error};
-use reporting::WrpcDocBuilder;
+use error::Error;
 
 use crate::ast::canonical as can;
 use crate::ast::source::Module;
@@ -10,40 +9,48 @@ use crate::canonicalize::canonicalize;
 pub mod ast;
 pub mod canonicalize;
 pub mod codegen;
+pub mod diagnostic;
 pub mod docs;
 pub mod error;
+pub mod export;
+pub mod format;
+pub mod import;
+pub mod links;
 mod parse;
+pub mod random;
 pub mod reporting;
+pub mod value;
 
 /// Parse the given `str` into a [`Module`].
 pub fn parse(filename: Option<PathBuf>, source: &str) -> Result<Module, error::Error> {
     parse::parse(filename, source).map_err(error::Error::BadSyntax)
 }
 
+/// Parse the given `str` and format it according to the
+/// formatting rules of wRPC.
+pub fn format(filename: Option<PathBuf>, source: &str) -> Result<String, error::Error> {
+    let module = parse(filename, source)?;
+    Ok(format::format_module(&module))
+}
+
 pub fn compile(filename: Option<PathBuf>, source: &str) -> Result<can::Module, error::Error> {
     let module = parse(filename, source)?;
     canonicalize(&module).map_err(error::Error::BadCanonicalization)
 }
 
+/// Compile the given `str` like [`compile`] and return the module
+/// together with its warnings, like links to missing declarations.
+pub fn compile_with_warnings(
+    filename: Option<PathBuf>,
+    source: &str,
+) -> Result<(can::Module, Vec<error::canonicalize::Warning>), error::Error> {
+    let module = compile(filename, source)?;
+    let warnings = canonicalize::check_links(&module);
+    Ok((module, warnings))
+}
+
 /// Print all given errors to the terminal.
 pub fn print_errors(filename: &PathBuf, str: &str, error: Error) {
-    match error {
-        Error::BadSyntax(errors) => {
-            let alloc = WrpcDocBuilder::new(str);
-            for error in errors {
-                match error {
-                    syntax::Error::ParseError(error) => {
-                        let report = error.to_report(&alloc);
-                        println!(
-                            "\x1b[31m{}\x1b[0m\n",
-                            report.render(&Some(filename.clone()), reporting::Target::Terminal)
-                        );
-                    }
-                }
-            }
-        }
-        Error::BadCanonicalization(error) => {
-            println!("Bad canonicalization happened: {error:?}");
-        }
-    }
+    let output = diagnostic::render(filename, str, &error, diagnostic::MessageFormat::Human);
+    println!("{output}");
 }
@@ -0,0 +1,253 @@
+//! Links in doc comments to other declarations of a module.
+//!
+//! A comment can link to a record, enum, service or method with a
+//! Markdown link to its anchor in the docs, like `[an id](#Id)` or
+//! `[get](#PersonService.get)`, or with just its name in brackets,
+//! like `[Person]`. Links in code spans and code blocks are ignored.
+use std::ops::Range;
+
+use crate::ast::canonical::Module;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// The text of a Markdown link, which is `None` for `[Person]`.
+    pub text: Option<String>,
+    /// The declaration, like `Person` or `PersonService.get`.
+    pub target: String,
+    /// The bytes of the whole link in the comment.
+    pub range: Range<usize>,
+}
+
+impl Link {
+    /// Returns the text of the link, which defaults to its target.
+    pub fn text(&self) -> &str {
+        self.text.as_deref().unwrap_or(&self.target)
+    }
+
+    /// Returns the service and method, if the link points to a method.
+    pub fn method(&self) -> Option<(&str, &str)> {
+        self.target.split_once('.')
+    }
+}
+
+/// Returns all links in the given comment.
+pub fn parse(comment: &str) -> Vec<Link> {
+    let mut links = vec![];
+    let mut offset = 0;
+    let mut fenced = false;
+    for line in comment.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            fenced = !fenced;
+        } else if !fenced {
+            parse_line(line, offset, &mut links);
+        }
+        offset += line.len();
+    }
+
+    links
+}
+
+/// Replace every link in the comment, for which `f` returns a
+/// replacement.
+pub fn rewrite(comment: &str, f: impl Fn(&Link) -> Option<String>) -> String {
+    let mut result = String::with_capacity(comment.len());
+    let mut end = 0;
+    for link in parse(comment) {
+        if let Some(replacement) = f(&link) {
+            result.push_str(&comment[end..link.range.start]);
+            result.push_str(&replacement);
+            end = link.range.end;
+        }
+    }
+    result.push_str(&comment[end..]);
+    result
+}
+
+/// Returns a copy of the module, where every link, which does not
+/// resolve, is replaced by its text, so generators never translate
+/// a link to a missing declaration.
+pub fn unlink_unresolved(module: &Module) -> Module {
+    let unlink = |comment: &mut Option<String>| {
+        if let Some(value) = comment {
+            *value = rewrite(value, |link| {
+                (!resolves(module, link)).then(|| link.text().to_string())
+            });
+        }
+    };
+
+    let mut result = module.clone();
+    for record in result.records.values_mut() {
+        unlink(&mut record.comment);
+        record
+            .properties
+            .iter_mut()
+            .for_each(|property| unlink(&mut property.comment));
+    }
+
+    for enum_ in result.enums.values_mut() {
+        unlink(&mut enum_.comment);
+        for variant in enum_.variants.iter_mut() {
+            unlink(&mut variant.comment);
+            variant
+                .properties
+                .iter_mut()
+                .for_each(|property| unlink(&mut property.comment));
+        }
+    }
+
+    for service in result.services.values_mut() {
+        unlink(&mut service.comment);
+        for method in service.methods.values_mut() {
+            unlink(&mut method.comment);
+            method
+                .parameters
+                .iter_mut()
+                .for_each(|parameter| unlink(&mut parameter.comment));
+        }
+    }
+
+    result
+}
+
+/// Check, if the target of the link is declared in the module.
+pub fn resolves(module: &Module, link: &Link) -> bool {
+    match link.method() {
+        Some((service, method)) => module.get_method(service, method).is_some(),
+        None => {
+            module.records.contains_key(&link.target)
+                || module.enums.contains_key(&link.target)
+                || module.services.contains_key(&link.target)
+        }
+    }
+}
+
+fn parse_line(line: &str, offset: usize, links: &mut Vec<Link>) {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' => {
+                let ticks = bytes[i..].iter().take_while(|byte| **byte == b'`').count();
+                let delimiter = &line[i..i + ticks];
+                i += ticks;
+                if let Some(end) = line[i..].find(delimiter) {
+                    i += end + ticks;
+                }
+            }
+            b'[' => match parse_link(line, i) {
+                Some((text, target, end)) => {
+                    links.push(Link {
+                        text,
+                        target,
+                        range: offset + i..offset + end,
+                    });
+                    i = end;
+                }
+                None => i += 1,
+            },
+            _ => i += 1,
+        }
+    }
+}
+
+/// Returns the text, the target and the end of the link starting at
+/// the `[` at `start`.
+fn parse_link(line: &str, start: usize) -> Option<(Option<String>, String, usize)> {
+    let close = start + 1 + line[start + 1..].find(['[', ']'])?;
+    if line.as_bytes()[close] != b']' {
+        return None;
+    }
+
+    let text = &line[start + 1..close];
+    let rest = &line[close + 1..];
+    if let Some(destination) = rest.strip_prefix("(#") {
+        let end = destination.find(')')?;
+        let target = destination[..end].to_string();
+        return Some((Some(text.to_string()), target, close + 3 + end + 1));
+    }
+
+    // `[text](url)`, `[text][reference]` and `[reference]: url` are
+    // regular Markdown.
+    let reference = start > 0 && line.as_bytes()[start - 1] == b']';
+    if reference || rest.starts_with(['(', '[', ':']) || !is_name(text) {
+        return None;
+    }
+
+    Some((None, text.to_string(), close + 1))
+}
+
+/// Check, if the text looks like the name of a declaration or a
+/// method, like `Person` or `PersonService.get`.
+fn is_name(text: &str) -> bool {
+    let mut segments = text.split('.');
+    let starts_upper = text.starts_with(|c: char| c.is_ascii_uppercase());
+    starts_upper
+        && segments.clone().count() <= 2
+        && segments.all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::links::{parse, rewrite, unlink_unresolved, Link};
+
+    #[test]
+    fn test_parse() {
+        let comment = "See [Person], [the id](#Id) and [get](#PersonService.get).";
+        let links = parse(comment);
+        assert_eq!(
+            links,
+            vec![
+                Link {
+                    text: None,
+                    target: "Person".to_string(),
+                    range: 4..12,
+                },
+                Link {
+                    text: Some("the id".to_string()),
+                    target: "Id".to_string(),
+                    range: 14..27,
+                },
+                Link {
+                    text: Some("get".to_string()),
+                    target: "PersonService.get".to_string(),
+                    range: 32..57,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_regular_markdown_is_ignored() {
+        let comment = "[docs](https://example.com), [x], [a][Ref], `[Code]`, \\[Escaped]\n\
+                       ```\n[Block]\n```\n\
+                       [Ref]: https://example.com";
+        assert_eq!(parse(comment), vec![]);
+    }
+
+    #[test]
+    fn test_unlink_unresolved() {
+        let spec = "// A [Person] with a [Missing] and [an id](#Id).\ndata Person { id: Int64 }";
+        let module = crate::compile(None, spec).unwrap();
+        let module = unlink_unresolved(&module);
+        assert_eq!(
+            module.records["Person"].comment.as_deref(),
+            Some("A [Person] with a Missing and an id.")
+        );
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let comment = "A [Person] with [an id](#Id).";
+        let rewritten = rewrite(comment, |link| match link.target.as_str() {
+            "Person" => Some(format!("{{@link {}}}", link.target)),
+            _ => None,
+        });
+        assert_eq!(rewritten, "A {@link Person} with [an id](#Id).");
+    }
+}
@@ -24,6 +24,8 @@ struct Parser<T: Iterator<Item = LexResult>> {
     token1: Option<Result<(Region, Token), syntax::Token>>,
     errors: Vec<syntax::Error>,
     last_position: Position,
+    previous_line: usize,
+    trailing_comment: Option<String>,
 }
 
 impl<T> Parser<T>
@@ -36,6 +38,8 @@ where
             token1: None,
             errors: vec![],
             last_position: Position { line: 0, col: 0 },
+            previous_line: 0,
+            trailing_comment: None,
         }
     }
 
@@ -63,6 +67,7 @@ where
                 version: "1".into(),
                 declarations,
                 doc_comment: None,
+                trailing_comment: self.trailing_comment.take(),
             })
         }
     }
@@ -75,7 +80,12 @@ where
             .parse_annotations()
             .map_err(|error| syntax::Decl::BadData(syntax::Data::BadAnnotation(error)))?;
         match self.advance() {
-            None => Ok(None),
+            None => {
+                // A comment at the end of the file does not belong
+                // to any declaration, but should not get lost.
+                self.trailing_comment = comment;
+                Ok(None)
+            }
             Some(Ok((_, Token::Data))) => self
                 .parse_data(comment, annotations)
                 .map(|x| Some(Decl::Data(x)))
@@ -115,7 +125,8 @@ where
             syntax::Enum::MissingStart(pos.line, pos.col)
         })?;
 
-        let variants = self.parse_variants().map_err(syntax::Enum::BadVariant)?;
+        let (variants, trailing_comment) =
+            self.parse_variants().map_err(syntax::Enum::BadVariant)?;
         self.expect_token(Token::RBrace, |pos| {
             syntax::Enum::MissingEnd(pos.line, pos.col)
         })?;
@@ -126,13 +137,18 @@ where
             name,
             variants,
             type_variables,
+            trailing_comment,
         })
     }
 
-    fn parse_variants(&mut self) -> Result<Vec<Variant>, syntax::Variant> {
+    /// Parse a list of variants and a trailing comment, that
+    /// is not followed by any variant.
+    fn parse_variants(&mut self) -> Result<(Vec<Variant>, Option<String>), syntax::Variant> {
         let mut variants = vec![];
+        let mut trailing_comment = None;
         while !matches!(self.peek(), Some(Token::RBrace) | Some(Token::Eof) | None) {
-            self.parse_variant(&mut variants)?;
+            let blank_line_before = !variants.is_empty() && self.follows_blank_line();
+            trailing_comment = self.parse_variant(&mut variants, blank_line_before)?;
             if !matches!(self.peek(), Some(Token::RBrace)) {
                 self.expect_token(Token::Comma, |pos| {
                     syntax::Variant::MissingComma(pos.line, pos.col)
@@ -140,16 +156,27 @@ where
             }
         }
 
-        Ok(variants)
+        Ok((variants, trailing_comment))
     }
 
-    fn parse_variant(&mut self, variants: &mut Vec<Variant>) -> Result<(), syntax::Variant> {
+    /// Parse a single variant and push it to the given `variants`.
+    ///
+    /// Returns the parsed comment, if it is not followed by a variant.
+    fn parse_variant(
+        &mut self,
+        variants: &mut Vec<Variant>,
+        blank_line_before: bool,
+    ) -> Result<Option<String>, syntax::Variant> {
         let comment = self.parse_comment().map_err(syntax::Variant::BadComment)?;
         let annotations = self
             .parse_annotations()
             .map_err(syntax::Variant::BadAnnotation)?;
+        if annotations.is_empty() && matches!(self.peek(), Some(Token::RBrace)) {
+            return Ok(comment);
+        }
+
         let name = self.expect_name().map_err(syntax::Variant::BadName)?;
-        let properties = if self.matches(Token::LBrace) {
+        let (properties, trailing_comment) = if self.matches(Token::LBrace) {
             let properties = self
                 .parse_properties()
                 .map_err(syntax::Variant::BadProperty)?;
@@ -160,7 +187,7 @@ where
 
             properties
         } else {
-            vec![]
+            (vec![], None)
         };
 
         let variant = Variant {
@@ -168,11 +195,13 @@ where
             properties,
             annotations,
             doc_comment: comment,
+            trailing_comment,
+            blank_line_before,
         };
 
         variants.push(variant);
 
-        Ok(())
+        Ok(None)
     }
 
     fn parse_service(
@@ -185,7 +214,8 @@ where
             syntax::Service::MissingStart(pos.line, pos.col)
         })?;
 
-        let methods = self.parse_methods().map_err(syntax::Service::BadMethod)?;
+        let (methods, trailing_comment) =
+            self.parse_methods().map_err(syntax::Service::BadMethod)?;
         self.expect_token(Token::RBrace, |pos| {
             syntax::Service::MissingEnd(name.clone(), pos.line, pos.col)
         })?;
@@ -195,23 +225,37 @@ where
             doc_comment: comment,
             name,
             methods,
+            trailing_comment,
         })
     }
 
-    fn parse_methods(&mut self) -> Result<Vec<Method>, syntax::Method> {
+    /// Parse a list of methods and a trailing comment, that
+    /// is not followed by any method.
+    fn parse_methods(&mut self) -> Result<(Vec<Method>, Option<String>), syntax::Method> {
         let mut methods = vec![];
+        let mut trailing_comment = None;
         while !matches!(self.peek(), Some(Token::RBrace) | Some(Token::Eof) | None) {
-            self.parse_method(&mut methods)?;
+            trailing_comment = self.parse_method(&mut methods)?;
         }
 
-        Ok(methods)
+        Ok((methods, trailing_comment))
     }
 
-    fn parse_method(&mut self, methods: &mut Vec<Method>) -> Result<(), syntax::Method> {
+    /// Parse a single method and push it to the given `methods`.
+    ///
+    /// Returns the parsed comment, if it is not followed by a method.
+    fn parse_method(
+        &mut self,
+        methods: &mut Vec<Method>,
+    ) -> Result<Option<String>, syntax::Method> {
         let comment = self.parse_comment().map_err(syntax::Method::BadComment)?;
         let annotations = self
             .parse_annotations()
             .map_err(syntax::Method::BadAnnotation)?;
+        if annotations.is_empty() && matches!(self.peek(), Some(Token::RBrace)) {
+            return Ok(comment);
+        }
+
         self.expect_token(Token::Def, |pos| {
             syntax::Method::MissingDef(pos.line, pos.col)
         })?;
@@ -219,7 +263,8 @@ where
         self.expect_token(Token::LParen, |pos| {
             syntax::Method::MissingParamStart(name.clone(), pos.line, pos.col)
         })?;
-        let properties = self.parse_properties().map_err(syntax::Method::BadParam)?;
+        let (properties, trailing_comment) =
+            self.parse_properties().map_err(syntax::Method::BadParam)?;
 
         self.expect_token(Token::RParen, |pos| {
             syntax::Method::MissingParamEnd(name.clone(), pos.line, pos.col)
@@ -238,17 +283,20 @@ where
                 .map(|prop| Parameter {
                     name: prop.name.clone(),
                     annotations: prop.annotations.clone(),
+                    doc_comment: prop.doc_comment.clone(),
                     type_: prop.type_.clone(),
+                    blank_line_before: prop.blank_line_before,
                 })
                 .collect(),
             annotations,
             return_type,
             doc_comment: comment,
+            trailing_comment,
         };
 
         methods.push(method);
 
-        Ok(())
+        Ok(None)
     }
 
     fn parse_data(
@@ -259,10 +307,12 @@ where
         let name = self.expect_name().map_err(syntax::Data::BadName)?;
         let type_variables = self.parse_type_variable(syntax::Data::BadType)?;
         let mut properties = vec![];
+        let mut trailing_comment = None;
         if self.matches(Token::LBrace) {
-            let mut parsed_properties =
+            let (mut parsed_properties, comment) =
                 self.parse_properties().map_err(syntax::Data::BadProperty)?;
             properties.append(&mut parsed_properties);
+            trailing_comment = comment;
             self.expect_token(Token::RBrace, |pos| {
                 syntax::Data::MissingEnd(name.clone(), pos.line, pos.col)
             })?;
@@ -274,13 +324,18 @@ where
             name,
             properties,
             type_variables,
+            trailing_comment,
         })
     }
 
-    fn parse_properties(&mut self) -> Result<Vec<Property>, syntax::Property> {
+    /// Parse a list of properties and a trailing comment, that
+    /// is not followed by any property.
+    fn parse_properties(&mut self) -> Result<(Vec<Property>, Option<String>), syntax::Property> {
         let mut properties = vec![];
+        let mut trailing_comment = None;
         while self.matches_property_start() {
-            self.parse_property(&mut properties)?;
+            let blank_line_before = !properties.is_empty() && self.follows_blank_line();
+            trailing_comment = self.parse_property(&mut properties, blank_line_before)?;
             if !matches!(self.peek(), Some(Token::RBrace) | Some(Token::RParen)) {
                 self.expect_token(Token::Comma, |pos| {
                     syntax::Property::MissingComma(pos.line, pos.col)
@@ -288,10 +343,17 @@ where
             }
         }
 
-        Ok(properties)
+        Ok((properties, trailing_comment))
     }
 
-    fn parse_property(&mut self, properties: &mut Vec<Property>) -> Result<(), syntax::Property> {
+    /// Parse a single property and push it to the given `properties`.
+    ///
+    /// Returns the parsed comment, if it is not followed by a property.
+    fn parse_property(
+        &mut self,
+        properties: &mut Vec<Property>,
+        blank_line_before: bool,
+    ) -> Result<Option<String>, syntax::Property> {
         let comment = self.parse_comment().map_err(syntax::Property::BadComment)?;
         let annotations = self
             .parse_annotations()
@@ -311,11 +373,13 @@ where
                 type_,
                 annotations,
                 doc_comment: comment,
+                blank_line_before,
             };
             properties.push(property);
+            Ok(None)
+        } else {
+            Ok(comment)
         }
-
-        Ok(())
     }
 
     fn matches_property_start(&mut self) -> bool {
@@ -414,7 +478,13 @@ where
             Some(Ok((region, Token::String(value)))) => Expr::String(region, value),
             Some(Ok((region, Token::Number(value)))) => Expr::Number(region, value),
             Some(Ok((region, Token::Boolean(value)))) => Expr::Boolean(region, value),
-            Some(Ok((region, Token::Symbol(_, value)))) => Expr::Symbol(region, value),
+            Some(Ok((region, Token::Symbol(namespace, value)))) => {
+                if namespace.is_empty() {
+                    Expr::Symbol(region, value)
+                } else {
+                    Expr::Symbol(region, format!("{}/{}", namespace.join("/"), value))
+                }
+            }
             Some(Ok((region, Token::Keyword(value)))) => Expr::Keyword(region, value),
             Some(Ok((region, Token::Identifier(value)))) => Expr::Symbol(region, value),
             Some(Ok((region, Token::LAngle))) => Expr::Symbol(region, "<".into()),
@@ -517,6 +587,16 @@ where
         }
     }
 
+    /// Returns true, if an empty line lies between the previous
+    /// and the next token.
+    fn follows_blank_line(&mut self) -> bool {
+        self.next_token();
+        matches!(
+            &self.token1,
+            Some(Ok((region, _))) if region.start.line > self.previous_line + 1
+        )
+    }
+
     fn peek(&mut self) -> Option<&Token> {
         self.next_token();
         self.token1
@@ -532,6 +612,10 @@ where
                 self.last_position = region.end.clone();
                 None
             }
+            Some(Ok((region, token))) => {
+                self.previous_line = region.end.line;
+                Some(Ok((region, token)))
+            }
             Some(value) => Some(value),
         }
     }
@@ -761,6 +845,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_trailing_comments_of_enums_and_services() {
+        let source = r#"
+           enum Color {
+               Red,
+               // Blue
+           }
+
+           service ColorService {
+               def all(): List<Color>
+               // More
+           }
+           "#;
+        let lexer = create_lexer(source);
+        let mut parser = Parser::new(lexer);
+        let module = parser.parse_module().expect("Source should parse");
+
+        match &module.declarations[..] {
+            [Decl::Enum(enum_), Decl::Service(service)] => {
+                assert_eq!(enum_.variants.len(), 1);
+                assert_eq!(enum_.trailing_comment.as_deref(), Some("Blue"));
+                assert_eq!(service.methods.len(), 1);
+                assert_eq!(service.trailing_comment.as_deref(), Some("More"));
+            }
+            _ => panic!("Expected an enum and a service declaration"),
+        }
+    }
+
     #[test]
     fn test_malformed_input_missing_braces() {
         let source = r#"
@@ -217,7 +217,7 @@ impl<T: Iterator<Item = char>> Lexer<T> {
                 // Escaped next character
                 self.advance();
                 match self.advance() {
-                    Some('"') => result.push(c),
+                    Some('"') => result.push('"'),
                     Some('t') => result.push('\t'),
                     Some('n') => result.push('\n'),
                     Some('\\') => result.push('\\'),
@@ -0,0 +1,539 @@
+//! Generate random values of wRPC types, e.g. for mock servers or
+//! seeding a database.
+//!
+//! Values are reproducible for a seed and satisfy the `#(check ...)`
+//! constraints of their properties, where possible. Simple bounds,
+//! like `(<= (len .name) 5)`, `(< 0 .age 150)`, `(not (blank .name))`
+//! or `(or (= .country "DE") (= .country "CH"))`, are used directly.
+//! Other constraints are satisfied by generating a record again, until
+//! it is valid or the attempts are exhausted.
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::ast::canonical::{Module, Property, Type};
+use crate::ast::constraints::Constraint;
+use crate::value::{self, bind, substitute};
+
+/// Collections are left empty and optional values are `null` below
+/// this depth, so recursive types produce finite values.
+const MAX_DEPTH: usize = 4;
+
+/// Give up on types, which cannot be constructed in a finite way.
+const LIMIT_DEPTH: usize = 32;
+
+/// How often a record is generated again, if it violates a constraint.
+const ATTEMPTS: usize = 50;
+
+/// How often an element of a set or a key of a map is generated
+/// again, if it is already contained.
+const UNIQUE_ATTEMPTS: usize = 10;
+
+/// The range of numbers and lengths without any constraints.
+const DEFAULT_NUMBERS: (f64, f64) = (0.0, 1000.0);
+const DEFAULT_LENGTH: (usize, usize) = (3, 12);
+const DEFAULT_ITEMS: (usize, usize) = (1, 3);
+
+/// Generates random values for the types of a module.
+pub struct Generator<'a> {
+    module: &'a Module,
+    rng: Rng,
+    examples: bool,
+}
+
+impl<'a> Generator<'a> {
+    pub fn new(module: &'a Module, seed: u64) -> Self {
+        Generator {
+            module,
+            rng: Rng(seed),
+            examples: false,
+        }
+    }
+
+    /// Prefer the examples of records and properties over random values.
+    pub fn with_examples(mut self) -> Self {
+        self.examples = true;
+        self
+    }
+
+    /// Generate a value of the given type.
+    pub fn generate(&mut self, type_: &Type) -> Value {
+        self.value(type_, &Bounds::default(), 0)
+    }
+
+    fn value(&mut self, type_: &Type, bounds: &Bounds, depth: usize) -> Value {
+        if depth > LIMIT_DEPTH {
+            return Value::Null;
+        }
+
+        if let Some(choices) = &bounds.choices {
+            return choices[self.rng.below(choices.len())].clone();
+        }
+
+        match type_ {
+            Type::String => Value::String(self.string(bounds)),
+            Type::Boolean => Value::Bool(self.rng.below(2) == 0),
+            Type::Int32 => json!(self.integer(bounds, i32::MIN as f64, i32::MAX as f64)),
+            Type::Int64 => json!(self.integer(bounds, i64::MIN as f64, i64::MAX as f64)),
+            Type::Float32 | Type::Float64 => json!(self.float(bounds)),
+            Type::Option(_) if depth >= MAX_DEPTH || self.rng.below(4) == 0 => Value::Null,
+            Type::Option(type_) => self.value(type_, bounds, depth),
+            Type::List(type_) => (0..self.items(bounds, depth))
+                .map(|_| self.value(type_, &Bounds::default(), depth + 1))
+                .collect(),
+            Type::Set(type_) => {
+                let count = self.items(bounds, depth);
+                Value::Array(self.unique(type_, count, depth))
+            }
+            Type::Map(key, value) => {
+                let count = self.items(bounds, depth);
+                self.unique(key, count, depth)
+                    .into_iter()
+                    .map(|key| json!([key, self.value(value, &Bounds::default(), depth + 1)]))
+                    .collect()
+            }
+            Type::Result(error, _) if self.rng.below(5) == 0 => json!({
+                "@type": "Err",
+                "error": self.value(error, &Bounds::default(), depth + 1),
+            }),
+            Type::Result(_, ok) => json!({
+                "@type": "Ok",
+                "value": self.value(ok, &Bounds::default(), depth + 1),
+            }),
+            Type::Ref(name, variables) => self.reference(type_, name, variables, depth),
+        }
+    }
+
+    fn reference(&mut self, type_: &Type, name: &str, variables: &[Type], depth: usize) -> Value {
+        if let Some(record) = self.module.records.get(name) {
+            if let Some(example) = self.example(&record.examples) {
+                return example;
+            }
+
+            let types = bind(&record.type_variables, variables);
+            let properties = record.get_validation_ordered_properties();
+            return self.valid(type_, |generator| {
+                Value::Object(generator.properties(&properties, &types, depth))
+            });
+        }
+
+        let Some(enum_) = self.module.enums.get(name) else {
+            // An unbound type variable.
+            return Value::Null;
+        };
+
+        if enum_.variants.is_empty() {
+            return Value::Null;
+        }
+
+        // Deep down, prefer small variants to end recursive types.
+        let variant = if depth < MAX_DEPTH {
+            &enum_.variants[self.rng.below(enum_.variants.len())]
+        } else {
+            enum_
+                .variants
+                .iter()
+                .min_by_key(|variant| variant.properties.len())
+                .unwrap()
+        };
+
+        if enum_.is_simple() {
+            return json!(variant.name.value);
+        }
+
+        let types = bind(&enum_.type_variables, variables);
+        let properties = variant.properties.iter().collect::<Vec<_>>();
+        self.valid(type_, |generator| {
+            let mut object = Map::new();
+            object.insert("@type".to_string(), json!(variant.name.value));
+            object.extend(generator.properties(&properties, &types, depth));
+            Value::Object(object)
+        })
+    }
+
+    /// Generate values until one satisfies all constraints of its type.
+    fn valid(&mut self, type_: &Type, mut generate: impl FnMut(&mut Self) -> Value) -> Value {
+        let mut value = generate(self);
+        for _ in 1..ATTEMPTS {
+            if value::validate(self.module, type_, &value, "").is_empty() {
+                break;
+            }
+            value = generate(self);
+        }
+
+        value
+    }
+
+    fn properties(
+        &mut self,
+        properties: &[&Property],
+        types: &HashMap<&str, &Type>,
+        depth: usize,
+    ) -> Map<String, Value> {
+        properties
+            .iter()
+            .map(|property| {
+                let value = match self.example(&property.examples) {
+                    Some(example) => example,
+                    None => {
+                        let type_ = substitute(&property.type_, types);
+                        let bounds = Bounds::of(&property.name.value, &property.constraints);
+                        self.value(&type_, &bounds, depth + 1)
+                    }
+                };
+                (property.name.value.clone(), value)
+            })
+            .collect()
+    }
+
+    /// Pick one of the given examples, if they should be used.
+    fn example(&mut self, examples: &[Value]) -> Option<Value> {
+        if !self.examples || examples.is_empty() {
+            return None;
+        }
+
+        Some(examples[self.rng.below(examples.len())].clone())
+    }
+
+    fn string(&mut self, bounds: &Bounds) -> String {
+        let (min, max) = bounds.length(DEFAULT_LENGTH);
+        let length = self.rng.between(min as i64, max as i64) as usize;
+        (0..length)
+            .map(|_| (b'a' + self.rng.below(26) as u8) as char)
+            .collect()
+    }
+
+    /// Generate up to `count` distinct values of the given type. There
+    /// are fewer, if the type has too few values, like `Boolean`.
+    fn unique(&mut self, type_: &Type, count: usize, depth: usize) -> Vec<Value> {
+        let mut values: Vec<Value> = vec![];
+        for _ in 0..count {
+            for _ in 0..UNIQUE_ATTEMPTS {
+                let value = self.value(type_, &Bounds::default(), depth + 1);
+                if !values.contains(&value) {
+                    values.push(value);
+                    break;
+                }
+            }
+        }
+
+        values
+    }
+
+    fn items(&mut self, bounds: &Bounds, depth: usize) -> usize {
+        if depth >= MAX_DEPTH {
+            return bounds.min_length.unwrap_or(0);
+        }
+
+        let (min, max) = bounds.length(DEFAULT_ITEMS);
+        self.rng.between(min as i64, max as i64) as usize
+    }
+
+    fn integer(&mut self, bounds: &Bounds, lowest: f64, highest: f64) -> i64 {
+        let (min, max) = bounds.numbers(1.0);
+        let (min, max) = (min.ceil().max(lowest), max.floor().min(highest));
+        if min > max {
+            return min as i64;
+        }
+
+        self.rng.between(min as i64, max as i64)
+    }
+
+    fn float(&mut self, bounds: &Bounds) -> f64 {
+        let (min, max) = bounds.numbers(f64::EPSILON * 1000.0);
+        let value = min + self.rng.fraction() * (max - min);
+        // Two decimals are easier to read, if they are still in bounds.
+        let rounded = (value * 100.0).round() / 100.0;
+        match min <= rounded && rounded <= max {
+            true => rounded,
+            false => value,
+        }
+    }
+}
+
+/// The bounds of a single value, derived from the constraints of its
+/// property. All bounds are inclusive, unless marked as exclusive.
+///
+/// Bounds are never stricter than the constraints, but constraints,
+/// which cannot be expressed as bounds, are ignored.
+#[derive(Debug, Default)]
+pub(crate) struct Bounds {
+    pub(crate) min: Option<f64>,
+    pub(crate) max: Option<f64>,
+    /// Bounds of numbers, which are exclusive.
+    pub(crate) min_exclusive: bool,
+    pub(crate) max_exclusive: bool,
+    pub(crate) min_length: Option<usize>,
+    pub(crate) max_length: Option<usize>,
+    /// The value has to be one of these.
+    pub(crate) choices: Option<Vec<Value>>,
+}
+
+impl Bounds {
+    /// Derive the bounds of the property with the given name.
+    pub(crate) fn of(name: &str, constraints: &[Constraint]) -> Bounds {
+        let mut bounds = Bounds::default();
+        for constraint in constraints {
+            bounds.collect(name, constraint);
+        }
+
+        bounds
+    }
+
+    fn collect(&mut self, name: &str, constraint: &Constraint) {
+        let is_value = |c: &Constraint| matches!(c, Constraint::Access(n) if n == name);
+        let is_length =
+            |c: &Constraint| matches!(c, Constraint::Len(inner) if is_value(inner.as_ref()));
+
+        match constraint {
+            Constraint::And(constraints) => {
+                for constraint in constraints {
+                    self.collect(name, constraint);
+                }
+            }
+            Constraint::Not(inner) => match inner.as_ref() {
+                Constraint::Blank(inner) if is_value(inner) => {
+                    self.min_length = Some(self.min_length.unwrap_or(0).max(1));
+                }
+                _ => {}
+            },
+            Constraint::Or(constraints) => {
+                let choices = constraints
+                    .iter()
+                    .map(|constraint| match constraint {
+                        Constraint::Eq(args) => equal_literal(args, &is_value),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<Value>>>();
+                if let Some(choices) = choices.filter(|choices| !choices.is_empty()) {
+                    self.choices = Some(choices);
+                }
+            }
+            Constraint::Eq(args) => {
+                if let Some(literal) = equal_literal(args, &is_value) {
+                    self.choices = Some(vec![literal]);
+                } else if let Some(length) = equal_literal(args, &is_length) {
+                    let length = length.as_f64().unwrap_or(0.0).max(0.0) as usize;
+                    self.min_length = Some(length);
+                    self.max_length = Some(length);
+                }
+            }
+            Constraint::Lt(args) => self.order(args, true, false, &is_value, &is_length),
+            Constraint::Le(args) => self.order(args, false, false, &is_value, &is_length),
+            Constraint::Gt(args) => self.order(args, true, true, &is_value, &is_length),
+            Constraint::Ge(args) => self.order(args, false, true, &is_value, &is_length),
+            _ => {}
+        }
+    }
+
+    /// Collect the bounds of a chain like `(< 0 .age 150)`, which is
+    /// ascending, or descending for `>` and `>=`.
+    fn order(
+        &mut self,
+        args: &[Constraint],
+        exclusive: bool,
+        descending: bool,
+        is_value: &dyn Fn(&Constraint) -> bool,
+        is_length: &dyn Fn(&Constraint) -> bool,
+    ) {
+        for pair in args.windows(2) {
+            let (low, high) = match descending {
+                false => (&pair[0], &pair[1]),
+                true => (&pair[1], &pair[0]),
+            };
+
+            match (low, high) {
+                // Only keep the tightest bound.
+                (Constraint::Number(min), high)
+                    if is_value(high) && self.min.is_none_or(|current| *min >= current) =>
+                {
+                    self.min = Some(*min);
+                    self.min_exclusive = exclusive;
+                }
+                (low, Constraint::Number(max))
+                    if is_value(low) && self.max.is_none_or(|current| *max <= current) =>
+                {
+                    self.max = Some(*max);
+                    self.max_exclusive = exclusive;
+                }
+                (Constraint::Number(min), high) if is_length(high) => {
+                    let min = (min.max(0.0) + if exclusive { 1.0 } else { 0.0 }) as usize;
+                    self.min_length = Some(self.min_length.unwrap_or(0).max(min));
+                }
+                (low, Constraint::Number(max)) if is_length(low) => {
+                    let max = (max.max(0.0) - if exclusive { 1.0 } else { 0.0 }).max(0.0) as usize;
+                    self.max_length = Some(self.max_length.map_or(max, |current| current.min(max)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The inclusive range of numbers, where exclusive bounds are
+    /// moved by the given step. Without bounds, the range is
+    /// [`DEFAULT_NUMBERS`], or as wide around a single bound.
+    fn numbers(&self, step: f64) -> (f64, f64) {
+        let min = self
+            .min
+            .map(|min| if self.min_exclusive { min + step } else { min });
+        let max = self
+            .max
+            .map(|max| if self.max_exclusive { max - step } else { max });
+        let width = DEFAULT_NUMBERS.1 - DEFAULT_NUMBERS.0;
+        match (min, max) {
+            (Some(min), Some(max)) => (min, max),
+            (Some(min), None) => (min, min.max(DEFAULT_NUMBERS.0) + width),
+            (None, Some(max)) => (max.min(DEFAULT_NUMBERS.1) - width, max),
+            (None, None) => DEFAULT_NUMBERS,
+        }
+    }
+
+    /// The inclusive range of lengths, which defaults to the given range.
+    fn length(&self, default: (usize, usize)) -> (usize, usize) {
+        match (self.min_length, self.max_length) {
+            (Some(min), Some(max)) => (min, max.max(min)),
+            (Some(min), None) => (min, min.max(default.1)),
+            (None, Some(max)) => (default.0.min(max), max),
+            (None, None) => default,
+        }
+    }
+}
+
+/// Returns the literal of `(= .name literal)` or `(= literal .name)`,
+/// where `.name` is matched by `is_target`.
+fn equal_literal(args: &[Constraint], is_target: &dyn Fn(&Constraint) -> bool) -> Option<Value> {
+    let literal = |constraint: &Constraint| match constraint {
+        Constraint::Number(value) => Some(json!(value)),
+        Constraint::String(value) => Some(json!(value)),
+        Constraint::Boolean(value) => Some(json!(value)),
+        _ => None,
+    };
+
+    match args {
+        [a, b] if is_target(a) => literal(b),
+        [a, b] if is_target(b) => literal(a),
+        _ => None,
+    }
+    .map(|value| match value.as_f64() {
+        // Keep integers as integers, like `5` instead of `5.0`.
+        Some(number) if number.fract() == 0.0 && value.is_f64() => json!(number as i64),
+        _ => value,
+    })
+}
+
+/// A SplitMix64 generator. Its output is defined here instead of in a
+/// dependency, so the values for a seed never change.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, or 0 if `n` is 0.
+    fn below(&mut self, n: usize) -> usize {
+        match n {
+            0 => 0,
+            n => (self.next() % n as u64) as usize,
+        }
+    }
+
+    /// A number in `min..=max`.
+    fn between(&mut self, min: i64, max: i64) -> i64 {
+        let width = (max as i128 - min as i128 + 1) as u128;
+        (min as i128 + (self.next() as u128 % width) as i128) as i64
+    }
+
+    /// A number in `0.0..1.0`.
+    fn fraction(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use serde_json::Value;
+
+    use crate::ast::canonical::Type;
+    use crate::random::Generator;
+    use crate::value::{check, validate};
+
+    const SPEC: &str = r#"
+data Person {
+    #(check (not (blank .name)))
+    #(check (<= (len .name) 5))
+    name: String,
+    #(check (< 0 .age 150))
+    age: Int32?,
+    #(check (>= (len .tags) 2))
+    tags: List<String>,
+    address: Address,
+    friends: List<Person>,
+    shape: Shape,
+    #(check (< .min .max))
+    min: Float64,
+    max: Float64,
+}
+
+data Address {
+    #(check (or (= .country "DE") (= .country "CH")))
+    country: String,
+    #(check (and (= .country "DE") (= (len .zipcode) 5)))
+    zipcode: String,
+}
+
+enum Shape {
+    Circle { radius: Float64 },
+    Square { length: Float64 },
+}
+"#;
+
+    #[test]
+    fn test_values_satisfy_types_and_constraints() {
+        let module = crate::compile(None, SPEC).unwrap();
+        let type_ = Type::Ref("Person".into(), vec![]);
+        let mut generator = Generator::new(&module, 42);
+        for _ in 0..20 {
+            let value = generator.generate(&type_);
+            assert_eq!(check(&module, &type_, &value, ""), Ok(()));
+            assert_eq!(validate(&module, &type_, &value, ""), vec![]);
+        }
+    }
+
+    #[test]
+    fn test_sets_and_maps_are_unique() {
+        let module = crate::compile(None, SPEC).unwrap();
+        let mut generator = Generator::new(&module, 3);
+        for _ in 0..50 {
+            let set = generator.generate(&Type::Set(Box::new(Type::Boolean)));
+            let set = set.as_array().unwrap();
+            assert!(set.len() <= 2);
+            assert!(set.iter().map(Value::to_string).all_unique());
+
+            let map = Type::Map(Box::new(Type::Boolean), Box::new(Type::Int32));
+            let map = generator.generate(&map);
+            let mut keys = map
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|entry| entry[0].to_string());
+            assert!(keys.all_unique());
+        }
+    }
+
+    #[test]
+    fn test_values_are_reproducible() {
+        let module = crate::compile(None, SPEC).unwrap();
+        let type_ = Type::List(Box::new(Type::Ref("Person".into(), vec![])));
+        let first = Generator::new(&module, 7).generate(&type_);
+        let second = Generator::new(&module, 7).generate(&type_);
+        let other = Generator::new(&module, 8).generate(&type_);
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+}
@@ -88,30 +88,50 @@ pub struct Report<'a> {
     pub doc: BoxDoc<'a, ()>,
 }
 
-#[derive(Debug)]
+/// The target a [`Report`] is rendered for.
+#[derive(Debug, Clone, Copy)]
 pub enum Target {
-    _Text,
+    /// Plain text, without any escape codes.
+    Text,
+    /// A terminal, supporting ANSI colors.
     Terminal,
+    /// An HTML fragment, e.g. to show a report in the browser.
+    Html,
 }
 
 impl<'a> Report<'a> {
     pub fn render(&self, filename: &Option<PathBuf>, target: Target) -> String {
         let name = filename.as_ref().and_then(|x| x.as_os_str().to_str());
+        let header = pretty_header(&self.title, name);
+        let body = self.doc.deref().pretty(70).to_string();
         match target {
-            Target::_Text => format!(
-                "{}\n\n{}",
-                pretty_header(&self.title, name),
-                self.doc.deref().pretty(70)
-            ),
-            Target::Terminal => format!(
-                "{}\n\n{}",
-                pretty_header(&self.title, name),
-                self.doc.deref().pretty(70)
+            Target::Text => format!("{header}\n\n{body}"),
+            Target::Terminal => format!("\x1b[31m{header}\n\n{body}\x1b[0m"),
+            Target::Html => format!(
+                "<pre class=\"report\"><span class=\"report-header\">{}</span>\n\n{}</pre>",
+                escape_html(&header),
+                escape_html(&body)
             ),
         }
     }
 }
 
+/// Escape the characters with a special meaning in HTML.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            char => escaped.push(char),
+        }
+    }
+    escaped
+}
+
 type WrpcDoc<'a> = BoxDoc<'a, ()>;
 
 #[derive(Debug)]
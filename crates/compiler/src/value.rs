@@ -0,0 +1,664 @@
+//! JSON values of wRPC types, following the wire format described in
+//! the README, e.g. enums with data use an `@type` discriminator and a
+//! `Map` is a list of pairs.
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+use crate::ast::canonical::{Expr, Module, Property, Type};
+use crate::ast::constraints::Constraint;
+use crate::ast::source::Name;
+use crate::reporting::Region;
+
+/// A value, which does not match its declared type.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Mismatch {
+    /// The JSON pointer to the value, see RFC 6901.
+    pub path: String,
+    /// A description of the expected value, like `Int32`.
+    pub expected: String,
+    pub message: String,
+}
+
+/// A value, which does not match its type or violates a constraint.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Violation {
+    /// The JSON pointer to the value, see RFC 6901.
+    pub path: String,
+    /// The violated `#(check ...)` expression or the expected type.
+    pub constraint: String,
+    pub message: String,
+}
+
+impl From<Mismatch> for Violation {
+    fn from(value: Mismatch) -> Self {
+        Violation {
+            path: value.path,
+            constraint: value.expected,
+            message: value.message,
+        }
+    }
+}
+
+/// Check, that the value at the given path matches the given type.
+pub fn check(module: &Module, type_: &Type, value: &Value, path: &str) -> Result<(), Mismatch> {
+    match (type_, value) {
+        (Type::Option(_), Value::Null) => Ok(()),
+        (Type::Option(type_), value) => check(module, type_, value, path),
+        (Type::String, Value::String(_)) => Ok(()),
+        (Type::Boolean, Value::Bool(_)) => Ok(()),
+        (Type::Int32, Value::Number(number))
+            if number.as_i64().is_some_and(|n| i32::try_from(n).is_ok()) =>
+        {
+            Ok(())
+        }
+        (Type::Int64, Value::Number(number)) if number.is_i64() => Ok(()),
+        (Type::Float32 | Type::Float64, Value::Number(_)) => Ok(()),
+        (Type::List(type_) | Type::Set(type_), Value::Array(values)) => {
+            for (index, value) in values.iter().enumerate() {
+                check(module, type_, value, &pointer(path, index))?;
+            }
+            Ok(())
+        }
+        (Type::Map(key, value_type), Value::Array(pairs)) => {
+            for (index, pair) in pairs.iter().enumerate() {
+                let path = pointer(path, index);
+                match pair.as_array().map(|pair| pair.as_slice()) {
+                    Some([k, v]) => {
+                        check(module, key, k, &pointer(&path, 0))?;
+                        check(module, value_type, v, &pointer(&path, 1))?;
+                    }
+                    _ => return Err(mismatch(&path, "a pair of key and value", pair)),
+                }
+            }
+            Ok(())
+        }
+        (Type::Result(error, ok), Value::Object(object)) => {
+            match object.get("@type").and_then(Value::as_str) {
+                Some("Ok") => check(module, ok, field(object, "value"), &pointer(path, "value")),
+                Some("Err") => check(
+                    module,
+                    error,
+                    field(object, "error"),
+                    &pointer(path, "error"),
+                ),
+                _ => Err(mismatch(
+                    &pointer(path, "@type"),
+                    "\"Ok\" or \"Err\"",
+                    field(object, "@type"),
+                )),
+            }
+        }
+        (Type::Ref(name, variables), value) => check_ref(module, name, variables, value, path),
+        (type_, value) => Err(mismatch(path, &type_.to_string(), value)),
+    }
+}
+
+fn check_ref(
+    module: &Module,
+    name: &str,
+    variables: &[Type],
+    value: &Value,
+    path: &str,
+) -> Result<(), Mismatch> {
+    if let Some(record) = module.records.get(name) {
+        let Value::Object(object) = value else {
+            return Err(mismatch(path, name, value));
+        };
+
+        let types = bind(&record.type_variables, variables);
+        return check_properties(module, &record.properties, &types, object, path);
+    }
+
+    let Some(enum_) = module.enums.get(name) else {
+        // An unbound type variable accepts everything.
+        return Ok(());
+    };
+
+    if enum_.is_simple() {
+        let is_variant = value
+            .as_str()
+            .is_some_and(|value| enum_.variants.iter().any(|v| v.name.value == value));
+        return match is_variant {
+            true => Ok(()),
+            false => Err(mismatch(path, &format!("a variant of {name}"), value)),
+        };
+    }
+
+    let Value::Object(object) = value else {
+        return Err(mismatch(path, name, value));
+    };
+
+    let tag = object.get("@type").and_then(Value::as_str);
+    match enum_
+        .variants
+        .iter()
+        .find(|v| Some(v.name.value.as_str()) == tag)
+    {
+        Some(variant) => {
+            let types = bind(&enum_.type_variables, variables);
+            check_properties(module, &variant.properties, &types, object, path)
+        }
+        None => Err(mismatch(
+            &pointer(path, "@type"),
+            &format!("a variant of {name}"),
+            field(object, "@type"),
+        )),
+    }
+}
+
+fn check_properties(
+    module: &Module,
+    properties: &[Property],
+    types: &HashMap<&str, &Type>,
+    object: &Map<String, Value>,
+    path: &str,
+) -> Result<(), Mismatch> {
+    for property in properties {
+        let type_ = substitute(&property.type_, types);
+        let value = field(object, &property.name.value);
+        check(module, &type_, value, &pointer(path, &property.name.value))?;
+    }
+
+    Ok(())
+}
+
+/// Convert an EDN expression of an annotation into JSON. Keywords
+/// become strings, lists become arrays and `nil` becomes `null`.
+///
+/// Returns the region and a description of the first expression,
+/// which cannot be converted.
+pub fn from_expr(expr: &Expr) -> Result<Value, (Region, String)> {
+    match expr {
+        Expr::Boolean(_, value) => Ok(Value::Bool(*value)),
+        Expr::Number(region, value) => number(*value)
+            .map(Value::Number)
+            .ok_or_else(|| (region.clone(), format!("`{value}` is not a valid number."))),
+        Expr::Keyword(_, value) | Expr::String(_, value) => Ok(Value::String(value.clone())),
+        Expr::Symbol(_, value) if value == "nil" => Ok(Value::Null),
+        Expr::Symbol(region, value) => Err((
+            region.clone(),
+            format!("The symbol `{value}` has no JSON value. Use a string or keyword instead."),
+        )),
+        Expr::List(_, values) => values.iter().map(from_expr).collect(),
+        Expr::Map(_, entries) => {
+            let mut object = Map::new();
+            for (key, value) in entries {
+                let key = match key {
+                    Expr::Keyword(_, key) | Expr::String(_, key) => key.clone(),
+                    key => {
+                        let message = "Only keywords and strings can be keys of a map.";
+                        return Err((key.region(), message.to_string()));
+                    }
+                };
+                object.insert(key, from_expr(value)?);
+            }
+            Ok(Value::Object(object))
+        }
+    }
+}
+
+/// EDN only knows a single kind of number, but integers should stay
+/// integers in JSON.
+fn number(value: f64) -> Option<Number> {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        Some(Number::from(value as i64))
+    } else {
+        Number::from_f64(value)
+    }
+}
+
+/// Bind the type variables of a declaration to the given types.
+pub fn bind<'a>(variables: &'a [Name], types: &'a [Type]) -> HashMap<&'a str, &'a Type> {
+    variables
+        .iter()
+        .map(|variable| variable.value.as_str())
+        .zip(types)
+        .collect()
+}
+
+/// Replace all bound type variables in the given type.
+pub fn substitute(type_: &Type, types: &HashMap<&str, &Type>) -> Type {
+    let boxed = |type_: &Type| Box::new(substitute(type_, types));
+    match type_ {
+        Type::Ref(name, variables) if variables.is_empty() => match types.get(name.as_str()) {
+            Some(type_) => (*type_).clone(),
+            None => type_.clone(),
+        },
+        Type::Ref(name, variables) => Type::Ref(
+            name.clone(),
+            variables
+                .iter()
+                .map(|type_| substitute(type_, types))
+                .collect(),
+        ),
+        Type::Map(key, value) => Type::Map(boxed(key), boxed(value)),
+        Type::Result(error, ok) => Type::Result(boxed(error), boxed(ok)),
+        Type::List(type_) => Type::List(boxed(type_)),
+        Type::Set(type_) => Type::Set(boxed(type_)),
+        Type::Option(type_) => Type::Option(boxed(type_)),
+        _ => type_.clone(),
+    }
+}
+
+/// Append the given token to a JSON pointer, see RFC 6901.
+pub fn pointer(path: &str, token: impl ToString) -> String {
+    let token = token.to_string().replace('~', "~0").replace('/', "~1");
+    format!("{path}/{token}")
+}
+
+fn field<'a>(object: &'a Map<String, Value>, name: &str) -> &'a Value {
+    object.get(name).unwrap_or(&Value::Null)
+}
+
+pub fn mismatch(path: &str, expected: &str, found: &Value) -> Mismatch {
+    let found = match found {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    };
+
+    Mismatch {
+        path: path.to_string(),
+        expected: expected.to_string(),
+        message: format!("Expected {expected}, but found {found}."),
+    }
+}
+
+/// Check all constraints of the value at the given path, which has
+/// to match its type already, see [`check`].
+///
+/// The properties of a record are validated in the order of
+/// [`Record::get_validation_ordered_properties`], and the constraints
+/// of a property are skipped, if a property it depends on is invalid.
+///
+/// [`Record::get_validation_ordered_properties`]: crate::ast::canonical::Record::get_validation_ordered_properties
+pub fn validate(module: &Module, type_: &Type, value: &Value, path: &str) -> Vec<Violation> {
+    let mut violations = vec![];
+    validate_value(module, type_, value, path, &mut violations);
+    violations
+}
+
+fn validate_value(
+    module: &Module,
+    type_: &Type,
+    value: &Value,
+    path: &str,
+    violations: &mut Vec<Violation>,
+) {
+    match (type_, value) {
+        (Type::Option(type_), value) if !value.is_null() => {
+            validate_value(module, type_, value, path, violations)
+        }
+        (Type::List(type_) | Type::Set(type_), Value::Array(values)) => {
+            for (index, value) in values.iter().enumerate() {
+                validate_value(module, type_, value, &pointer(path, index), violations);
+            }
+        }
+        (Type::Map(key, value_type), Value::Array(pairs)) => {
+            for (index, pair) in pairs.iter().enumerate() {
+                if let Some([k, v]) = pair.as_array().map(|pair| pair.as_slice()) {
+                    let path = pointer(path, index);
+                    validate_value(module, key, k, &pointer(&path, 0), violations);
+                    validate_value(module, value_type, v, &pointer(&path, 1), violations);
+                }
+            }
+        }
+        (Type::Result(error, ok), Value::Object(object)) => {
+            match object.get("@type").and_then(Value::as_str) {
+                Some("Ok") => {
+                    let path = pointer(path, "value");
+                    validate_value(module, ok, field(object, "value"), &path, violations)
+                }
+                Some("Err") => {
+                    let path = pointer(path, "error");
+                    validate_value(module, error, field(object, "error"), &path, violations)
+                }
+                _ => {}
+            }
+        }
+        (Type::Ref(name, variables), Value::Object(object)) => {
+            if let Some(record) = module.records.get(name.as_str()) {
+                let types = bind(&record.type_variables, variables);
+                let properties = record.get_validation_ordered_properties();
+                validate_properties(module, &properties, &types, object, path, violations);
+                validate_constraints(name, &record.constraints, object, path, violations);
+            } else if let Some(enum_) = module.enums.get(name.as_str()) {
+                let tag = object.get("@type").and_then(Value::as_str);
+                let variant = enum_
+                    .variants
+                    .iter()
+                    .find(|v| Some(v.name.value.as_str()) == tag);
+                if let Some(variant) = variant {
+                    let types = bind(&enum_.type_variables, variables);
+                    let properties = variant.properties.iter().collect::<Vec<_>>();
+                    validate_properties(module, &properties, &types, object, path, violations);
+                    let name = &variant.name.value;
+                    validate_constraints(name, &variant.constraints, object, path, violations);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn validate_properties(
+    module: &Module,
+    properties: &[&Property],
+    types: &HashMap<&str, &Type>,
+    object: &Map<String, Value>,
+    path: &str,
+    violations: &mut Vec<Violation>,
+) {
+    let mut invalid = HashSet::new();
+    for property in properties {
+        let name = property.name.value.as_str();
+        if property
+            .deps
+            .iter()
+            .any(|dep| invalid.contains(dep.as_str()))
+        {
+            invalid.insert(name);
+            continue;
+        }
+
+        let count = violations.len();
+        let type_ = substitute(&property.type_, types);
+        let value = field(object, name);
+        let path = pointer(path, name);
+        validate_value(module, &type_, value, &path, violations);
+
+        // The constraints of an absent optional value do not apply.
+        if !(value.is_null() && matches!(type_, Type::Option(_))) {
+            validate_constraints(name, &property.constraints, object, &path, violations);
+        }
+
+        if violations.len() > count {
+            invalid.insert(name);
+        }
+    }
+}
+
+/// Check the constraints of the named element, which access the
+/// properties of the given object.
+pub fn validate_constraints(
+    name: &str,
+    constraints: &[Constraint],
+    object: &Map<String, Value>,
+    path: &str,
+    violations: &mut Vec<Violation>,
+) {
+    for constraint in constraints {
+        if !is_truthy(&eval(constraint, object)) {
+            violations.push(Violation {
+                path: path.to_string(),
+                constraint: constraint.to_string(),
+                message: format!("`{name}` does not satisfy `{constraint}`."),
+            });
+        }
+    }
+}
+
+/// Evaluate the constraint, where `.name` refers to the property
+/// `name` of the given object.
+fn eval(constraint: &Constraint, object: &Map<String, Value>) -> Value {
+    let all = |args: &[Constraint]| args.iter().map(|arg| eval(arg, object)).collect::<Vec<_>>();
+    match constraint {
+        Constraint::Number(value) => number(*value).map_or(Value::Null, Value::Number),
+        Constraint::String(value) => Value::String(value.clone()),
+        Constraint::Boolean(value) => Value::Bool(*value),
+        Constraint::Access(name) => field(object, name).clone(),
+        Constraint::Map(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    let key = match eval(key, object) {
+                        Value::String(key) => key,
+                        key => key.to_string(),
+                    };
+                    (key, eval(value, object))
+                })
+                .collect(),
+        ),
+        Constraint::Len(arg) => match eval(arg, object) {
+            Value::String(value) => Value::from(value.chars().count()),
+            Value::Array(values) => Value::from(values.len()),
+            Value::Object(values) => Value::from(values.len()),
+            _ => Value::Null,
+        },
+        Constraint::Blank(arg) => Value::Bool(match eval(arg, object) {
+            Value::Null => true,
+            Value::String(value) => value.trim().is_empty(),
+            Value::Array(values) => values.is_empty(),
+            Value::Object(values) => values.is_empty(),
+            _ => false,
+        }),
+        Constraint::Not(arg) => Value::Bool(!is_truthy(&eval(arg, object))),
+        Constraint::And(args) => Value::Bool(all(args).iter().all(is_truthy)),
+        Constraint::Or(args) => Value::Bool(all(args).iter().any(is_truthy)),
+        Constraint::Xor(args) => {
+            Value::Bool(all(args).iter().filter(|v| is_truthy(v)).count() == 1)
+        }
+        Constraint::Eq(args) => {
+            Value::Bool(all(args).windows(2).all(|pair| equals(&pair[0], &pair[1])))
+        }
+        Constraint::Lt(args) => compare(&all(args), Ordering::is_lt),
+        Constraint::Le(args) => compare(&all(args), Ordering::is_le),
+        Constraint::Gt(args) => compare(&all(args), Ordering::is_gt),
+        Constraint::Ge(args) => compare(&all(args), Ordering::is_ge),
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+/// Numbers are equal regardless of their representation, like `5` and `5.0`.
+fn equals(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Check, that all consecutive values are ordered as expected, like
+/// `(< 1 .age 100)`. Values, which cannot be compared, are never ordered.
+fn compare(values: &[Value], expected: fn(Ordering) -> bool) -> Value {
+    let ordered = values.windows(2).all(|pair| {
+        let ordering = match (&pair[0], &pair[1]) {
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            (a, b) => a
+                .as_f64()
+                .zip(b.as_f64())
+                .and_then(|(a, b)| a.partial_cmp(&b)),
+        };
+        ordering.is_some_and(expected)
+    });
+
+    Value::Bool(ordered)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::ast::canonical::Type;
+    use crate::error::{canonicalize, Error};
+    use crate::value::{check, validate, Mismatch};
+
+    const SPEC: &str = r#"
+data Box<A> {
+    value: A,
+}
+
+enum Shape {
+    Circle { radius: Float64 },
+    Square { length: Float64 },
+}
+"#;
+
+    #[test]
+    fn test_check_reports_path() {
+        let module = crate::compile(None, SPEC).unwrap();
+        let type_ = Type::Ref("Box".into(), vec![Type::Ref("Shape".into(), vec![])]);
+        let value = json!({"value": {"@type": "Circle", "radius": 1.5}});
+        assert_eq!(check(&module, &type_, &value, ""), Ok(()));
+
+        let value = json!({"value": {"@type": "Circle", "radius": "large"}});
+        let expected = Mismatch {
+            path: "/value/radius".to_string(),
+            expected: "Float64".to_string(),
+            message: "Expected Float64, but found a string.".to_string(),
+        };
+        assert_eq!(check(&module, &type_, &value, ""), Err(expected));
+
+        let value = json!({"value": {"@type": "Triangle"}});
+        let result = check(&module, &type_, &value, "").unwrap_err();
+        assert_eq!(result.path, "/value/@type");
+    }
+
+    #[test]
+    fn test_examples_are_converted_and_checked() {
+        let spec = r#"
+#(example {:name "Ada" :tags ("a" :b) :age nil :ratio 0.5})
+data Person {
+    #(example 32)
+    age: Int32?,
+    name: String,
+    tags: List<String>,
+    ratio: Float64,
+}
+
+enum Shape {
+    Circle {
+        #(example 1.5)
+        radius: Float64,
+    },
+}
+"#;
+        let module = crate::compile(None, spec).unwrap();
+        let circle = &module.enums["Shape"].variants[0];
+        assert_eq!(circle.properties[0].examples, vec![json!(1.5)]);
+        let person = &module.records["Person"];
+        assert_eq!(
+            person.examples,
+            vec![json!({"name": "Ada", "tags": ["a", "b"], "age": null, "ratio": 0.5})]
+        );
+        assert_eq!(person.properties[0].examples, vec![json!(32)]);
+
+        let spec = r#"
+data Person {
+    #(example "32")
+    age: Int32?,
+}
+
+enum Shape {
+    Circle {
+        #(example "1.5")
+        radius: Float64,
+    },
+}
+
+service PersonService {
+    #(example {:age unknown})
+    def get(): Person
+}
+"#;
+        let Err(Error::BadCanonicalization(errors)) = crate::compile(None, spec) else {
+            panic!("The examples should be invalid");
+        };
+
+        let messages = errors
+            .iter()
+            .map(|error| match error.annotation() {
+                canonicalize::Annotation::BadExample(_, message) => message.as_str(),
+                _ => panic!("Expected a bad example"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "The example is not a valid `Int32?`. Expected Int32, but found a string.",
+                "The example is not a valid `Float64`. Expected Float64, but found a string.",
+                "The symbol `unknown` has no JSON value. Use a string or keyword instead.",
+            ]
+        );
+        assert!(errors.iter().all(|error| error.code() == "E0203"));
+    }
+
+    #[test]
+    fn test_xor_requires_exactly_one() {
+        let spec = r#"
+#(check (xor .a .b .c))
+data Choice {
+    a: Boolean,
+    b: Boolean,
+    c: Boolean,
+}
+"#;
+        let module = crate::compile(None, spec).unwrap();
+        let type_ = Type::Ref("Choice".into(), vec![]);
+
+        let value = json!({"a": false, "b": true, "c": false});
+        assert_eq!(validate(&module, &type_, &value, ""), vec![]);
+
+        let value = json!({"a": true, "b": true, "c": true});
+        assert_eq!(validate(&module, &type_, &value, "").len(), 1);
+    }
+
+    #[test]
+    fn test_validate_constraints_in_dependency_order() {
+        let spec = r#"
+#(check (< .min .max))
+data Range {
+    #(check (>= .min 0))
+    min: Int32,
+    max: Int32,
+}
+
+data Address {
+    #(check (or (= .country "DE") (= .country "CH")))
+    country: String,
+    #(check (and (= .country "DE") (= (len .zipcode) 5)))
+    zipcode: String,
+    #(check (not (blank .street)))
+    street: String?,
+    ranges: List<Range>,
+}
+"#;
+        let module = crate::compile(None, spec).unwrap();
+        let type_ = Type::Ref("Address".into(), vec![]);
+
+        let value = json!({"country": "DE", "zipcode": "12345", "ranges": [{"min": 1, "max": 2}]});
+        assert_eq!(validate(&module, &type_, &value, ""), vec![]);
+
+        // The zipcode depends on the invalid country, so it is skipped.
+        let value = json!({
+            "country": "FR",
+            "zipcode": "1",
+            "street": " ",
+            "ranges": [{"min": -1, "max": 2}, {"min": 3, "max": 2}],
+        });
+        let violations = validate(&module, &type_, &value, "")
+            .into_iter()
+            .map(|violation| (violation.path, violation.constraint))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            violations,
+            vec![
+                (
+                    "/country".to_string(),
+                    "(or (= .country \"DE\") (= .country \"CH\"))".to_string()
+                ),
+                ("/ranges/0/min".to_string(), "(>= .min 0)".to_string()),
+                ("/ranges/1".to_string(), "(< .min .max)".to_string()),
+                ("/street".to_string(), "(not (blank .street))".to_string()),
+            ]
+        );
+    }
+}
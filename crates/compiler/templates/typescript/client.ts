@@ -1,5 +1,5 @@
 {% if !imports.is_empty() %}
-import { {{imports}} } from './models.ts';
+import { {{imports}} } from '{{models_path}}';
 {% endif %}
 
 /**
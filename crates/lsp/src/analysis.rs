@@ -0,0 +1,787 @@
+//! Editor features based on the [source AST](compiler::ast::source)
+//! of a single wRPC file.
+//!
+//! All positions are converted between the 1-based, inclusive
+//! [`Region`]s of the compiler and the 0-based, exclusive
+//! [`Range`]s of the language server protocol.
+use std::path::PathBuf;
+
+use compiler::ast::source::{Decl, Enum, Method, Module, Name, Parameter, Property, Service, Type};
+use compiler::diagnostic;
+use compiler::format::{format_decl, format_type};
+use compiler::links;
+use compiler::reporting::Region;
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic, DiagnosticSeverity, DocumentSymbol,
+    Documentation, Hover, HoverContents, MarkupContent, MarkupKind, NumberOrString, Position,
+    Range, SymbolKind,
+};
+
+/// The primitive and built-in types of the language and their description.
+const BUILTIN_TYPES: [(&str, &str); 11] = [
+    ("Int32", "A 32-bit integer."),
+    ("Int64", "A 64-bit integer."),
+    ("Float32", "A 32-bit floating point number."),
+    ("Float64", "A 64-bit floating point number."),
+    ("String", "A UTF-8 encoded string."),
+    ("Boolean", "Either `true` or `false`."),
+    ("List", "`List<A>` is a sequence of values of type `A`."),
+    ("Set", "`Set<A>` is an unordered set of values of type `A`."),
+    (
+        "Map",
+        "`Map<K, V>` is a set of unordered key value pairs. On the wire, it is a list of pairs.",
+    ),
+    (
+        "Option",
+        "`Option<A>` is either a value of type `A` or `null`. It can also be written as `A?`.",
+    ),
+    (
+        "Result",
+        "`Result<E, A>` is either a successful value `Ok { value: A }` or an error `Err { error: E }`.",
+    ),
+];
+
+/// The symbols, that can be used in annotations and their description.
+const ANNOTATION_SYMBOLS: [(&str, &str); 15] = [
+    (
+        "check",
+        "Declares constraints, that a value has to satisfy.",
+    ),
+    ("example", "Declares an example value in edn."),
+    (
+        "deprecated",
+        "Marks an element as deprecated, with an optional note.",
+    ),
+    (
+        "mock",
+        "Lets the mock server store a record, like `#(mock :crud Person)`.",
+    ),
+    ("not", "Negates the given constraint."),
+    ("blank", "Checks, whether the given string is empty."),
+    (
+        "len",
+        "Returns the length of the given string or collection.",
+    ),
+    (
+        "and",
+        "Checks, whether all given constraints are satisfied.",
+    ),
+    (
+        "or",
+        "Checks, whether at least one of the given constraints is satisfied.",
+    ),
+    ("=", "Checks, whether all given values are equal."),
+    ("<", "Checks, whether each value is less than the next one."),
+    (
+        "<=",
+        "Checks, whether each value is less than or equal to the next one.",
+    ),
+    (
+        ">",
+        "Checks, whether each value is greater than the next one.",
+    ),
+    (
+        ">=",
+        "Checks, whether each value is greater than or equal to the next one.",
+    ),
+    (
+        "xor",
+        "Checks, whether exactly one of the given constraints is satisfied.",
+    ),
+];
+
+/// Something with a name in a wRPC file.
+#[derive(Debug)]
+pub enum Symbol<'a> {
+    Decl(&'a Decl),
+    Property(&'a Property),
+    Variant(&'a Enum, &'a Name),
+    Method(&'a Service, &'a Method),
+    Parameter(&'a Parameter),
+    Type(&'a Name),
+}
+
+/// Returns the [`Symbol`] at the given position, if there is any.
+///
+/// Unlike the other functions of this module, the position counts
+/// characters, like the regions of the compiler.
+pub fn symbol_at<'a>(module: &'a Module, position: &Position) -> Option<Symbol<'a>> {
+    for decl in &module.declarations {
+        match decl {
+            Decl::Data(data) => {
+                if contains(&data.name.region, position) {
+                    return Some(Symbol::Decl(decl));
+                }
+                if let Some(symbol) = property_at(&data.properties, position) {
+                    return Some(symbol);
+                }
+            }
+            Decl::Enum(enum_) => {
+                if contains(&enum_.name.region, position) {
+                    return Some(Symbol::Decl(decl));
+                }
+                for variant in &enum_.variants {
+                    if contains(&variant.name.region, position) {
+                        return Some(Symbol::Variant(enum_, &variant.name));
+                    }
+                    if let Some(symbol) = property_at(&variant.properties, position) {
+                        return Some(symbol);
+                    }
+                }
+            }
+            Decl::Service(service) => {
+                if contains(&service.name.region, position) {
+                    return Some(Symbol::Decl(decl));
+                }
+                for method in &service.methods {
+                    if contains(&method.name.region, position) {
+                        return Some(Symbol::Method(service, method));
+                    }
+                    for parameter in &method.parameters {
+                        if contains(&parameter.name.region, position) {
+                            return Some(Symbol::Parameter(parameter));
+                        }
+                        if let Some(name) = type_at(&parameter.type_, position) {
+                            return Some(Symbol::Type(name));
+                        }
+                    }
+                    if let Some(name) = method
+                        .return_type
+                        .as_ref()
+                        .and_then(|type_| type_at(type_, position))
+                    {
+                        return Some(Symbol::Type(name));
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn property_at<'a>(properties: &'a [Property], position: &Position) -> Option<Symbol<'a>> {
+    for property in properties {
+        if contains(&property.name.region, position) {
+            return Some(Symbol::Property(property));
+        }
+        if let Some(name) = type_at(&property.type_, position) {
+            return Some(Symbol::Type(name));
+        }
+    }
+
+    None
+}
+
+fn type_at<'a>(type_: &'a Type, position: &Position) -> Option<&'a Name> {
+    if contains(&type_.name.region, position) {
+        return Some(&type_.name);
+    }
+
+    type_
+        .variables
+        .iter()
+        .find_map(|variable| type_at(variable, position))
+}
+
+/// Returns the declaration with the given name.
+pub fn find_decl<'a>(module: &'a Module, name: &str) -> Option<&'a Decl> {
+    module
+        .declarations
+        .iter()
+        .find(|decl| decl_name(decl).value == name)
+}
+
+fn decl_name(decl: &Decl) -> &Name {
+    match decl {
+        Decl::Data(data) => &data.name,
+        Decl::Enum(enum_) => &enum_.name,
+        Decl::Service(service) => &service.name,
+    }
+}
+
+pub fn hover(module: &Module, text: &str, position: &Position) -> Option<Hover> {
+    let position = from_utf16(text, position);
+    let value = match symbol_at(module, &position)? {
+        Symbol::Decl(decl) => describe_decl(decl),
+        Symbol::Type(name) => match builtin_type(&name.value) {
+            Some(description) => format!("{}\n\n{description}", code(&name.value)),
+            None => describe_decl(find_decl(module, &name.value)?),
+        },
+        Symbol::Property(property) => describe_member(
+            module,
+            format!("{}: {}", property.name.value, format_type(&property.type_)),
+            &property.doc_comment,
+            Some(&property.type_),
+        ),
+        Symbol::Parameter(parameter) => describe_member(
+            module,
+            format!(
+                "{}: {}",
+                parameter.name.value,
+                format_type(&parameter.type_)
+            ),
+            &parameter.doc_comment,
+            Some(&parameter.type_),
+        ),
+        Symbol::Variant(enum_, name) => {
+            let variant = enum_.variants.iter().find(|v| v.name.value == name.value)?;
+            describe_member(
+                module,
+                format!("{}.{}", enum_.name.value, name.value),
+                &variant.doc_comment,
+                None,
+            )
+        }
+        Symbol::Method(service, method) => describe_member(
+            module,
+            format!("{}.{}", service.name.value, method_signature(method)),
+            &method.doc_comment,
+            method.return_type.as_ref(),
+        ),
+    };
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: None,
+    })
+}
+
+fn describe_decl(decl: &Decl) -> String {
+    let mut decl = decl.clone();
+    let comment = match &mut decl {
+        Decl::Data(data) => data.doc_comment.take(),
+        Decl::Enum(enum_) => enum_.doc_comment.take(),
+        Decl::Service(service) => service.doc_comment.take(),
+    };
+
+    with_comment(code(&format_decl(&decl)), &comment)
+}
+
+/// Describe a member of a declaration, including the definitions
+/// of all types its `type_` refers to.
+fn describe_member(
+    module: &Module,
+    signature: String,
+    comment: &Option<String>,
+    type_: Option<&Type>,
+) -> String {
+    let mut value = with_comment(code(&signature), comment);
+    let mut names = vec![];
+    if let Some(type_) = type_ {
+        collect_type_names(type_, &mut names);
+    }
+
+    let definitions = names
+        .iter()
+        .filter_map(|name| find_decl(module, name))
+        .map(|decl| {
+            let mut decl = decl.clone();
+            match &mut decl {
+                Decl::Data(data) => data.doc_comment = None,
+                Decl::Enum(enum_) => enum_.doc_comment = None,
+                Decl::Service(service) => service.doc_comment = None,
+            }
+            format_decl(&decl)
+        })
+        .collect::<Vec<String>>();
+
+    if !definitions.is_empty() {
+        value = format!("{value}\n\n---\n\n{}", code(&definitions.join("\n\n")));
+    }
+
+    value
+}
+
+fn collect_type_names(type_: &Type, names: &mut Vec<String>) {
+    if builtin_type(&type_.name.value).is_none() && !names.contains(&type_.name.value) {
+        names.push(type_.name.value.clone());
+    }
+
+    for variable in &type_.variables {
+        collect_type_names(variable, names);
+    }
+}
+
+fn method_signature(method: &Method) -> String {
+    let parameters = method
+        .parameters
+        .iter()
+        .map(|param| format!("{}: {}", param.name.value, format_type(&param.type_)))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    let return_type = method
+        .return_type
+        .as_ref()
+        .map(|type_| format!(": {}", format_type(type_)))
+        .unwrap_or_default();
+
+    format!("{}({parameters}){return_type}", method.name.value)
+}
+
+fn builtin_type(name: &str) -> Option<&'static str> {
+    BUILTIN_TYPES
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(_, description)| *description)
+}
+
+fn code(value: &str) -> String {
+    format!("```wrpc\n{value}\n```")
+}
+
+fn with_comment(value: String, comment: &Option<String>) -> String {
+    match comment {
+        Some(comment) => format!("{value}\n\n{comment}"),
+        None => value,
+    }
+}
+
+/// Returns the range of the declaration, the symbol or doc comment
+/// link at the given position refers to.
+pub fn definition(module: &Module, text: &str, position: &Position) -> Option<Range> {
+    let position = from_utf16(text, position);
+    let name = match symbol_at(module, &position) {
+        Some(Symbol::Type(name)) => name.value.clone(),
+        Some(_) => return None,
+        None => {
+            let line = text.lines().nth(position.line as usize)?;
+            if !line.trim_start().starts_with("//") {
+                return None;
+            }
+
+            let character = position.character as usize;
+            doc_links(line)
+                .into_iter()
+                .find(|link| link.start <= character && character < link.end)?
+                .target
+        }
+    };
+
+    // Links to methods look like `[get](#PersonService.get)`.
+    if let Some((service, method)) = name.split_once('.') {
+        let Some(Decl::Service(service)) = find_decl(module, service) else {
+            return None;
+        };
+        let method = service
+            .methods
+            .iter()
+            .find(|candidate| candidate.name.value == method)?;
+        return Some(to_range(text, &method.name.region));
+    }
+
+    find_decl(module, &name).map(|decl| to_range(text, &decl_name(decl).region))
+}
+
+/// A markdown link in a doc comment, like `[Club](#Club)` or `[Club]`.
+#[derive(Debug, PartialEq)]
+pub struct DocLink {
+    /// The character index, where the link starts.
+    pub start: usize,
+    /// The character index after the end of the link.
+    pub end: usize,
+    pub target: String,
+}
+
+/// Returns all links to other declarations in the given line, see
+/// [`compiler::links`].
+pub fn doc_links(line: &str) -> Vec<DocLink> {
+    let character = |byte: usize| line[..byte].chars().count();
+    links::parse(line)
+        .into_iter()
+        .map(|link| DocLink {
+            start: character(link.range.start),
+            end: character(link.range.end),
+            target: link.target,
+        })
+        .collect()
+}
+
+/// Returns the completions at the given position.
+///
+/// Inside of an annotation, these are the symbols of the annotation
+/// language and the properties of the surrounding declaration,
+/// otherwise all known types.
+pub fn completion(module: &Module, text: &str, position: &Position) -> Vec<CompletionItem> {
+    let position = &from_utf16(text, position);
+    let prefix = text_before(text, position);
+    if is_in_annotation(&prefix) {
+        let mut items = ANNOTATION_SYMBOLS
+            .iter()
+            .map(|(symbol, description)| CompletionItem {
+                label: symbol.to_string(),
+                kind: Some(CompletionItemKind::FUNCTION),
+                detail: Some(description.to_string()),
+                ..CompletionItem::default()
+            })
+            .collect::<Vec<CompletionItem>>();
+
+        if let Some(properties) = surrounding_properties(module, position) {
+            items.extend(properties.iter().map(|property| CompletionItem {
+                label: format!(".{}", property.name.value),
+                kind: Some(CompletionItemKind::FIELD),
+                detail: Some(format_type(&property.type_)),
+                ..CompletionItem::default()
+            }));
+        }
+
+        return items;
+    }
+
+    let builtins = BUILTIN_TYPES
+        .iter()
+        .map(|(name, description)| CompletionItem {
+            label: name.to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            detail: Some(description.to_string()),
+            ..CompletionItem::default()
+        });
+
+    let declared = module
+        .declarations
+        .iter()
+        .filter_map(|decl| match decl {
+            Decl::Data(data) => Some((&data.name, &data.doc_comment, CompletionItemKind::STRUCT)),
+            Decl::Enum(enum_) => Some((&enum_.name, &enum_.doc_comment, CompletionItemKind::ENUM)),
+            Decl::Service(_) => None,
+        })
+        .map(|(name, comment, kind)| CompletionItem {
+            label: name.value.clone(),
+            kind: Some(kind),
+            documentation: comment.as_ref().map(|comment| {
+                Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: comment.clone(),
+                })
+            }),
+            ..CompletionItem::default()
+        });
+
+    builtins.chain(declared).collect()
+}
+
+fn text_before(text: &str, position: &Position) -> String {
+    let mut result = vec![];
+    for (index, line) in text.lines().enumerate() {
+        if index == position.line as usize {
+            result.push(line.chars().take(position.character as usize).collect());
+            break;
+        }
+        result.push(line.to_string());
+    }
+
+    result.join("\n")
+}
+
+fn is_in_annotation(prefix: &str) -> bool {
+    match prefix.rfind("#(") {
+        Some(start) => {
+            let rest = &prefix[start..];
+            rest.matches('(').count() > rest.matches(')').count()
+        }
+        None => false,
+    }
+}
+
+/// Returns the properties of the data declaration, that most
+/// likely surrounds the given position.
+fn surrounding_properties<'a>(module: &'a Module, position: &Position) -> Option<&'a [Property]> {
+    let line = position.line as usize + 1;
+    module
+        .declarations
+        .iter()
+        .filter_map(|decl| match decl {
+            Decl::Data(data) if data.name.region.start.line <= line => Some(data),
+            _ => None,
+        })
+        .max_by_key(|data| data.name.region.start.line)
+        .map(|data| data.properties.as_slice())
+}
+
+#[allow(deprecated)]
+pub fn document_symbols(module: &Module, text: &str) -> Vec<DocumentSymbol> {
+    fn symbol(
+        text: &str,
+        name: &Name,
+        detail: Option<String>,
+        kind: SymbolKind,
+        children: Vec<DocumentSymbol>,
+    ) -> DocumentSymbol {
+        DocumentSymbol {
+            name: name.value.clone(),
+            detail,
+            kind,
+            tags: None,
+            deprecated: None,
+            range: to_range(text, &name.region),
+            selection_range: to_range(text, &name.region),
+            children: if children.is_empty() {
+                None
+            } else {
+                Some(children)
+            },
+        }
+    }
+
+    fn properties(text: &str, properties: &[Property]) -> Vec<DocumentSymbol> {
+        properties
+            .iter()
+            .map(|property| {
+                let detail = Some(format_type(&property.type_));
+                symbol(text, &property.name, detail, SymbolKind::FIELD, vec![])
+            })
+            .collect()
+    }
+
+    module
+        .declarations
+        .iter()
+        .map(|decl| match decl {
+            Decl::Data(data) => symbol(
+                text,
+                &data.name,
+                None,
+                SymbolKind::STRUCT,
+                properties(text, &data.properties),
+            ),
+            Decl::Enum(enum_) => {
+                let variants = enum_
+                    .variants
+                    .iter()
+                    .map(|variant| {
+                        let children = properties(text, &variant.properties);
+                        symbol(text, &variant.name, None, SymbolKind::ENUM_MEMBER, children)
+                    })
+                    .collect();
+                symbol(text, &enum_.name, None, SymbolKind::ENUM, variants)
+            }
+            Decl::Service(service) => {
+                let methods = service
+                    .methods
+                    .iter()
+                    .map(|method| {
+                        let detail = Some(method_signature(method));
+                        symbol(text, &method.name, detail, SymbolKind::METHOD, vec![])
+                    })
+                    .collect();
+                symbol(text, &service.name, None, SymbolKind::INTERFACE, methods)
+            }
+        })
+        .collect()
+}
+
+/// Compile the given text and return its errors and warnings.
+pub fn diagnostics(path: Option<PathBuf>, text: &str) -> Vec<Diagnostic> {
+    let diagnostics = match compiler::compile_with_warnings(path, text) {
+        Ok((_, warnings)) => diagnostic::from_warnings(&warnings),
+        Err(error) => diagnostic::from_error(&error),
+    };
+    diagnostics
+        .into_iter()
+        .map(|diagnostic| Diagnostic {
+            range: to_range(text, &diagnostic.region),
+            severity: Some(match diagnostic.severity {
+                diagnostic::Severity::Error => DiagnosticSeverity::ERROR,
+                diagnostic::Severity::Warning => DiagnosticSeverity::WARNING,
+            }),
+            code: Some(NumberOrString::String(diagnostic.code.to_string())),
+            source: Some("wrpc".to_string()),
+            message: format!("{}\n\n{}", diagnostic.title, diagnostic.message),
+            ..Diagnostic::default()
+        })
+        .collect()
+}
+
+/// Convert a [`Region`] of the compiler into a [`Range`] of the
+/// given text.
+///
+/// The compiler counts characters, while positions of the language
+/// server protocol count UTF-16 code units.
+pub fn to_range(text: &str, region: &Region) -> Range {
+    let position = |line: usize, col: usize| {
+        let line = line.saturating_sub(1);
+        let character = text
+            .lines()
+            .nth(line)
+            .unwrap_or_default()
+            .chars()
+            .take(col)
+            .map(char::len_utf16)
+            .sum::<usize>();
+        Position::new(line as u32, character as u32)
+    };
+
+    Range::new(
+        position(region.start.line, region.start.col.saturating_sub(1)),
+        position(region.end.line, region.end.col),
+    )
+}
+
+/// Convert a [`Position`] of the client, counting UTF-16 code units,
+/// into one counting characters.
+fn from_utf16(text: &str, position: &Position) -> Position {
+    let line = text.lines().nth(position.line as usize).unwrap_or_default();
+    let mut units = 0;
+    let character = line
+        .chars()
+        .take_while(|c| {
+            units += c.len_utf16();
+            units <= position.character as usize
+        })
+        .count();
+
+    Position::new(position.line, character as u32)
+}
+
+/// Check, whether the given position is inside of the region. A
+/// position directly after the region still counts, since that
+/// is where the cursor is after typing a name.
+fn contains(region: &Region, position: &Position) -> bool {
+    let line = position.line as usize + 1;
+    let col = position.character as usize + 1;
+    region.start.line <= line
+        && line <= region.end.line
+        && (line != region.start.line || region.start.col <= col)
+        && (line != region.end.line || col <= region.end.col + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"// An *Association* is an organisation.
+data Association {
+    id: Int64,
+    name: String,
+}
+
+// A *Club* belongs to an [Association](#Association).
+data Club {
+    #(check (not (blank .name)))
+    name: String,
+    association: Association?,
+}
+
+service ClubService {
+    def get(id: Int64): Club?
+}
+"#;
+
+    fn module() -> Module {
+        compiler::parse(None, SOURCE).expect("Source should parse")
+    }
+
+    #[test]
+    fn test_hover_on_type_reference_shows_definition_and_comment() {
+        let module = module();
+        let hover = hover(&module, SOURCE, &Position::new(10, 18)).expect("Hover");
+        let HoverContents::Markup(content) = hover.contents else {
+            panic!("Expected markup");
+        };
+        assert!(content.value.contains("data Association {"));
+        assert!(content
+            .value
+            .contains("An *Association* is an organisation."));
+    }
+
+    #[test]
+    fn test_definition_of_type_reference() {
+        let module = module();
+        let range = definition(&module, SOURCE, &Position::new(14, 25)).expect("Definition");
+        assert_eq!(range, Range::new(Position::new(7, 5), Position::new(7, 9)));
+    }
+
+    #[test]
+    fn test_definition_of_doc_link() {
+        let module = module();
+        let range = definition(&module, SOURCE, &Position::new(6, 30)).expect("Definition");
+        assert_eq!(range, Range::new(Position::new(1, 5), Position::new(1, 16)));
+    }
+
+    #[test]
+    fn test_definition_of_method_link() {
+        let module = module();
+        let text = format!("{SOURCE}// Uses [get](#ClubService.get).");
+        let range = definition(&module, &text, &Position::new(16, 10)).expect("Definition");
+        assert_eq!(
+            range,
+            Range::new(Position::new(14, 8), Position::new(14, 11))
+        );
+    }
+
+    #[test]
+    fn test_positions_count_utf16_code_units() {
+        let module = module();
+        let text = format!("{SOURCE}// 😀😀😀😀 [Club]");
+        let range = definition(&module, &text, &Position::new(16, 16)).expect("Definition");
+        assert_eq!(range, Range::new(Position::new(7, 5), Position::new(7, 9)));
+
+        let range = to_range("a😀b", &Region::new(1, 3, 1, 3));
+        assert_eq!(range, Range::new(Position::new(0, 3), Position::new(0, 4)));
+    }
+
+    #[test]
+    fn test_diagnostics_of_unfinished_constraints() {
+        for (constraint, code) in [
+            ("(foo .name)", "E0200"),
+            ("(not)", "E0205"),
+            ("()", "E0201"),
+        ] {
+            let text = SOURCE.replace("(not (blank .name))", constraint);
+            let diagnostics = diagnostics(None, &text);
+            assert_eq!(diagnostics.len(), 1, "{constraint}");
+            assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+            let code = Some(NumberOrString::String(code.to_string()));
+            assert_eq!(diagnostics[0].code, code, "{constraint}");
+        }
+    }
+
+    #[test]
+    fn test_doc_links() {
+        let links = doc_links("See [Club](#Club), [SessionService] and [docs](https://x.y).");
+        assert_eq!(
+            links,
+            vec![
+                DocLink {
+                    start: 4,
+                    end: 17,
+                    target: "Club".to_string()
+                },
+                DocLink {
+                    start: 19,
+                    end: 35,
+                    target: "SessionService".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_completion_in_annotation_contains_properties() {
+        let module = module();
+        let items = completion(&module, SOURCE, &Position::new(8, 22));
+        let labels = items
+            .iter()
+            .map(|item| item.label.as_str())
+            .collect::<Vec<_>>();
+        assert!(labels.contains(&"blank"));
+        assert!(labels.contains(&".association"));
+    }
+
+    #[test]
+    fn test_completion_of_types() {
+        let module = module();
+        let items = completion(&module, SOURCE, &Position::new(3, 10));
+        let labels = items
+            .iter()
+            .map(|item| item.label.as_str())
+            .collect::<Vec<_>>();
+        assert!(labels.contains(&"Int64"));
+        assert!(labels.contains(&"Association"));
+        assert!(!labels.contains(&"ClubService"));
+    }
+}
@@ -0,0 +1,177 @@
+//! A language server for wRPC files, communicating over stdio.
+//!
+//! The server keeps the text of every open document and re-compiles
+//! it on every change. Diagnostics come from the compiler, all other
+//! features work on the last [`Module`] that could be parsed, so
+//! that hover and completion keep working while typing.
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use compiler::ast::source::Module;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{
+    CompletionOptions, CompletionParams, CompletionResponse, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, DocumentSymbolParams,
+    DocumentSymbolResponse, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverParams,
+    HoverProviderCapability, InitializeParams, InitializeResult, InitializedParams, Location,
+    MessageType, OneOf, ServerCapabilities, ServerInfo, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url,
+};
+use tower_lsp::{Client, LanguageServer, LspService, Server};
+
+pub mod analysis;
+
+#[derive(Debug, Default)]
+struct Document {
+    text: String,
+    /// The last version of the document, that could be parsed.
+    module: Option<Module>,
+}
+
+#[derive(Debug)]
+struct Backend {
+    client: Client,
+    documents: RwLock<HashMap<Url, Document>>,
+}
+
+impl Backend {
+    async fn update(&self, uri: Url, text: String) {
+        let path = uri.to_file_path().ok();
+        let diagnostics = analysis::diagnostics(path.clone(), &text);
+
+        {
+            let mut documents = self.documents.write().unwrap();
+            let document = documents.entry(uri.clone()).or_default();
+            if let Ok(module) = compiler::parse(path, &text) {
+                document.module = Some(module);
+            }
+            document.text = text;
+        }
+
+        self.client
+            .publish_diagnostics(uri, diagnostics, None)
+            .await;
+    }
+
+    /// Run the given function with the document at the given uri,
+    /// if it could be parsed at least once.
+    fn with_document<T>(&self, uri: &Url, f: impl FnOnce(&Module, &str) -> T) -> Option<T> {
+        let documents = self.documents.read().unwrap();
+        let document = documents.get(uri)?;
+        document
+            .module
+            .as_ref()
+            .map(|module| f(module, &document.text))
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Backend {
+    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+        Ok(InitializeResult {
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                    TextDocumentSyncKind::FULL,
+                )),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                definition_provider: Some(OneOf::Left(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(
+                        [".", "(", ":", "<"].iter().map(|c| c.to_string()).collect(),
+                    ),
+                    ..CompletionOptions::default()
+                }),
+                ..ServerCapabilities::default()
+            },
+            server_info: Some(ServerInfo {
+                name: "wrpc".to_string(),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+        })
+    }
+
+    async fn initialized(&self, _: InitializedParams) {
+        self.client
+            .log_message(MessageType::INFO, "wRPC language server initialized")
+            .await;
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        self.update(params.text_document.uri, params.text_document.text)
+            .await;
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        // With full synchronization, the last change contains the whole document.
+        if let Some(change) = params.content_changes.into_iter().last() {
+            self.update(params.text_document.uri, change.text).await;
+        }
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        self.documents.write().unwrap().remove(&uri);
+        self.client.publish_diagnostics(uri, vec![], None).await;
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let position = params.text_document_position_params;
+        Ok(self
+            .with_document(&position.text_document.uri, |module, text| {
+                analysis::hover(module, text, &position.position)
+            })
+            .flatten())
+    }
+
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        let position = params.text_document_position_params;
+        let uri = position.text_document.uri;
+        Ok(self
+            .with_document(&uri, |module, text| {
+                analysis::definition(module, text, &position.position)
+            })
+            .flatten()
+            .map(|range| GotoDefinitionResponse::Scalar(Location::new(uri.clone(), range))))
+    }
+
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let position = params.text_document_position;
+        Ok(self
+            .with_document(&position.text_document.uri, |module, text| {
+                analysis::completion(module, text, &position.position)
+            })
+            .map(CompletionResponse::Array))
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        Ok(self
+            .with_document(&params.text_document.uri, |module, text| {
+                analysis::document_symbols(module, text)
+            })
+            .map(DocumentSymbolResponse::Nested))
+    }
+}
+
+/// Run the language server on stdin and stdout until the client
+/// shuts it down.
+pub async fn run() {
+    let stdin = tokio::io::stdin();
+    let stdout = tokio::io::stdout();
+
+    let (service, socket) = LspService::new(|client| Backend {
+        client,
+        documents: RwLock::new(HashMap::new()),
+    });
+    Server::new(stdin, stdout, socket).serve(service).await;
+}
@@ -0,0 +1,408 @@
+//! Let the mock server misbehave on purpose, e.g. to test loading
+//! states and error handling of a frontend.
+//!
+//! A [`Fault`] describes the latency of responses and how often a
+//! call fails, answers with a malformed body or never answers at all.
+//! It applies to all methods or only to a single one, see [`Chaos`].
+//! The documentation and the routes below `/_wrpc` are never affected.
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::hash::{BuildHasher, RandomState};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use axum::body::{self, Body};
+use axum::extract::Request;
+use axum::http::{header, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use tower::{Layer, Service};
+
+/// The faults of all methods, which can be changed while the server
+/// is running.
+pub type Shared = Arc<RwLock<Chaos>>;
+
+/// The faults of the mock server.
+///
+/// ```json
+/// {
+///     "global": { "latency": { "min": 100, "max": 2000 } },
+///     "methods": {
+///         "PersonService/get": { "error_rate": 0.5, "error_status": 503 }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Chaos {
+    /// The fault of every method without its own.
+    pub global: Fault,
+    /// The faults of single methods, like `PersonService/get`, which
+    /// replace the global one.
+    pub methods: BTreeMap<String, Fault>,
+}
+
+/// How a method misbehaves. All rates are between 0 and 1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Fault {
+    /// The delay before every response.
+    pub latency: Option<Latency>,
+    /// How often a call is answered with the `error_status`.
+    pub error_rate: f64,
+    pub error_status: u16,
+    /// How often a call is answered with a body, that is cut off.
+    pub malformed_rate: f64,
+    /// How often a call is never answered.
+    pub timeout_rate: f64,
+}
+
+/// A latency between `min` and `max` milliseconds, where every value
+/// is equally likely.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Latency {
+    pub min: u64,
+    pub max: u64,
+}
+
+/// What happens to a single call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Outcome {
+    Answer,
+    Error(StatusCode),
+    Malformed,
+    Timeout,
+}
+
+impl Default for Fault {
+    fn default() -> Self {
+        Fault {
+            latency: None,
+            error_rate: 0.0,
+            error_status: 500,
+            malformed_rate: 0.0,
+            timeout_rate: 0.0,
+        }
+    }
+}
+
+impl Chaos {
+    /// Returns the fault of the method at the given path, like
+    /// `PersonService/get`.
+    fn fault(&self, path: &str) -> &Fault {
+        self.methods.get(path).unwrap_or(&self.global)
+    }
+
+    /// Check, that all rates, latencies and status codes are valid.
+    pub fn validate(&self) -> Result<(), String> {
+        self.global
+            .validate()
+            .map_err(|message| format!("global: {message}"))?;
+        for (method, fault) in &self.methods {
+            if method.split('/').count() != 2 {
+                return Err(format!("`{method}` should have the form Service/method"));
+            }
+            fault
+                .validate()
+                .map_err(|message| format!("{method}: {message}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Fault {
+    fn validate(&self) -> Result<(), String> {
+        let rates = [
+            ("error_rate", self.error_rate),
+            ("malformed_rate", self.malformed_rate),
+            ("timeout_rate", self.timeout_rate),
+        ];
+        for (name, rate) in rates {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(format!("`{name}` should be between 0 and 1, but is {rate}"));
+            }
+        }
+
+        if self.error_rate + self.malformed_rate + self.timeout_rate > 1.0 {
+            return Err("the rates should add up to at most 1".to_string());
+        }
+
+        match StatusCode::from_u16(self.error_status) {
+            Ok(status) if status.is_client_error() || status.is_server_error() => {}
+            _ => {
+                let status = self.error_status;
+                return Err(format!("`{status}` is not an error status code"));
+            }
+        }
+
+        match self.latency {
+            Some(Latency { min, max }) if min > max => Err(format!(
+                "the latency {min}..{max} should not end before it starts"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn delay(&self) -> Option<Duration> {
+        let Latency { min, max } = self.latency?;
+        let millis = min + (random() * (max - min + 1) as f64) as u64;
+        Some(Duration::from_millis(millis.min(max)))
+    }
+
+    fn outcome(&self) -> Outcome {
+        let value = random();
+        if value < self.timeout_rate {
+            Outcome::Timeout
+        } else if value < self.timeout_rate + self.error_rate {
+            // The status has already been validated.
+            let status = StatusCode::from_u16(self.error_status);
+            Outcome::Error(status.unwrap_or(StatusCode::INTERNAL_SERVER_ERROR))
+        } else if value < self.timeout_rate + self.error_rate + self.malformed_rate {
+            Outcome::Malformed
+        } else {
+            Outcome::Answer
+        }
+    }
+}
+
+/// Parses a latency in milliseconds like `200` or `100..500`.
+impl FromStr for Latency {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let parse = |value: &str| {
+            value
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("`{value}` should be milliseconds, like `200` or `100..500`"))
+        };
+
+        match value.split_once("..") {
+            Some((min, max)) => Ok(Latency {
+                min: parse(min)?,
+                max: parse(max)?,
+            }),
+            None => {
+                let latency = parse(value)?;
+                Ok(Latency {
+                    min: latency,
+                    max: latency,
+                })
+            }
+        }
+    }
+}
+
+/// A random number in `0.0..1.0`. Faults do not need to be
+/// reproducible, so the random keys of the standard library suffice.
+fn random() -> f64 {
+    let value = RandomState::new().hash_one(());
+    (value >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// A [`Layer`] applying the faults of a [`Chaos`] to all calls.
+#[derive(Debug, Clone)]
+pub struct ChaosLayer {
+    chaos: Shared,
+}
+
+impl ChaosLayer {
+    pub fn new(chaos: Shared) -> Self {
+        ChaosLayer { chaos }
+    }
+}
+
+impl<S> Layer<S> for ChaosLayer {
+    type Service = ChaosService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ChaosService {
+            inner,
+            chaos: self.chaos.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChaosService<S> {
+    inner: S,
+    chaos: Shared,
+}
+
+impl<S> Service<Request> for ChaosService<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let path = request.uri().path().trim_start_matches('/');
+        let fault = match request.method() == Method::POST && !path.starts_with("_wrpc/") {
+            true => {
+                let chaos = self.chaos.read().unwrap_or_else(|error| error.into_inner());
+                Some(chaos.fault(path).clone())
+            }
+            false => None,
+        };
+
+        // The inner service is ready, but its clone may not be.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let Some(fault) = fault else {
+                return inner.call(request).await;
+            };
+
+            if let Some(delay) = fault.delay() {
+                tokio::time::sleep(delay).await;
+            }
+
+            match fault.outcome() {
+                Outcome::Answer => inner.call(request).await,
+                Outcome::Timeout => std::future::pending().await,
+                Outcome::Error(status) => {
+                    let message = "This error was injected by the mock server.";
+                    Ok((status, message).into_response())
+                }
+                Outcome::Malformed => {
+                    let response = inner.call(request).await?;
+                    Ok(malformed(response).await)
+                }
+            }
+        })
+    }
+}
+
+/// Cut off the body of the response in the middle.
+async fn malformed(response: Response) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let bytes = body::to_bytes(body, usize::MAX).await.unwrap_or_default();
+    // Even an empty body should not be valid JSON.
+    let cut = match bytes.len() {
+        0 => body::Bytes::from_static(b"{"),
+        len => bytes.slice(..len / 2),
+    };
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(cut))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, RwLock};
+    use std::time::{Duration, Instant};
+
+    use axum::body::{self, Body};
+    use axum::http::{Request, StatusCode};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    use crate::chaos::{Chaos, ChaosLayer, Fault, Latency};
+
+    fn app(chaos: Chaos) -> Router {
+        Router::new()
+            .route("/", get(|| async { "docs" }))
+            .route(
+                "/:service/:method",
+                post(|| async { Json(json!({"id": 1})) }),
+            )
+            .layer(ChaosLayer::new(Arc::new(RwLock::new(chaos))))
+    }
+
+    async fn call(app: Router, method: &str, uri: &str) -> (StatusCode, Vec<u8>) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = body::to_bytes(response.into_body(), usize::MAX).await;
+        (status, body.unwrap().to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_method_faults_replace_the_global_one() {
+        let fault = Fault {
+            error_rate: 1.0,
+            error_status: 503,
+            ..Fault::default()
+        };
+        let chaos = Chaos {
+            global: Fault::default(),
+            methods: BTreeMap::from([("PersonService/get".to_string(), fault)]),
+        };
+
+        let (status, _) = call(app(chaos.clone()), "POST", "/PersonService/get").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+        let (status, body) = call(app(chaos), "POST", "/PersonService/insert").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).unwrap(),
+            json!({"id": 1})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_malformed_bodies_and_latency() {
+        let chaos = Chaos {
+            global: Fault {
+                latency: Some(Latency { min: 20, max: 40 }),
+                malformed_rate: 1.0,
+                ..Fault::default()
+            },
+            methods: BTreeMap::new(),
+        };
+
+        let start = Instant::now();
+        let (status, body) = call(app(chaos.clone()), "POST", "/PersonService/get").await;
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert_eq!(status, StatusCode::OK);
+        assert!(serde_json::from_slice::<Value>(&body).is_err());
+
+        // The documentation is never affected.
+        let (status, body) = call(app(chaos), "GET", "/").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, b"docs");
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut chaos = Chaos::default();
+        assert_eq!(chaos.validate(), Ok(()));
+
+        chaos.global.error_rate = 0.7;
+        chaos.global.timeout_rate = 0.7;
+        assert!(chaos.validate().is_err());
+
+        chaos.global.timeout_rate = 0.0;
+        chaos.global.error_status = 200;
+        assert_eq!(
+            chaos.validate(),
+            Err("global: `200` is not an error status code".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_latency() {
+        assert_eq!("200".parse(), Ok(Latency { min: 200, max: 200 }));
+        assert_eq!("100..500".parse(), Ok(Latency { min: 100, max: 500 }));
+        assert!("fast".parse::<Latency>().is_err());
+    }
+}
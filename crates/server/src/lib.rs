@@ -1,27 +1,147 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    io,
+    path::PathBuf,
+    process,
+    sync::{Arc, Mutex, RwLock},
+};
 
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::{Html, IntoResponse},
-    routing::get,
-    Router,
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderValue, Method as HttpMethod, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
 };
-use compiler::docs::render;
-use compiler::print_errors;
+use chaos::{Chaos, ChaosLayer};
+use compiler::ast::canonical::Module;
+use compiler::diagnostic::{self, MessageFormat};
+use compiler::docs;
+use compiler::value::Violation;
+use recording::Recordings;
+use serde_json::{json, Value};
+use store::Store;
 use tokio::net::TcpListener;
-use tower_http::{compression::CompressionLayer, trace::TraceLayer};
+use tokio::sync::broadcast;
+use tower_http::{
+    compression::CompressionLayer,
+    cors::{AllowOrigin, Any, CorsLayer},
+    trace::TraceLayer,
+};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+pub mod chaos;
+pub mod mock;
+pub mod proxy;
+pub mod recording;
+mod reload;
+pub mod store;
+
+/// The URL of the server-sent events, which reload the documentation.
+const EVENTS: &str = "/_wrpc/events";
+
+/// The URL to read and change the faults of the mock server.
+const CHAOS: &str = "/_wrpc/chaos";
+
+/// The URL to read and reset the values of CRUD services.
+const STORE: &str = "/_wrpc/store";
+
+/// How the server is reached.
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub host: String,
+    pub port: u16,
+    /// A prefix of all routes, like `/api`.
+    pub base_path: String,
+    /// The origins allowed to call the server from a browser, or `*`
+    /// for any origin.
+    pub cors_origins: Vec<String>,
+    /// Open the documentation in the browser, once the server started.
+    pub open: bool,
+    /// The faults of the mock server at the start.
+    pub chaos: Chaos,
+    /// Traffic recorded by `wrpc proxy`, which is answered with the
+    /// recorded responses.
+    pub replay: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            host: "127.0.0.1".to_string(),
+            port: 3000,
+            base_path: "".to_string(),
+            cors_origins: vec![],
+            open: false,
+            chaos: Chaos::default(),
+            replay: None,
+        }
+    }
+}
+
+impl Options {
+    /// Returns the base path with a leading, but without a trailing
+    /// slash, or an empty string for the root.
+    fn base_path(&self) -> String {
+        match self.base_path.trim_matches('/') {
+            "" => "".to_string(),
+            path => format!("/{path}"),
+        }
+    }
+
+    fn cors(&self) -> Result<Option<CorsLayer>, io::Error> {
+        if self.cors_origins.is_empty() {
+            return Ok(None);
+        }
+
+        let origin = match self.cors_origins.iter().any(|origin| origin == "*") {
+            true => AllowOrigin::any(),
+            false => {
+                let origins = self
+                    .cors_origins
+                    .iter()
+                    .map(|origin| {
+                        HeaderValue::from_str(origin).map_err(|_| {
+                            let message = format!("`{origin}` is not a valid CORS origin");
+                            io::Error::new(io::ErrorKind::InvalidInput, message)
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                AllowOrigin::list(origins)
+            }
+        };
+
+        let layer = CorsLayer::new()
+            .allow_origin(origin)
+            .allow_methods([HttpMethod::GET, HttpMethod::POST])
+            .allow_headers(Any);
+        Ok(Some(layer))
+    }
+}
+
 #[derive(Debug, Clone)]
 struct AppState {
     file: Arc<PathBuf>,
+    /// The URL of the server-sent events, including the base path.
+    events: Arc<String>,
+    /// Receives a message, whenever the spec changes.
+    changes: broadcast::Sender<()>,
+    chaos: chaos::Shared,
+    store: Arc<Mutex<Store>>,
+    recordings: Arc<Recordings>,
 }
 
 #[derive(Debug)]
 enum Error {
     File(tokio::io::Error),
-    BadSyntax(),
+    /// The spec with the given source contains errors.
+    BadSyntax(String, compiler::error::Error),
+    UnknownMethod(String),
+    BadRequest(Vec<Violation>),
+    /// The faults of the mock server are invalid.
+    BadChaos(String),
 }
 
 impl IntoResponse for Error {
@@ -30,62 +150,292 @@ impl IntoResponse for Error {
             Error::File(error) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, format!("{error:?}")).into_response()
             }
-            Error::BadSyntax() => (
+            Error::BadSyntax(_, _) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Bad Syntax, check your server output"),
             )
                 .into_response(),
+            Error::UnknownMethod(path) => (
+                StatusCode::NOT_FOUND,
+                format!("There is no method {path} in the spec"),
+            )
+                .into_response(),
+            Error::BadChaos(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+            Error::BadRequest(violations) => {
+                let body = json!({ "errors": violations });
+                (StatusCode::BAD_REQUEST, Json(body)).into_response()
+            }
         }
     }
 }
 
-pub async fn run(file: PathBuf) {
+/// Serve the documentation and a mock server of the given spec,
+/// until the process is stopped. Returns an error, if the server
+/// cannot be started.
+pub async fn run(file: PathBuf, options: Options) -> Result<(), io::Error> {
     //dotenv().expect("There should be a .env file");
+    init_tracing();
+
+    let changes = match reload::watch(&file) {
+        Ok(changes) => changes,
+        Err(error) => {
+            tracing::warn!(
+                "Cannot watch {}, pages will not reload: {error}",
+                file.display()
+            );
+            broadcast::channel(1).0
+        }
+    };
+
+    options.chaos.validate().map_err(|message| {
+        let message = format!("The faults of the mock server are invalid, {message}");
+        io::Error::new(io::ErrorKind::InvalidInput, message)
+    })?;
+    warn(&file);
+    let chaos = Arc::new(RwLock::new(options.chaos.clone()));
+    let recordings = match &options.replay {
+        Some(path) => Recordings::load(path)?,
+        None => Recordings::default(),
+    };
+
+    let base_path = options.base_path();
+    let state = AppState {
+        file: Arc::new(file.clone()),
+        events: Arc::new(format!("{base_path}{EVENTS}")),
+        changes,
+        chaos: chaos.clone(),
+        store: Arc::new(Mutex::new(Store::default())),
+        recordings: Arc::new(recordings),
+    };
+
+    // build our application with a route
+    let mut app = Router::new()
+        .route("/", get(index))
+        .route(EVENTS, get(events))
+        .route(CHAOS, get(get_chaos).post(set_chaos).delete(reset_chaos))
+        .route(STORE, get(get_store).delete(reset_store))
+        .route("/:service/:method", post(call))
+        .layer(ChaosLayer::new(chaos))
+        .with_state(state);
+    if !base_path.is_empty() {
+        app = Router::new().nest(&base_path, app);
+    }
+    if let Some(cors) = options.cors()? {
+        app = app.layer(cors);
+    }
+    let app = app
+        .layer(TraceLayer::new_for_http())
+        .layer(CompressionLayer::new());
+
+    // run it
+    let listener = bind(&options).await?;
+    let url = url(&options, &listener)?;
+    println!("Serving {} at {url}", file.display());
+    if options.open {
+        open(&url);
+    }
 
+    axum::serve(listener, app).await
+}
+
+fn init_tracing() {
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| {
                 // axum logs rejections from built-in extractors with the `axum::rejection`
                 // target, at `TRACE` level. `axum::rejection=trace` enables showing those events
-                "wrpc=debug,tower_http=debug,axum::rejection=trace".into()
+                "wrpc=debug,server=debug,tower_http=debug,axum::rejection=trace".into()
             }),
         )
         .with(tracing_subscriber::fmt::layer())
         .init();
+}
 
-    let state = AppState {
-        file: Arc::new(file.clone()),
+/// Listen on the host and port of the options.
+async fn bind(options: &Options) -> Result<TcpListener, io::Error> {
+    let address = format!("{}:{}", options.host, options.port);
+    TcpListener::bind(&address).await.map_err(|error| {
+        let hint = match error.kind() {
+            io::ErrorKind::AddrInUse => ", choose another one with --port",
+            _ => "",
+        };
+        io::Error::new(
+            error.kind(),
+            format!("Cannot listen on {address}: {error}{hint}"),
+        )
+    })
+}
+
+/// Returns the URL of the root, that can be opened in a browser.
+fn url(options: &Options, listener: &TcpListener) -> Result<String, io::Error> {
+    let host = match options.host.as_str() {
+        "0.0.0.0" | "::" => "localhost",
+        host => host,
+    };
+    // The documentation of a nested router is only served without a trailing slash.
+    let base_path = options.base_path();
+    let root = match base_path.as_str() {
+        "" => "/",
+        path => path,
     };
+    Ok(format!(
+        "http://{host}:{}{root}",
+        listener.local_addr()?.port()
+    ))
+}
 
-    // build our application with a route
-    let app = Router::new()
-        .route("/", get(index))
-        .layer(TraceLayer::new_for_http())
-        .layer(CompressionLayer::new())
-        .with_state(state);
+/// Open the URL in the default browser, without waiting for it.
+fn open(url: &str) {
+    let command = if cfg!(target_os = "macos") {
+        process::Command::new("open").arg(url).spawn()
+    } else if cfg!(target_os = "windows") {
+        process::Command::new("cmd")
+            .args(["/C", "start", "", url])
+            .spawn()
+    } else {
+        process::Command::new("xdg-open").arg(url).spawn()
+    };
 
-    // run it
-    let listener = TcpListener::bind("127.0.0.1:3000").await.unwrap();
-    tracing::debug!(
-        "✅ Server started, listening on http://localhost:{}",
-        listener.local_addr().unwrap()
-    );
-    axum::serve(listener, app).await.unwrap();
-}
-
-async fn index(State(state): State<AppState>) -> Result<Html<String>, Error> {
-    let file = &*state.file;
-    let result = tokio::fs::read_to_string(file).await.map_err(Error::File)?;
-    let str = result.as_str();
-    match compiler::compile(Some(file.clone()), str) {
-        Ok(module) => {
-            let result = render(&module);
-            Ok(Html(result))
+    if let Err(error) = command {
+        tracing::warn!("Cannot open {url} in the browser: {error}");
+    }
+}
+
+/// Show the documentation or, if the spec contains errors, all
+/// diagnostics. Either page reloads, whenever the spec changes.
+async fn index(State(state): State<AppState>) -> Result<Response, Error> {
+    match load(&state.file).await {
+        Ok(module) => Ok(Html(docs::render_live(&module, Some(&state.events))).into_response()),
+        Err(Error::BadSyntax(source, error)) => {
+            let page = docs::render_errors(&state.file, &source, &error, Some(&state.events));
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, Html(page)).into_response())
         }
+        Err(error) => Err(error),
+    }
+}
 
-        Err(error) => {
-            print_errors(file, str, error);
-            Err(Error::BadSyntax())
+async fn events(State(state): State<AppState>) -> impl IntoResponse {
+    reload::events(&state.changes)
+}
+
+async fn get_chaos(State(state): State<AppState>) -> Json<Chaos> {
+    let chaos = state
+        .chaos
+        .read()
+        .unwrap_or_else(|error| error.into_inner());
+    Json(chaos.clone())
+}
+
+/// Replace all faults of the mock server.
+async fn set_chaos(
+    State(state): State<AppState>,
+    Json(chaos): Json<Chaos>,
+) -> Result<Json<Chaos>, Error> {
+    chaos.validate().map_err(Error::BadChaos)?;
+    let mut current = state
+        .chaos
+        .write()
+        .unwrap_or_else(|error| error.into_inner());
+    *current = chaos.clone();
+    Ok(Json(chaos))
+}
+
+/// Remove all faults of the mock server.
+async fn reset_chaos(State(state): State<AppState>) -> Json<Chaos> {
+    let mut current = state
+        .chaos
+        .write()
+        .unwrap_or_else(|error| error.into_inner());
+    *current = Chaos::default();
+    Json(Chaos::default())
+}
+
+async fn get_store(State(state): State<AppState>) -> Json<Value> {
+    let store = state
+        .store
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+    Json(store.values())
+}
+
+/// Remove all values of CRUD services.
+async fn reset_store(State(state): State<AppState>) -> StatusCode {
+    let mut store = state
+        .store
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+    store.reset();
+    StatusCode::NO_CONTENT
+}
+
+/// Answer a call of a method in the spec with stored or generated data.
+async fn call(
+    State(state): State<AppState>,
+    Path((service, method)): Path<(String, String)>,
+    body: Bytes,
+) -> Result<Response, Error> {
+    let module = load(&state.file).await?;
+    let method = module
+        .get_method(service.as_str(), method.as_str())
+        .ok_or_else(|| Error::UnknownMethod(format!("/{service}/{method}")))?;
+
+    let body = match body.is_empty() {
+        true => json!({}),
+        false => serde_json::from_slice(&body).map_err(|error| {
+            Error::BadRequest(vec![Violation {
+                path: "".to_string(),
+                constraint: "JSON".to_string(),
+                message: format!("The body is not valid JSON: {error}"),
+            }])
+        })?,
+    };
+    mock::validate_request(&module, method, &body).map_err(Error::BadRequest)?;
+
+    // The same request is answered with the same data.
+    let mut hasher = DefaultHasher::new();
+    (&service, &method.name.value, body.to_string()).hash(&mut hasher);
+    let seed = hasher.finish();
+
+    if let Some(recording) = state.recordings.find(&service, &method.name.value, &body) {
+        let status = StatusCode::from_u16(recording.status).unwrap_or(StatusCode::OK);
+        return Ok((status, Json(recording.response.clone())).into_response());
+    }
+
+    let service = &module.services[&service];
+    let mut store = state
+        .store
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+    let stored = store.call(&module, service, method, &body, seed);
+    let value = stored.unwrap_or_else(|| mock::respond(&module, method, seed));
+    Ok(Json(value).into_response())
+}
+
+/// Print the warnings of the spec once, like links to missing
+/// declarations. Errors are reported on every request instead.
+fn warn(file: &PathBuf) {
+    let Ok(source) = fs::read_to_string(file) else {
+        return;
+    };
+
+    if let Ok((_, warnings)) = compiler::compile_with_warnings(Some(file.clone()), &source) {
+        if !warnings.is_empty() {
+            println!(
+                "{}",
+                diagnostic::render_warnings(file, &source, &warnings, MessageFormat::Human)
+            );
         }
     }
 }
+
+/// Read and compile the spec again, so changes are visible without a restart.
+async fn load(file: &PathBuf) -> Result<Module, Error> {
+    let source = tokio::fs::read_to_string(file).await.map_err(Error::File)?;
+    compiler::compile(Some(file.clone()), &source).map_err(|error| {
+        println!(
+            "{}",
+            diagnostic::render(file, &source, &error, MessageFormat::Human)
+        );
+        Error::BadSyntax(source.clone(), error)
+    })
+}
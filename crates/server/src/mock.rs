@@ -0,0 +1,196 @@
+//! Answer RPC calls with data generated from the spec, so clients can
+//! be developed before the actual server exists.
+//!
+//! All values follow the wire format described in the README, e.g.
+//! enums with data use an `@type` discriminator and a `Map` is a list
+//! of pairs. Examples of the spec are preferred over generated values.
+use compiler::ast::canonical::{Method, Module};
+use compiler::random::Generator;
+use compiler::value::{self, Violation};
+use serde_json::Value;
+
+/// Validate the given request body against the parameters of the
+/// method. Missing parameters are `null`.
+///
+/// All values have to match their types first, before the
+/// constraints of parameters and records are checked.
+pub fn validate_request(
+    module: &Module,
+    method: &Method,
+    body: &Value,
+) -> Result<(), Vec<Violation>> {
+    let Value::Object(params) = body else {
+        let mismatch = value::mismatch("", "an object with the parameters", body);
+        return Err(vec![mismatch.into()]);
+    };
+
+    let mismatches = method
+        .parameters
+        .iter()
+        .filter_map(|param| {
+            let value = params.get(&param.name.value).unwrap_or(&Value::Null);
+            let path = value::pointer("", &param.name.value);
+            value::check(module, &param.type_, value, &path).err()
+        })
+        .map(Violation::from)
+        .collect::<Vec<_>>();
+    if !mismatches.is_empty() {
+        return Err(mismatches);
+    }
+
+    let mut violations = vec![];
+    for param in &method.parameters {
+        let name = &param.name.value;
+        let value = params.get(name).unwrap_or(&Value::Null);
+        let path = value::pointer("", name);
+        violations.extend(value::validate(module, &param.type_, value, &path));
+        if !value.is_null() {
+            value::validate_constraints(name, &param.constraints, params, &path, &mut violations);
+        }
+    }
+
+    match violations.is_empty() {
+        true => Ok(()),
+        false => Err(violations),
+    }
+}
+
+/// Answer a call of the method with one of its examples, or with a
+/// random value of its return type. The value is `null`, if the
+/// method has no return type.
+///
+/// Random values only depend on the `seed`, so the same request can
+/// be answered with the same value.
+pub fn respond(module: &Module, method: &Method, seed: u64) -> Value {
+    let mut generator = Generator::new(module, seed).with_examples();
+    match (method.examples.as_slice(), &method.return_type) {
+        ([], Some(type_)) => generator.generate(type_),
+        ([], None) => Value::Null,
+        (examples, _) => examples[seed as usize % examples.len()].clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use compiler::ast::canonical::{Module, Type};
+    use serde_json::json;
+
+    use compiler::value::{check, Violation};
+
+    use compiler::random::Generator;
+
+    use crate::mock::{respond, validate_request};
+
+    const SPEC: &str = r#"
+data Person {
+    #(example "Ada")
+    name: String,
+    age: Int32?,
+    tags: List<String>,
+    role: Role,
+    friends: List<Person>,
+    boxed: Box<Int32>,
+}
+
+data Box<A> {
+    value: A,
+}
+
+#(example {:radius 2})
+data Circle {
+    radius: Float64,
+}
+
+enum Role {
+    Admin,
+    User,
+}
+
+enum Shape {
+    Circle {
+        #(check (> .radius 0))
+        radius: Float64,
+    },
+    Square { length: Float64 },
+}
+
+service PersonService {
+    def get(#(check (> .id 0)) id: Int64, shape: Shape?): Result<String, Person>
+
+    #(example ({:radius 1} {:radius 3}))
+    def circles(): List<Circle>
+
+    def other(): Circle
+}
+"#;
+
+    fn module() -> Module {
+        compiler::compile(None, SPEC).unwrap()
+    }
+
+    #[test]
+    fn test_generated_values_match_their_type() {
+        let module = module();
+        let method = module.get_method("PersonService", "get").unwrap();
+        for seed in 0..5 {
+            let type_ = method.return_type.as_ref().unwrap();
+            let value = Generator::new(&module, seed).generate(type_);
+            assert_eq!(check(&module, type_, &value, ""), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_examples_are_preferred() {
+        let module = module();
+        let mut generator = Generator::new(&module, 0).with_examples();
+
+        let value = generator.generate(&Type::Ref("Person".into(), vec![]));
+        assert_eq!(value["name"], json!("Ada"));
+
+        let method = module.get_method("PersonService", "other").unwrap();
+        assert_eq!(respond(&module, method, 7), json!({"radius": 2}));
+
+        let method = module.get_method("PersonService", "circles").unwrap();
+        assert_eq!(
+            respond(&module, method, 7),
+            json!([{"radius": 1}, {"radius": 3}])
+        );
+    }
+
+    #[test]
+    fn test_validate_request_reports_all_violations() {
+        let module = module();
+        let method = module.get_method("PersonService", "get").unwrap();
+        let body = json!({"id": 1, "shape": {"@type": "Circle", "radius": 1.5}});
+        assert_eq!(validate_request(&module, method, &body), Ok(()));
+
+        let body = json!({"shape": {"@type": "Circle", "radius": "large"}});
+        let expected = vec![
+            Violation {
+                path: "/id".to_string(),
+                constraint: "Int64".to_string(),
+                message: "Expected Int64, but found null.".to_string(),
+            },
+            Violation {
+                path: "/shape/radius".to_string(),
+                constraint: "Float64".to_string(),
+                message: "Expected Float64, but found a string.".to_string(),
+            },
+        ];
+        assert_eq!(validate_request(&module, method, &body), Err(expected));
+
+        let body = json!({"id": -1, "shape": {"@type": "Circle", "radius": 0}});
+        let violations = validate_request(&module, method, &body)
+            .unwrap_err()
+            .into_iter()
+            .map(|violation| (violation.path, violation.constraint))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            violations,
+            vec![
+                ("/id".to_string(), "(> .id 0)".to_string()),
+                ("/shape/radius".to_string(), "(> .radius 0)".to_string()),
+            ]
+        );
+    }
+}
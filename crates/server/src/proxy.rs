@@ -0,0 +1,336 @@
+//! Forward calls to a real server and check, that requests and
+//! responses follow the spec.
+//!
+//! Every violation is logged with a JSON pointer to the offending
+//! value, but requests and responses are forwarded unchanged. Calls
+//! can be recorded, so the mock server can replay them later, see
+//! [`recording`](crate::recording).
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use axum::body::{self, Body, Bytes};
+use axum::extract::{Request, State};
+use axum::http::{header, request, HeaderValue, Method as HttpMethod, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use compiler::ast::canonical::{Method, Module};
+use compiler::value::{self, Violation};
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use serde_json::{json, Value};
+use tower_http::trace::TraceLayer;
+
+use crate::recording::{Recorder, Recording};
+use crate::{bind, init_tracing, load, mock, url, Options};
+
+#[derive(Debug, Clone)]
+struct ProxyState {
+    file: Arc<PathBuf>,
+    /// The URL of the real server, without a trailing slash.
+    upstream: Arc<String>,
+    client: Client<HttpConnector, Body>,
+    recorder: Option<Arc<Mutex<Recorder>>>,
+}
+
+/// Forward all calls to the server at the `upstream` URL and check
+/// them against the given spec, until the process is stopped. If
+/// `record` is given, all calls are appended to this file.
+pub async fn run(
+    file: PathBuf,
+    upstream: String,
+    options: Options,
+    record: Option<PathBuf>,
+) -> Result<(), io::Error> {
+    init_tracing();
+
+    let uri = upstream.parse::<Uri>().ok();
+    if uri.as_ref().and_then(Uri::scheme_str) != Some("http") {
+        let message = format!("The upstream `{upstream}` should be an http:// URL");
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+    }
+
+    let recorder = match record {
+        Some(path) => Some(Arc::new(Mutex::new(Recorder::open(&path)?))),
+        None => None,
+    };
+
+    let state = ProxyState {
+        file: Arc::new(file.clone()),
+        upstream: Arc::new(upstream.trim_end_matches('/').to_string()),
+        client: Client::builder(TokioExecutor::new()).build_http(),
+        recorder,
+    };
+
+    let mut app = app(state);
+    if let Some(cors) = options.cors()? {
+        app = app.layer(cors);
+    }
+
+    let listener = bind(&options).await?;
+    let url = url(&options, &listener)?;
+    println!(
+        "Forwarding {url} to {upstream}, checking against {}",
+        file.display()
+    );
+    axum::serve(listener, app).await
+}
+
+fn app(state: ProxyState) -> Router {
+    Router::new()
+        .fallback(forward)
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
+async fn forward(State(state): State<ProxyState>, request: Request) -> Response {
+    let (parts, body) = request.into_parts();
+    let Ok(body) = body::to_bytes(body, usize::MAX).await else {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Cannot read the body of the request",
+        )
+            .into_response();
+    };
+
+    let call = rpc_call(&parts);
+    let module = match &call {
+        // Errors of the spec have already been printed.
+        Some(_) => load(&state.file).await.ok(),
+        None => None,
+    };
+    let method = match (&module, &call) {
+        (Some(module), Some((service, method))) => module.get_method(service, method),
+        _ => None,
+    };
+
+    let request = parse(&body, json!({}));
+    if let (Some(module), Some(method), Some((service, name))) = (&module, method, &call) {
+        let violations = match &request {
+            Ok(value) => mock::validate_request(module, method, value).err(),
+            Err(message) => Some(vec![invalid_json(message)]),
+        };
+        log(service, name, "request", &violations.unwrap_or_default());
+    }
+
+    let uri = match parts.uri.path_and_query() {
+        Some(path) => format!("{}{path}", state.upstream),
+        None => format!("{}/", state.upstream),
+    };
+    let mut upstream = request::Builder::new().method(parts.method).uri(uri);
+    for (name, value) in parts.headers.iter() {
+        if name != header::HOST && name != header::ACCEPT_ENCODING {
+            upstream = upstream.header(name, value);
+        }
+    }
+    // The response has to be readable, to check it.
+    upstream = upstream.header(
+        header::ACCEPT_ENCODING,
+        HeaderValue::from_static("identity"),
+    );
+
+    let response = match upstream.body(Body::from(body)) {
+        Ok(upstream) => state.client.request(upstream).await,
+        Err(error) => return (StatusCode::BAD_REQUEST, error.to_string()).into_response(),
+    };
+    let response = match response {
+        Ok(response) => response,
+        Err(error) => {
+            let message = format!("Cannot reach {}: {error}", state.upstream);
+            tracing::warn!("{message}");
+            return (StatusCode::BAD_GATEWAY, message).into_response();
+        }
+    };
+
+    let (mut parts, incoming) = response.into_parts();
+    let Ok(bytes) = body::to_bytes(Body::new(incoming), usize::MAX).await else {
+        let message = format!("Cannot read the response of {}", state.upstream);
+        return (StatusCode::BAD_GATEWAY, message).into_response();
+    };
+
+    if let (Some(module), Some(method), Some((service, name))) = (&module, method, &call) {
+        let response = parse(&bytes, Value::Null);
+        if parts.status.is_success() {
+            log(
+                service,
+                name,
+                "response",
+                &check_response(module, method, &response),
+            );
+        }
+
+        if let (Some(recorder), Ok(request), Ok(response)) = (&state.recorder, request, response) {
+            let recording = Recording {
+                service: service.clone(),
+                method: name.clone(),
+                request,
+                status: parts.status.as_u16(),
+                response,
+            };
+            let mut recorder = recorder.lock().unwrap_or_else(|error| error.into_inner());
+            if let Err(error) = recorder.record(&recording) {
+                tracing::warn!("Cannot record the call of {service}/{name}: {error}");
+            }
+        }
+    }
+
+    // The body has already been read completely.
+    parts.headers.remove(header::TRANSFER_ENCODING);
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// Returns the service and method of a call like `POST /Service/method`.
+fn rpc_call(parts: &request::Parts) -> Option<(String, String)> {
+    if parts.method != HttpMethod::POST {
+        return None;
+    }
+
+    let path = parts.uri.path().trim_start_matches('/');
+    match path.split('/').collect::<Vec<_>>().as_slice() {
+        [service, method] => Some((service.to_string(), method.to_string())),
+        _ => None,
+    }
+}
+
+/// Parse the body as JSON, where an empty body is the given value.
+fn parse(body: &Bytes, empty: Value) -> Result<Value, String> {
+    if body.is_empty() {
+        return Ok(empty);
+    }
+
+    serde_json::from_slice(body).map_err(|error| format!("The body is not valid JSON: {error}"))
+}
+
+fn invalid_json(message: &str) -> Violation {
+    Violation {
+        path: "".to_string(),
+        constraint: "JSON".to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// Check the body of a successful response against the return type of
+/// the method.
+fn check_response(
+    module: &Module,
+    method: &Method,
+    response: &Result<Value, String>,
+) -> Vec<Violation> {
+    match (&method.return_type, response) {
+        (None, _) => vec![],
+        (Some(_), Err(message)) => vec![invalid_json(message)],
+        (Some(type_), Ok(value)) => match value::check(module, type_, value, "") {
+            Ok(()) => value::validate(module, type_, value, ""),
+            Err(mismatch) => vec![mismatch.into()],
+        },
+    }
+}
+
+fn log(service: &str, method: &str, direction: &str, violations: &[Violation]) {
+    for violation in violations {
+        let path = match violation.path.as_str() {
+            "" => "/",
+            path => path,
+        };
+        tracing::warn!(
+            "{service}/{method} {direction} at {path}: {}",
+            violation.message
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+
+    use axum::body::{self, Body};
+    use axum::http::{Request, StatusCode};
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use hyper_util::client::legacy::Client;
+    use hyper_util::rt::TokioExecutor;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    use crate::proxy::{app, check_response, ProxyState};
+    use crate::recording::{Recorder, Recordings};
+
+    const SPEC: &str = r#"
+data Person {
+    id: Int64,
+    #(check (<= (len .name) 5))
+    name: String,
+}
+
+service PersonService {
+    def get(id: Int64): Person
+}
+"#;
+
+    #[tokio::test]
+    async fn test_calls_are_forwarded_and_recorded() {
+        let upstream = Router::new().route(
+            "/PersonService/get",
+            post(|Json(params): Json<Value>| async move {
+                Json(json!({"id": params["id"], "name": "Margaret"}))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, upstream).await });
+
+        let directory = std::env::temp_dir().join(format!("wrpc-proxy-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let file = directory.join("spec.wrpc");
+        fs::write(&file, SPEC).unwrap();
+        let record = directory.join("calls.ndjson");
+
+        let state = ProxyState {
+            file: Arc::new(file),
+            upstream: Arc::new(format!("http://{address}")),
+            client: Client::builder(TokioExecutor::new()).build_http(),
+            recorder: Some(Arc::new(Mutex::new(Recorder::open(&record).unwrap()))),
+        };
+        let request = Request::builder()
+            .method("POST")
+            .uri("/PersonService/get")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"id": 1}"#))
+            .unwrap();
+        let response = app(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body, json!({"id": 1, "name": "Margaret"}));
+
+        let recordings = Recordings::load(&record).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+        let recording = recordings.find("PersonService", "get", &json!({"id": 1}));
+        assert_eq!(recording.map(|recording| &recording.response), Some(&body));
+    }
+
+    #[test]
+    fn test_responses_are_checked() {
+        let module = compiler::compile(None, SPEC).unwrap();
+        let method = module.get_method("PersonService", "get").unwrap();
+
+        let valid = Ok(json!({"id": 1, "name": "Ada"}));
+        assert_eq!(check_response(&module, method, &valid), vec![]);
+
+        let invalid = Ok(json!({"id": 1, "name": "Margaret"}));
+        let violations = check_response(&module, method, &invalid);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, "/name");
+
+        let mismatch = Ok(json!({"id": "1", "name": "Ada"}));
+        let violations = check_response(&module, method, &mismatch);
+        assert_eq!(violations[0].path, "/id");
+
+        let malformed = Err("The body is not valid JSON".to_string());
+        assert_eq!(check_response(&module, method, &malformed).len(), 1);
+    }
+}
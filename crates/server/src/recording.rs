@@ -0,0 +1,117 @@
+//! Traffic recorded by `wrpc proxy`, which the mock server can
+//! replay later.
+//!
+//! A recording is a file with one JSON object per line and call:
+//!
+//! ```json
+//! {"service":"PersonService","method":"get","request":{"id":1},"status":200,"response":{"id":1,"name":"Ada"}}
+//! ```
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A single call of a method and its response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    pub service: String,
+    pub method: String,
+    pub request: Value,
+    pub status: u16,
+    pub response: Value,
+}
+
+/// All recordings of a file.
+#[derive(Debug, Default)]
+pub struct Recordings {
+    recordings: Vec<Recording>,
+}
+
+impl Recordings {
+    /// Read all recordings of the file at the given path.
+    pub fn load(path: &Path) -> Result<Recordings, io::Error> {
+        let content = fs::read_to_string(path)?;
+        let recordings = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|error| {
+                    let message = format!("{}:{}: {error}", path.display(), index + 1);
+                    io::Error::new(io::ErrorKind::InvalidData, message)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Recordings { recordings })
+    }
+
+    /// Returns the latest recording of a call of the method with the
+    /// same request.
+    pub fn find(&self, service: &str, method: &str, request: &Value) -> Option<&Recording> {
+        self.recordings.iter().rev().find(|recording| {
+            recording.service == service
+                && recording.method == method
+                && &recording.request == request
+        })
+    }
+}
+
+/// Appends recordings to a file.
+#[derive(Debug)]
+pub struct Recorder {
+    file: File,
+}
+
+impl Recorder {
+    /// Open the file at the given path, keeping all existing recordings.
+    pub fn open(path: &Path) -> Result<Recorder, io::Error> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Recorder { file })
+    }
+
+    pub fn record(&mut self, recording: &Recording) -> Result<(), io::Error> {
+        let line = serde_json::to_string(recording).map_err(io::Error::other)?;
+        writeln!(self.file, "{line}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::json;
+
+    use crate::recording::{Recorder, Recording, Recordings};
+
+    #[test]
+    fn test_recordings_are_replayed() {
+        let path = std::env::temp_dir().join(format!("wrpc-recording-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut recorder = Recorder::open(&path).unwrap();
+        for (name, status) in [("Ada", 200), ("Grace", 200), ("Grace", 404)] {
+            let recording = Recording {
+                service: "PersonService".to_string(),
+                method: "get".to_string(),
+                request: json!({"name": name}),
+                status,
+                response: json!({"name": name}),
+            };
+            recorder.record(&recording).unwrap();
+        }
+
+        let recordings = Recordings::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let found = recordings.find("PersonService", "get", &json!({"name": "Grace"}));
+        assert_eq!(found.map(|recording| recording.status), Some(404));
+        let found = recordings.find("PersonService", "get", &json!({"name": "Ada"}));
+        assert_eq!(found.map(|recording| recording.status), Some(200));
+        assert_eq!(
+            recordings.find("PersonService", "insert", &json!({"name": "Ada"})),
+            None
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Reload open documentation pages, whenever the spec changes.
+//!
+//! Pages subscribe to [server-sent events] and reload themselves on
+//! every `reload` event.
+//!
+//! [server-sent events]: https://developer.mozilla.org/en-US/docs/Web/API/Server-sent_events
+use std::convert::Infallible;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use std::{fs, io, thread};
+
+use axum::response::sse::{Event, KeepAlive, Sse};
+use notify::{EventKind, RecursiveMode, Watcher};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+
+/// Editors often write a file in multiple steps, which are
+/// collected into a single change.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Watch the given file and send a message, whenever it changes.
+///
+/// The file is watched until the program exits.
+pub fn watch(file: &Path) -> Result<broadcast::Sender<()>, io::Error> {
+    let file = fs::canonicalize(file)?;
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(io::Error::other)?;
+
+    // Watch the directory instead of the file, since many editors
+    // replace a file instead of writing to it.
+    let directory = file.parent().unwrap_or(Path::new("/"));
+    watcher
+        .watch(directory, RecursiveMode::NonRecursive)
+        .map_err(io::Error::other)?;
+
+    let (changes, _) = broadcast::channel(16);
+    let sender = changes.clone();
+    thread::spawn(move || {
+        let _watcher = watcher;
+        while let Ok(event) = receiver.recv() {
+            if is_relevant(&file, event) {
+                while receiver.recv_timeout(DEBOUNCE).is_ok() {}
+                // Without any open page, there is nobody to notify.
+                let _ = sender.send(());
+            }
+        }
+    });
+
+    Ok(changes)
+}
+
+fn is_relevant(file: &PathBuf, event: notify::Result<notify::Event>) -> bool {
+    match event {
+        Ok(event) => {
+            !matches!(event.kind, EventKind::Access(_))
+                && event.paths.iter().any(|path| path == file)
+        }
+        Err(_) => false,
+    }
+}
+
+/// Send a `reload` event for every change of the spec.
+pub fn events(
+    changes: &broadcast::Sender<()>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    // A lagging receiver missed some changes, but should reload all the same.
+    let stream = BroadcastStream::new(changes.subscribe())
+        .map(|_| Ok(Event::default().event("reload").data("")));
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::Duration;
+
+    use crate::reload::watch;
+
+    #[tokio::test]
+    async fn test_changes_are_sent() {
+        let directory = std::env::temp_dir().join(format!("wrpc-reload-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let file = directory.join("spec.wrpc");
+        fs::write(&file, "data Person {}").unwrap();
+
+        let mut changes = watch(&file).unwrap().subscribe();
+        fs::write(directory.join("other.wrpc"), "data Other {}").unwrap();
+        fs::write(&file, "data Person { name: String }").unwrap();
+
+        let change = tokio::time::timeout(Duration::from_secs(5), changes.recv()).await;
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(change.unwrap(), Ok(()));
+    }
+}
@@ -0,0 +1,291 @@
+//! Keep the values of services with a `#(mock :crud Record)`
+//! annotation in memory, so a value returned by `insert` can be found
+//! with `get` afterwards.
+//!
+//! Methods are recognized by their name:
+//!
+//! - `get(id)` returns the stored value or, if there is none, `null`
+//!   or an `Err`, depending on the return type.
+//! - `insert(data)` and `create(data)` store a new value with a fresh
+//!   `id`, taking all properties of the record from `data`.
+//! - `update(data)` changes the value with the `id` in `data` or a
+//!   parameter `id`.
+//! - `delete(id)` removes the value.
+//! - `list()` and `all()` return all values.
+//!
+//! All other methods are answered with random values.
+use std::collections::{BTreeMap, HashMap};
+
+use compiler::ast::canonical::{Method, Module, Record, Service, Type};
+use compiler::random::Generator;
+use compiler::value;
+use serde_json::{json, Map, Value};
+
+/// The stored values of all services.
+#[derive(Debug, Default)]
+pub struct Store {
+    tables: HashMap<String, Table>,
+}
+
+#[derive(Debug, Default)]
+struct Table {
+    /// The values by their `id` as JSON.
+    values: BTreeMap<String, Value>,
+    last_id: i64,
+}
+
+impl Store {
+    /// Answer a call of a method of a service with a `#(mock :crud
+    /// Record)` annotation. Returns `None`, if the method should be
+    /// answered with random values instead.
+    pub fn call(
+        &mut self,
+        module: &Module,
+        service: &Service,
+        method: &Method,
+        params: &Value,
+        seed: u64,
+    ) -> Option<Value> {
+        let record = module.records.get(service.crud.as_ref()?)?;
+        let table = self.tables.entry(service.name.value.clone()).or_default();
+        let mut generator = Generator::new(module, seed);
+        let data = data(module, &record.name.value, method, params);
+        let found = match method.name.value.as_str() {
+            "get" => table.values.get(&key(find_id(params, data)?)).cloned(),
+            "insert" | "create" => {
+                let mut value = generator.generate(&record.as_type());
+                merge(module, record, &mut value, data?);
+                table.last_id += 1;
+                let id = match value.get("id") {
+                    Some(Value::String(_)) => json!(table.last_id.to_string()),
+                    _ => json!(table.last_id),
+                };
+                value["id"] = id.clone();
+                table.values.insert(key(&id), value.clone());
+                Some(value)
+            }
+            "update" => {
+                let id = key(find_id(params, data)?);
+                table.values.get_mut(&id).map(|value| {
+                    if let Some(data) = data {
+                        merge(module, record, value, data);
+                    }
+                    value.clone()
+                })
+            }
+            "delete" => table.values.remove(&key(find_id(params, data)?)),
+            "list" | "all" => {
+                let values = table.values.values().cloned().collect::<Vec<_>>();
+                return match &method.return_type {
+                    Some(Type::List(_) | Type::Set(_)) => Some(Value::Array(values)),
+                    _ => None,
+                };
+            }
+            _ => return None,
+        };
+
+        match &method.return_type {
+            Some(type_) => shape(&mut generator, &record.name.value, type_, found),
+            None => Some(Value::Null),
+        }
+    }
+
+    /// Returns all stored values by service.
+    pub fn values(&self) -> Value {
+        self.tables
+            .iter()
+            .map(|(service, table)| {
+                let values = table.values.values().cloned().collect();
+                (service.clone(), Value::Array(values))
+            })
+            .collect::<Map<String, Value>>()
+            .into()
+    }
+
+    /// Remove all stored values.
+    pub fn reset(&mut self) {
+        self.tables.clear();
+    }
+}
+
+/// Returns the `id` parameter or the `id` of the data.
+fn find_id<'a>(params: &'a Value, data: Option<&'a Map<String, Value>>) -> Option<&'a Value> {
+    params
+        .get("id")
+        .filter(|id| !id.is_null())
+        .or_else(|| data?.get("id"))
+}
+
+/// Returns the parameter declared as the given record or, if there
+/// is none, as any other record, like the `data` of `insert(data:
+/// PersonData)`.
+fn data<'a>(
+    module: &Module,
+    record: &str,
+    method: &Method,
+    params: &'a Value,
+) -> Option<&'a Map<String, Value>> {
+    let declared_as = |matches: &dyn Fn(&str) -> bool| {
+        method
+            .parameters
+            .iter()
+            .find(|parameter| match &parameter.type_ {
+                Type::Ref(name, _) => matches(name),
+                _ => false,
+            })
+    };
+
+    let parameter = declared_as(&|name| name == record)
+        .or_else(|| declared_as(&|name| module.records.contains_key(name)))?;
+    params.get(&parameter.name.value)?.as_object()
+}
+
+fn key(id: &Value) -> String {
+    id.to_string()
+}
+
+/// Copy all properties of the data, which are valid properties of the
+/// record, except for its `id`.
+fn merge(module: &Module, record: &Record, value: &mut Value, data: &Map<String, Value>) {
+    for property in &record.properties {
+        let name = &property.name.value;
+        match data.get(name) {
+            Some(new) if name != "id" && value::check(module, &property.type_, new, "").is_ok() => {
+                value[name] = new.clone();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Put the found value of the record into the return type, e.g.
+/// `null` for an `Option` or an `Err` for a `Result`, if nothing was
+/// found.
+fn shape(
+    generator: &mut Generator,
+    record: &str,
+    type_: &Type,
+    found: Option<Value>,
+) -> Option<Value> {
+    match type_ {
+        Type::Ref(name, _) if name == record => found,
+        Type::Option(type_) => Some(shape(generator, record, type_, found).unwrap_or(Value::Null)),
+        Type::Result(error, type_) => match shape(generator, record, type_, found) {
+            Some(value) => Some(json!({"@type": "Ok", "value": value})),
+            None => Some(json!({"@type": "Err", "error": generator.generate(error)})),
+        },
+        Type::Boolean => Some(Value::Bool(found.is_some())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use compiler::ast::canonical::Module;
+    use compiler::diagnostic;
+    use serde_json::{json, Value};
+
+    use crate::store::Store;
+
+    const SPEC: &str = r#"
+data Person {
+    id: Int64,
+    name: String,
+    age: Int32?,
+}
+
+data PersonData {
+    name: String,
+    age: Int32?,
+}
+
+data Audit {
+    name: String,
+}
+
+enum Error {
+    NotFound,
+}
+
+#(mock :crud Person)
+service PersonService {
+    def get(id: Int64): Person?
+    def insert(data: PersonData): Result<Error, Person>
+    def create(audit: Audit, person: Person): Person
+    def update(id: Int64, data: PersonData): Result<Error, Person>
+    def delete(id: Int64)
+    def list(): List<Person>
+    def count(): Int32
+}
+"#;
+
+    fn call(store: &mut Store, module: &Module, method: &str, params: Value) -> Option<Value> {
+        let service = &module.services["PersonService"];
+        let method = &service.methods[method];
+        store.call(module, service, method, &params, 0)
+    }
+
+    #[test]
+    fn test_values_are_kept_across_calls() {
+        let module = compiler::compile(None, SPEC).unwrap();
+        let mut store = Store::default();
+
+        let inserted = call(
+            &mut store,
+            &module,
+            "insert",
+            json!({"data": {"name": "Ada"}}),
+        );
+        let inserted = inserted.unwrap();
+        assert_eq!(inserted["@type"], "Ok");
+        assert_eq!(inserted["value"]["id"], 1);
+        assert_eq!(inserted["value"]["name"], "Ada");
+
+        let found = call(&mut store, &module, "get", json!({"id": 1}));
+        assert_eq!(found, Some(inserted["value"].clone()));
+
+        let data = json!({"id": 1, "data": {"name": "Grace", "age": 45}});
+        let updated = call(&mut store, &module, "update", data).unwrap();
+        assert_eq!(updated["value"]["name"], "Grace");
+        assert_eq!(updated["value"]["age"], 45);
+
+        let listed = call(&mut store, &module, "list", json!({})).unwrap();
+        assert_eq!(listed, json!([updated["value"]]));
+
+        assert_eq!(call(&mut store, &module, "count", json!({})), None);
+        assert_eq!(
+            call(&mut store, &module, "delete", json!({"id": 1})),
+            Some(Value::Null)
+        );
+        assert_eq!(
+            call(&mut store, &module, "get", json!({"id": 1})),
+            Some(Value::Null)
+        );
+
+        let data = json!({"id": 1, "data": {"name": "Grace"}});
+        let missing = call(&mut store, &module, "update", data).unwrap();
+        assert_eq!(missing, json!({"@type": "Err", "error": "NotFound"}));
+    }
+
+    #[test]
+    fn test_data_is_the_parameter_of_the_record() {
+        let module = compiler::compile(None, SPEC).unwrap();
+        let mut store = Store::default();
+
+        let params = json!({"audit": {"name": "Admin"}, "person": {"name": "Ada"}});
+        let created = call(&mut store, &module, "create", params).unwrap();
+        assert_eq!(created["name"], "Ada");
+    }
+
+    #[test]
+    fn test_mock_needs_a_record_with_an_id() {
+        let spec = "data PersonData { name: String }\n\
+                    #(mock :crud PersonData)\n\
+                    service PersonService { def get(): PersonData }";
+        let error = compiler::compile(None, spec).unwrap_err();
+        let diagnostics = diagnostic::from_error(&error);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "E0204");
+        assert!(diagnostics[0].message.contains("needs an `id`"));
+    }
+}